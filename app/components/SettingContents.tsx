@@ -15,6 +15,8 @@ export function ConfigContents({ config, onProviderChange }: ConfigContentsProps
       return aiServiceConfig.ollama;
     case 'azure':
       return aiServiceConfig.azure;
+    case 'openai':
+      return aiServiceConfig.openai;
     case 'deepseek':
       return aiServiceConfig.deepseek;
   }})();
@@ -31,6 +33,7 @@ export function ConfigContents({ config, onProviderChange }: ConfigContentsProps
         >
           <option value="ollama">Ollama</option>
           <option value="azure">Azure</option>
+          <option value="openai">OpenAI</option>
           <option value="deepseek">DeepSeek</option>
         </select>
       </div>
@@ -33,6 +33,7 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
 
   const handleProviderSelect = (provider: AIServiceConfig['provider']) => {
     setConfig(prev => ({
+      ...prev,
       'ai-service': {
         ...prev['ai-service'],
         provider
@@ -43,6 +44,7 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
   const handleConfigChange = (provider: AIServiceConfig['provider'], key: string, value: string) => {
     const canonicalizedProvider = canonicalizeProvider(provider);
     setConfig(prev => ({
+      ...prev,
       'ai-service': {
         ...prev['ai-service'],
         [canonicalizedProvider]: {
@@ -57,6 +59,7 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
     { id: 'general', label: 'General' },
     { id: 'ollama', label: 'Ollama' },
     { id: 'azure', label: 'Azure' },
+    { id: 'openai', label: 'OpenAI' },
     { id: 'deepseek', label: 'DeepSeek' },
     { id: 'azure-deepseek', label: 'Azure DeepSeek' },
   ];
@@ -74,8 +77,10 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
             >
               <option value="ollama">Ollama</option>
               <option value="azure">Azure</option>
+              <option value="openai">OpenAI</option>
               <option value="deepseek">DeepSeek</option>
               <option value="azure-deepseek">Azure DeepSeek</option>
+              <option value="mock">Mock (scripted)</option>
             </select>
           </SettingItem>
         </div>
@@ -104,6 +109,15 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
                 className="w-full p-2 border rounded"
               />
             </SettingItem>
+            <SettingItem label="Embedding Model">
+              <input
+                type="text"
+                value={config['ai-service'].ollama?.embeddingModel || ''}
+                onChange={e => handleConfigChange('ollama', 'embeddingModel', e.target.value)}
+                placeholder="nomic-embed-text"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
           </div>
         );
       case 'azure':
@@ -136,6 +150,56 @@ export function SettingPanel({ initConfig, onSave }: SettingPanelProps) {
                 className="w-full p-2 border rounded"
               />
             </SettingItem>
+            <SettingItem label="Embedding Model">
+              <input
+                type="text"
+                value={config['ai-service'].azure?.embeddingModel || ''}
+                onChange={e => handleConfigChange('azure', 'embeddingModel', e.target.value)}
+                placeholder="text-embedding-3-small"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
+          </div>
+        );
+      case 'openai':
+        return (
+          <div className="flex flex-col gap-6">
+            <SettingItem label="Base URL">
+              <input
+                type="text"
+                value={config['ai-service'].openai?.baseUrl || ''}
+                onChange={e => handleConfigChange('openai', 'baseUrl', e.target.value)}
+                placeholder="https://api.openai.com/v1"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
+            <SettingItem label="API Key">
+              <input
+                type="password"
+                value={config['ai-service'].openai?.apiKey || ''}
+                onChange={e => handleConfigChange('openai', 'apiKey', e.target.value)}
+                placeholder="Enter your API key"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
+            <SettingItem label="Model">
+              <input
+                type="text"
+                value={config['ai-service'].openai?.model || ''}
+                onChange={e => handleConfigChange('openai', 'model', e.target.value)}
+                placeholder="gpt-4o-mini"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
+            <SettingItem label="Embedding Model">
+              <input
+                type="text"
+                value={config['ai-service'].openai?.embeddingModel || ''}
+                onChange={e => handleConfigChange('openai', 'embeddingModel', e.target.value)}
+                placeholder="text-embedding-3-small"
+                className="w-full p-2 border rounded"
+              />
+            </SettingItem>
           </div>
         );
       case 'deepseek':
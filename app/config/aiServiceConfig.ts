@@ -1,12 +1,21 @@
 export interface OllamaConfig {
   baseUrl: string;
   model: string;
+  embeddingModel?: string;
 }
 
 export interface AzureConfig {
   apiKey: string;
   model: string;
   baseUrl: string;
+  embeddingModel?: string;
+}
+
+export interface OpenAIConfig {
+  baseUrl: string;
+  apiKey: string;
+  model: string;
+  embeddingModel?: string;
 }
 
 export interface DeepSeekConfig {
@@ -19,11 +28,18 @@ export interface AzureDeepSeekConfig {
   baseUrl: string;
 }
 
+export interface MockConfig {
+  script: string;
+}
+
 export type AIServiceConfig = {
-  provider: 'ollama' | 'azure' | 'deepseek' | 'azure-deepseek';
+  provider: 'ollama' | 'azure' | 'openai' | 'deepseek' | 'azure-deepseek' | 'mock';
+  embeddingProvider?: 'ollama' | 'azure' | 'openai' | 'mock';
 
   ollama?: OllamaConfig;
   azure?: AzureConfig;
+  openai?: OpenAIConfig;
   deepseek?: DeepSeekConfig;
   azureDeepSeek?: AzureDeepSeekConfig;
+  mock?: MockConfig;
 }
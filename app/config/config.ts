@@ -4,6 +4,7 @@ import { appConfigDir, join } from '@tauri-apps/api/path';
 
 export interface Config {
   'ai-service': AIServiceConfig;
+  budget?: Record<string, unknown>;
 }
 
 async function configPaths(): Promise<string[]> {
@@ -18,11 +19,15 @@ function convertConfigJson(config_json: any): Config {
   return {
     'ai-service': {
       provider: config_json['ai-service'].provider,
+      embeddingProvider: config_json['ai-service'].embeddingProvider,
       ollama: config_json['ai-service'].ollama,
       azure: config_json['ai-service'].azure,
+      openai: config_json['ai-service'].openai,
       deepseek: config_json['ai-service'].deepseek,
       azureDeepSeek: config_json['ai-service']['azure-deepseek'],
-    }
+      mock: config_json['ai-service'].mock,
+    },
+    budget: config_json.budget,
   }
 }
 
@@ -49,16 +54,20 @@ export async function loadConfig(): Promise<Config> {
   throw new Error('Should not happen');
 }
 
-export function canonicalizeProvider(provider: AIServiceConfig['provider']): 'ollama' | 'azure' | 'deepseek' | 'azureDeepSeek' {
+export function canonicalizeProvider(provider: AIServiceConfig['provider']): 'ollama' | 'azure' | 'openai' | 'deepseek' | 'azureDeepSeek' | 'mock' {
   switch (provider) {
     case 'ollama':
       return 'ollama';
     case 'azure':
       return 'azure';
+    case 'openai':
+      return 'openai';
     case 'deepseek':
       return 'deepseek';
     case 'azure-deepseek':
       return 'azureDeepSeek';
+    case 'mock':
+      return 'mock';
     default:
       throw new Error(`Unsupported provider: ${provider}`);
   }
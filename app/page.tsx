@@ -30,7 +30,8 @@ export default function Home() {
       return 'Session not initialized';
     }
 
-    return await aiService.askQuestion(sessionId, question);
+    const answer = await aiService.askQuestion(sessionId, question);
+    return answer.content;
   };
 
   if (error) {
@@ -1,5 +1,161 @@
 import { invoke } from '@tauri-apps/api/core';
 
+export type SourceCitation = {
+  number: number;
+  chunk_id: string;
+  document: string;
+  page?: number;
+  start: number;
+  end: number;
+  score: number;
+  excerpt: string;
+};
+
+export type CitedSource = {
+  chunk_id: string;
+  document: string;
+  page?: number;
+  start: number;
+  end: number;
+  quote: string;
+};
+
+export type Citation = {
+  number: number;
+  marker_start: number;
+  marker_end: number;
+  claim: string;
+  status: 'resolved' | 'not-retrieved';
+  source?: CitedSource;
+};
+
+export type SourcePassage = {
+  document: string;
+  page?: number;
+  text: string;
+  start: number;
+  end: number;
+  stale: boolean;
+};
+
+export type Answer = {
+  content: string;
+  sources: SourceCitation[];
+  citations: Citation[];
+  // Cite keys of the library entries the answer refers to
+  references: string[];
+};
+
+export type SummaryPoint = {
+  text: string;
+  pages: number[];
+};
+
+export type PaperSummary = {
+  contributions: SummaryPoint[];
+  method: SummaryPoint[];
+  datasets: SummaryPoint[];
+  limitations: SummaryPoint[];
+};
+
+export type SummaryStyle = 'paragraphs' | 'bullets' | 'outline' | 'abstract';
+
+export type SummaryResult = {
+  summary: string;
+  chunks: number;
+  cached: number;
+  levels: number;
+};
+
+export type StructuredOutput = {
+  value: unknown;
+  attempts: number;
+};
+
+export type TableField = {
+  name: string;
+  description?: string;
+  type: 'text' | 'number' | 'integer' | 'boolean';
+  options?: string[];
+};
+
+export type TableCell = {
+  value: unknown;
+  confidence: number;
+  page?: number;
+  quote?: string;
+  verified: boolean;
+  corrected: boolean;
+};
+
+export type ExecutionResult = {
+  id: string;
+  language: string;
+  code: string;
+  exit_code?: number;
+  stdout: string;
+  stderr: string;
+  limit?: 'wall-clock' | 'cpu-time';
+  truncated: boolean;
+  files?: { name: string; path: string; size: number }[];
+  duration_ms: number;
+};
+
+export type RiskLevel = 'read-only' | 'writes-files' | 'executes-code' | 'network';
+
+// Payload of the agent-approval-request event
+export type ApprovalRequest = {
+  id: string;
+  run_id: string;
+  tool: string;
+  risk: RiskLevel;
+  parameters: unknown;
+};
+
+export type ApprovalDecision =
+  | { decision: 'approve' }
+  | { decision: 'edit'; parameters: unknown }
+  | { decision: 'reject'; reason?: string };
+
+export type ToolSpec = {
+  name: string;
+  description: string;
+  parameters: object;
+  risk: RiskLevel;
+  builtin: boolean;
+};
+
+export type FileChange = {
+  path: string;
+  kind: 'created' | 'modified' | 'deleted';
+  writes: number;
+  diff: string;
+};
+
+export type AgentRunStatus = 'running' | 'completed' | 'failed' | 'interrupted';
+
+export type AgentRunSummary = {
+  id: string;
+  goal: string;
+  status: AgentRunStatus;
+  step_count: number;
+  error?: string;
+  created_at: number;
+  updated_at: number;
+};
+
+export type ReviewStage = 'extracting' | 'clustering' | 'writing' | 'completed' | 'failed';
+
+// Payload of the review-progress event
+export type ReviewProgress = {
+  run_id: string;
+  stage: ReviewStage;
+  completed: number;
+  total: number;
+  document?: string;
+  error?: string;
+};
+
 export const aiService ={
   createSession: async (): Promise<string> => {
     const response = await invoke('create_session') as string | {
@@ -12,15 +168,130 @@ export const aiService ={
 
     return response;
   },
-  askQuestion: async (sessionId: string, question: string): Promise<string> => {
-    const response = await invoke('ask_question', { sessionId, question }) as string | {
+  askQuestion: async (sessionId: string, question: string): Promise<Answer> => {
+    const response = await invoke('ask_question', { sessionId, question }) as Answer | {
       error: unknown;
     }
 
-    if (typeof response !== 'string') {
+    if ('error' in response) {
       throw new Error('Failed to ask question ' + JSON.stringify(response.error));
     }
 
+    return response;
+  },
+  openCitation: async (sessionId: string, messageId: string, number: number): Promise<SourcePassage> => {
+    const response = await invoke('open_citation', { sessionId, messageId, number }) as SourcePassage | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to open citation ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  askPaperQuestion: async (sessionId: string, question: string, section?: number): Promise<Answer> => {
+    const response = await invoke('ask_paper_question', { sessionId, question, section }) as Answer | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to ask question ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  summarizePaper: async (sessionId: string): Promise<PaperSummary> => {
+    const response = await invoke('summarize_paper', { sessionId }) as PaperSummary | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to summarize paper ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  summarizeText: async (source: { text?: string; path?: string }, style?: SummaryStyle, instructions?: string): Promise<SummaryResult> => {
+    const response = await invoke('summarize_text', { ...source, style, instructions }) as SummaryResult | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to summarize ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  completeStructured: async (prompts: { role: string; content: string }[], schema: object, maxRepairs?: number): Promise<StructuredOutput> => {
+    const response = await invoke('complete_structured', { prompts, schema, maxRepairs }) as StructuredOutput | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to get structured output ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  startExtraction: async (name: string, fields: TableField[], documents?: string[]): Promise<string> => {
+    const table = await invoke('start_extraction', { name, fields, documents }) as { id: string };
+    return table.id;
+  },
+  updateExtractionCell: async (tableId: string, document: string, field: string, value: unknown): Promise<TableCell> => {
+    return await invoke('update_extraction_cell', { tableId, document, field, value }) as TableCell;
+  },
+  exportExtractionTable: async (tableId: string, format: 'csv' | 'json'): Promise<string | null> => {
+    return await invoke('export_extraction_table', { tableId, format }) as string | null;
+  },
+  runCodeBlock: async (sessionId: string, messageId: string, block?: number): Promise<ExecutionResult> => {
+    const response = await invoke('run_code_block', { sessionId, messageId, block }) as ExecutionResult | {
+      error: unknown;
+    }
+
+    if ('error' in response) {
+      throw new Error('Failed to run code ' + JSON.stringify(response.error));
+    }
+
+    return response;
+  },
+  startAgentRun: async (goal: string): Promise<string> => {
+    return await invoke('start_agent_run', { goal }) as string;
+  },
+  registerAgentTool: async (name: string, description: string, parameters: object, code: string): Promise<ToolSpec> => {
+    return await invoke('register_agent_tool', { name, description, parameters, code }) as ToolSpec;
+  },
+  getAgentFileChanges: async (runId: string): Promise<FileChange[]> => {
+    return await invoke('get_agent_file_changes', { runId }) as FileChange[];
+  },
+  undoAgentFileWrites: async (runId: string, writeId?: string): Promise<string[]> => {
+    return await invoke('undo_agent_file_writes', { runId, writeId }) as string[];
+  },
+  resumeAgentRun: async (runId: string): Promise<AgentRunSummary> => {
+    return await invoke('resume_agent_run', { runId }) as AgentRunSummary;
+  },
+  listAgentRuns: async (): Promise<AgentRunSummary[]> => {
+    return await invoke('list_agent_runs') as AgentRunSummary[];
+  },
+  resolveAgentAction: async (requestId: string, decision: ApprovalDecision): Promise<void> => {
+    await invoke('resolve_agent_action', { requestId, decision });
+  },
+  startLiteratureReview: async (question: string, documents?: string[], format?: 'markdown' | 'latex'): Promise<string> => {
+    const run = await invoke('start_literature_review', { question, documents, format }) as { id: string };
+    return run.id;
+  },
+  exportSessionBibliography: async (sessionId: string): Promise<string | null> => {
+    return await invoke('export_session_bibliography', { sessionId }) as string | null;
+  },
+  embedTexts: async (texts: string[]): Promise<number[][]> => {
+    const response = await invoke('embed_texts', { texts }) as number[][] | {
+      error: unknown;
+    }
+
+    if (!Array.isArray(response)) {
+      throw new Error('Failed to embed texts ' + JSON.stringify(response.error));
+    }
+
     return response;
   }
 }
@@ -0,0 +1,242 @@
+use crate::agent::{
+    AgentBuilder, AgentContext, AgentRun, AgentRunConfig, AgentRunStore, AgentRunSummary, ApprovalDecision,
+    ApprovalGate, ApprovalPolicy, ApprovalRequest, FileChange, FileJournal, FileWrite, LLMConfig, Memory, RunStatus,
+    ScriptTool, ToolRegistry, ToolSpec,
+};
+use crate::chat::new_id;
+use crate::llm_bridge::LLMBridge;
+use crate::AppState;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn run_store(holder: &AppState) -> Result<Arc<AgentRunStore>, String> {
+    holder.agent_runs.clone().ok_or_else(|| "The configuration is not loaded yet".to_string())
+}
+
+fn file_journal(holder: &AppState) -> Result<Arc<FileJournal>, String> {
+    holder.agent_files.clone().ok_or_else(|| "The configuration is not loaded yet".to_string())
+}
+
+fn tool_registry(holder: &AppState) -> Result<Arc<ToolRegistry>, String> {
+    holder.agent_tools.clone().ok_or_else(|| "The configuration is not loaded yet".to_string())
+}
+
+// Builds the agent for a run record and sets it going in the background
+fn launch(
+    app: tauri::AppHandle,
+    holder: &mut AppState,
+    llm_bridge: Arc<dyn LLMBridge>,
+    record: AgentRun,
+    memory: Option<Memory>,
+) -> Result<(), String> {
+    let store = run_store(holder)?;
+    let tools = tool_registry(holder)?;
+    let gate = holder
+        .approvals
+        .get_or_insert_with(|| Arc::new(ApprovalGate::default()))
+        .clone();
+    let mut builder = AgentBuilder::new(LLMConfig {
+        model: record.config.model.clone(),
+        temperature: record.config.temperature,
+        max_tokens: record.config.max_tokens,
+    })
+    .with_tools(tools);
+    if let Some(memory) = memory {
+        builder = builder.with_initial_memory(memory);
+    }
+    let context = AgentContext {
+        app,
+        run_id: record.id.clone(),
+        llm_bridge,
+        provider: record.config.provider.clone(),
+        budget: holder.budget.clone(),
+        gate,
+        policy: ApprovalPolicy {
+            threshold: record.config.approval_threshold,
+        },
+        store,
+    };
+    let agent = builder.build(context, record);
+    tauri::async_runtime::spawn(agent.run());
+    Ok(())
+}
+
+// Starts an agent on the goal in the background and returns the run id; steps arrive as agent-progress
+// events and risky actions as agent-approval-request events
+#[tauri::command]
+pub async fn start_agent_run(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    goal: String,
+) -> Result<String, String> {
+    let goal = goal.trim().to_string();
+    if goal.is_empty() {
+        return Err("The goal cannot be empty".to_string());
+    }
+    let mut holder = app_state.lock().await;
+    let config = holder.config.as_ref().ok_or("The configuration is not loaded yet")?;
+    let agent_config = config.agent();
+    let provider = config.provider().to_string();
+    let llm_bridge = holder.llm_bridge.clone().ok_or("No LLM provider is configured")?;
+    let store = run_store(&holder)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let mut record = AgentRun {
+        id: new_id(),
+        goal,
+        config: AgentRunConfig {
+            provider,
+            model: llm_bridge.model().to_string(),
+            temperature: agent_config.temperature,
+            max_tokens: agent_config.max_tokens,
+            max_steps: agent_config.max_steps,
+            approval_threshold: agent_config.approval_threshold,
+            tools: tool_registry(&holder)?.names(),
+        },
+        status: RunStatus::Running,
+        steps: Vec::new(),
+        memory: Memory {
+            observations: Vec::new(),
+            thoughts: Vec::new(),
+            actions_taken: Vec::new(),
+            decisions: Vec::new(),
+        },
+        answer: None,
+        error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    store.save(&mut record)?;
+    let run_id = record.id.clone();
+    launch(app, &mut holder, llm_bridge, record, None)?;
+    Ok(run_id)
+}
+
+// Continues a failed or interrupted run from its last checkpoint, with the provider and model it started with
+#[tauri::command]
+pub async fn resume_agent_run(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<AgentRunSummary, String> {
+    let mut holder = app_state.lock().await;
+    let run = run_store(&holder)?.load(&run_id).await?;
+    match run.status {
+        RunStatus::Completed => return Err("The agent run has already completed".to_string()),
+        RunStatus::Running => return Err("The agent run is still running".to_string()),
+        RunStatus::Failed | RunStatus::Interrupted => {}
+    }
+    let config = holder.config.as_ref().ok_or("The configuration is not loaded yet")?;
+    let llm_bridge = config.create_bridge(&run.config.provider, Some(&run.config.model))?;
+    let summary = AgentRunSummary::from(&run);
+    let memory = run.memory.clone();
+    launch(app, &mut holder, llm_bridge, run, Some(memory))?;
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn list_agent_runs(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<Vec<AgentRunSummary>, String> {
+    let store = run_store(&*app_state.lock().await)?;
+    Ok(store.list().await)
+}
+
+// The full trace of a run: every thought, action and observation with its timing
+#[tauri::command]
+pub async fn get_agent_run(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<AgentRun, String> {
+    let store = run_store(&*app_state.lock().await)?;
+    store.load(&run_id).await
+}
+
+// Approves, edits or rejects an action an agent is waiting on
+#[tauri::command]
+pub async fn resolve_agent_action(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    request_id: String,
+    decision: ApprovalDecision,
+) -> Result<(), String> {
+    let gate = {
+        let holder = app_state.lock().await;
+        holder.approvals.clone().ok_or("No agent is waiting for approval")?
+    };
+    gate.resolve(&request_id, decision).await
+}
+
+#[tauri::command]
+pub async fn list_pending_agent_actions(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<ApprovalRequest>, String> {
+    let holder = app_state.lock().await;
+    Ok(match &holder.approvals {
+        Some(gate) => gate.pending().await,
+        None => Vec::new(),
+    })
+}
+
+#[tauri::command]
+pub async fn list_agent_tools(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<Vec<ToolSpec>, String> {
+    let holder = app_state.lock().await;
+    Ok(tool_registry(&holder)?.specs())
+}
+
+// Adds a Python tool for agents until the app closes: the script gets its arguments, checked against
+// `parameters`, as a `params` dict and runs in the sandbox
+#[tauri::command]
+pub async fn register_agent_tool(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+    code: String,
+) -> Result<ToolSpec, String> {
+    let holder = app_state.lock().await;
+    let runner = holder.code_runner.clone().ok_or("The configuration is not loaded yet")?;
+    tool_registry(&holder)?.register(Arc::new(ScriptTool {
+        name: name.trim().to_string(),
+        description,
+        parameters,
+        code,
+        runner,
+    }))
+}
+
+#[tauri::command]
+pub async fn remove_agent_tool(app_state: tauri::State<'_, Mutex<AppState>>, name: String) -> Result<(), String> {
+    let holder = app_state.lock().await;
+    tool_registry(&holder)?.unregister(&name)
+}
+
+#[tauri::command]
+pub async fn list_agent_file_writes(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<Vec<FileWrite>, String> {
+    let holder = app_state.lock().await;
+    file_journal(&holder)?.writes(&run_id)
+}
+
+// Every file the run changed, as a diff against how it was before the run
+#[tauri::command]
+pub async fn get_agent_file_changes(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<Vec<FileChange>, String> {
+    let journal = file_journal(&*app_state.lock().await)?;
+    tauri::async_runtime::spawn_blocking(move || journal.changes(&run_id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Undoes one write and the later writes to the same file, or every write of the run; returns the files restored
+#[tauri::command]
+pub async fn undo_agent_file_writes(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+    write_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    let journal = file_journal(&*app_state.lock().await)?;
+    let restored = journal.undo(&run_id, write_id.as_deref())?;
+    Ok(restored.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
@@ -0,0 +1,450 @@
+use super::{format_number, Table};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+
+// Correlation matrices wider than this are hard to read in a prompt
+const MAX_CORRELATED_COLUMNS: usize = 12;
+
+// One value read from a CSV file or a query result
+#[derive(Debug, Clone, PartialEq)]
+pub enum Datum {
+    Number(f64),
+    Text(String),
+    Missing,
+}
+
+impl Datum {
+    // Empty cells and the usual spellings of "no value" are missing
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        match text {
+            "" | "NA" | "N/A" | "NaN" | "nan" | "null" | "NULL" | "None" => Datum::Missing,
+            _ => text
+                .parse::<f64>()
+                .map(Datum::Number)
+                .unwrap_or_else(|_| Datum::Text(text.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ColumnData {
+    Numeric(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub data: ColumnData,
+}
+
+impl Column {
+    fn kind(&self) -> &'static str {
+        match self.data {
+            ColumnData::Numeric(_) => "number",
+            ColumnData::Text(_) => "text",
+        }
+    }
+
+    fn missing(&self) -> usize {
+        match &self.data {
+            ColumnData::Numeric(values) => values.iter().filter(|v| v.is_none()).count(),
+            ColumnData::Text(values) => values.iter().filter(|v| v.is_none()).count(),
+        }
+    }
+
+    fn display(&self, row: usize) -> String {
+        match &self.data {
+            ColumnData::Numeric(values) => values[row].map(format_number).unwrap_or_default(),
+            ColumnData::Text(values) => values[row].clone().unwrap_or_default(),
+        }
+    }
+
+    fn numbers(&self) -> Result<&[Option<f64>], String> {
+        match &self.data {
+            ColumnData::Numeric(values) => Ok(values),
+            ColumnData::Text(_) => Err(format!("Column {} is not numeric", self.name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Mean,
+    Median,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn label(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Sum => "sum",
+            Aggregate::Mean => "mean",
+            Aggregate::Median => "median",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+        }
+    }
+
+    fn apply(&self, values: &mut [f64]) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Aggregate::Count => values.len() as f64,
+            Aggregate::Sum => values.iter().sum(),
+            Aggregate::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregate::Median => {
+                values.sort_by(|a, b| a.total_cmp(b));
+                quantile(values, 0.5)
+            }
+            Aggregate::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+// Linear interpolation between the closest ranks, as in pandas; `sorted` must not be empty
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+// Numbers compare as numbers, so group keys sort 2 before 10
+fn compare_keys(a: &[String], b: &[String]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        let order = match (x.parse::<f64>(), y.parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.total_cmp(&y),
+            _ => x.cmp(y),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    Ordering::Equal
+}
+
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    let n = pairs.len() as f64;
+    if pairs.len() < 2 {
+        return None;
+    }
+    let (mean_x, mean_y) = (
+        pairs.iter().map(|p| p.0).sum::<f64>() / n,
+        pairs.iter().map(|p| p.1).sum::<f64>() / n,
+    );
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x * var_y).sqrt())
+}
+
+// A table held in memory, with each column typed as numbers or text
+#[derive(Debug, Clone)]
+pub struct DataFrame {
+    pub columns: Vec<Column>,
+    pub rows: usize,
+}
+
+impl DataFrame {
+    // A column is numeric when every value it has is a number
+    pub fn from_records(headers: Vec<String>, records: Vec<Vec<Datum>>) -> Self {
+        let mut seen = HashSet::new();
+        let names: Vec<String> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                let base = match header.trim() {
+                    "" => format!("column_{}", i + 1),
+                    name => name.to_string(),
+                };
+                let mut name = base.clone();
+                let mut n = 2;
+                while !seen.insert(name.clone()) {
+                    name = format!("{}_{}", base, n);
+                    n += 1;
+                }
+                name
+            })
+            .collect();
+
+        let rows = records.len();
+        let columns = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let values: Vec<&Datum> = records.iter().map(|r| r.get(i).unwrap_or(&Datum::Missing)).collect();
+                let numeric = values.iter().all(|v| !matches!(v, Datum::Text(_)));
+                let data = if numeric {
+                    ColumnData::Numeric(
+                        values
+                            .iter()
+                            .map(|v| match v {
+                                Datum::Number(x) => Some(*x),
+                                _ => None,
+                            })
+                            .collect(),
+                    )
+                } else {
+                    ColumnData::Text(
+                        values
+                            .iter()
+                            .map(|v| match v {
+                                Datum::Number(x) => Some(format_number(*x)),
+                                Datum::Text(text) => Some(text.clone()),
+                                Datum::Missing => None,
+                            })
+                            .collect(),
+                    )
+                };
+                Column { name, data }
+            })
+            .collect();
+        Self { columns, rows }
+    }
+
+    pub fn from_csv(reader: impl Read, delimiter: u8) -> Result<Self, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_reader(reader);
+        let headers = reader
+            .headers()
+            .map_err(|e| format!("Failed to read the CSV header: {}", e))?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        let mut records = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| format!("Failed to read the CSV: {}", e))?;
+            records.push(record.iter().map(Datum::parse).collect());
+        }
+        Ok(Self::from_records(headers, records))
+    }
+
+    pub fn column(&self, name: &str) -> Result<&Column, String> {
+        self.columns.iter().find(|c| c.name == name).ok_or_else(|| {
+            let names: Vec<&str> = self.columns.iter().map(|c| c.name.as_str()).collect();
+            format!("There is no column {}; the columns are {}", name, names.join(", "))
+        })
+    }
+
+    // The named columns, or all of them
+    fn select(&self, names: &[String]) -> Result<Vec<&Column>, String> {
+        if names.is_empty() {
+            return Ok(self.columns.iter().collect());
+        }
+        names.iter().map(|name| self.column(name)).collect()
+    }
+
+    pub fn head(&self, n: usize) -> Table {
+        Table {
+            headers: self.columns.iter().map(|c| c.name.clone()).collect(),
+            rows: (0..self.rows.min(n))
+                .map(|row| self.columns.iter().map(|c| c.display(row)).collect())
+                .collect(),
+            total_rows: self.rows,
+        }
+    }
+
+    pub fn schema(&self) -> Table {
+        Table::new(
+            vec!["column".to_string(), "type".to_string(), "missing".to_string()],
+            self.columns
+                .iter()
+                .map(|c| vec![c.name.clone(), c.kind().to_string(), c.missing().to_string()])
+                .collect(),
+        )
+    }
+
+    // Count, spread and quartiles of numeric columns; distinct values and the most common one of text columns
+    pub fn describe(&self, columns: &[String]) -> Result<Table, String> {
+        let headers = [
+            "column", "type", "count", "missing", "mean", "std", "min", "25%", "50%", "75%", "max", "unique", "top",
+        ];
+        let mut rows = Vec::new();
+        for column in self.select(columns)? {
+            let missing = column.missing();
+            let mut row = vec![
+                column.name.clone(),
+                column.kind().to_string(),
+                (self.rows - missing).to_string(),
+                missing.to_string(),
+            ];
+            match &column.data {
+                ColumnData::Numeric(values) => {
+                    let mut sorted: Vec<f64> = values.iter().flatten().copied().collect();
+                    sorted.sort_by(|a, b| a.total_cmp(b));
+                    if sorted.is_empty() {
+                        row.extend(std::iter::repeat(String::new()).take(7));
+                    } else {
+                        let n = sorted.len() as f64;
+                        let mean = sorted.iter().sum::<f64>() / n;
+                        let std = if sorted.len() > 1 {
+                            format_number((sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt())
+                        } else {
+                            String::new()
+                        };
+                        row.extend([
+                            format_number(mean),
+                            std,
+                            format_number(sorted[0]),
+                            format_number(quantile(&sorted, 0.25)),
+                            format_number(quantile(&sorted, 0.5)),
+                            format_number(quantile(&sorted, 0.75)),
+                            format_number(sorted[sorted.len() - 1]),
+                        ]);
+                    }
+                    row.extend([String::new(), String::new()]);
+                }
+                ColumnData::Text(values) => {
+                    let mut counts: HashMap<&str, usize> = HashMap::new();
+                    for value in values.iter().flatten() {
+                        *counts.entry(value).or_default() += 1;
+                    }
+                    let top = counts
+                        .iter()
+                        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+                        .map(|(value, count)| format!("{} ({})", value, count))
+                        .unwrap_or_default();
+                    row.extend(std::iter::repeat(String::new()).take(7));
+                    row.extend([counts.len().to_string(), top]);
+                }
+            }
+            rows.push(row);
+        }
+        Ok(Table::new(headers.iter().map(|h| h.to_string()).collect(), rows))
+    }
+
+    // One row per combination of the `by` columns, with the row count or an aggregate of `column`;
+    // sorted by the groups unless `descending` sorts by the value
+    pub fn group_by(
+        &self,
+        by: &[String],
+        column: Option<&str>,
+        aggregate: Aggregate,
+        descending: Option<bool>,
+        limit: usize,
+    ) -> Result<Table, String> {
+        if by.is_empty() {
+            return Err("Name at least one column to group by".to_string());
+        }
+        let keys = self.select(by)?;
+        let values = match (aggregate, column) {
+            (Aggregate::Count, None) => None,
+            (_, Some(name)) => Some(self.column(name)?.numbers()?),
+            (_, None) => return Err(format!("{} needs a column", aggregate.label())),
+        };
+
+        let mut groups: BTreeMap<Vec<String>, (usize, Vec<f64>)> = BTreeMap::new();
+        for row in 0..self.rows {
+            let key = keys
+                .iter()
+                .map(|c| match c.display(row) {
+                    text if text.is_empty() => "(missing)".to_string(),
+                    text => text,
+                })
+                .collect();
+            let group = groups.entry(key).or_default();
+            group.0 += 1;
+            if let Some(value) = values.and_then(|v| v[row]) {
+                group.1.push(value);
+            }
+        }
+
+        let mut results: Vec<(Vec<String>, Option<f64>)> = groups
+            .into_iter()
+            .map(|(key, (count, mut numbers))| {
+                let value = match values {
+                    None => Some(count as f64),
+                    Some(_) => aggregate.apply(&mut numbers),
+                };
+                (key, value)
+            })
+            .collect();
+        match descending {
+            Some(descending) => results.sort_by(|a, b| {
+                let order = a.1.unwrap_or(f64::NEG_INFINITY).total_cmp(&b.1.unwrap_or(f64::NEG_INFINITY));
+                if descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            }),
+            None => results.sort_by(|a, b| compare_keys(&a.0, &b.0)),
+        }
+
+        let mut headers: Vec<String> = by.to_vec();
+        headers.push(match column {
+            Some(name) => format!("{}({})", aggregate.label(), name),
+            None => "count".to_string(),
+        });
+        let total_rows = results.len();
+        let rows = results
+            .into_iter()
+            .take(limit)
+            .map(|(mut key, value)| {
+                key.push(value.map(format_number).unwrap_or_default());
+                key
+            })
+            .collect();
+        Ok(Table {
+            headers,
+            rows,
+            total_rows,
+        })
+    }
+
+    // Pearson correlation between numeric columns, over the rows where both have a value
+    pub fn correlate(&self, columns: &[String]) -> Result<Table, String> {
+        let selected: Vec<&Column> = if columns.is_empty() {
+            self.columns.iter().filter(|c| matches!(c.data, ColumnData::Numeric(_))).collect()
+        } else {
+            self.select(columns)?
+        };
+        if selected.len() < 2 {
+            return Err("Correlation needs at least two numeric columns".to_string());
+        }
+        if selected.len() > MAX_CORRELATED_COLUMNS {
+            return Err(format!(
+                "There are {} numeric columns; name at most {} to correlate",
+                selected.len(),
+                MAX_CORRELATED_COLUMNS
+            ));
+        }
+        let numbers = selected.iter().map(|c| c.numbers()).collect::<Result<Vec<_>, _>>()?;
+
+        let mut headers = vec![String::new()];
+        headers.extend(selected.iter().map(|c| c.name.clone()));
+        let rows = selected
+            .iter()
+            .zip(&numbers)
+            .map(|(column, xs)| {
+                let mut row = vec![column.name.clone()];
+                row.extend(numbers.iter().map(|ys| {
+                    let pairs: Vec<(f64, f64)> = xs
+                        .iter()
+                        .zip(ys.iter())
+                        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                        .collect();
+                    pearson(&pairs).map(|r| format!("{:.3}", r)).unwrap_or_default()
+                }));
+                row
+            })
+            .collect();
+        Ok(Table::new(headers, rows))
+    }
+}
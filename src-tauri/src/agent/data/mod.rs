@@ -0,0 +1,11 @@
+mod table;
+pub use table::*;
+mod frame;
+pub use frame::*;
+mod sql;
+pub use sql::*;
+mod tools;
+pub use tools::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,169 @@
+use super::{DataFrame, Datum, Table};
+use rusqlite::types::ValueRef;
+use rusqlite::{Batch, Connection, OpenFlags};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+// Queries still running after this are interrupted
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+// Rows read from a result at most; the rest are only counted as cut off
+pub const MAX_RESULT_ROWS: usize = 10_000;
+const SAMPLE_ROWS: usize = 5;
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// A SQLite file opened read-only; statements that could change anything are refused before they run
+pub struct Database {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub struct QueryResult {
+    pub frame: DataFrame,
+    // More rows followed than were read
+    pub truncated: bool,
+}
+
+impl Database {
+    fn open(&self) -> Result<Connection, String> {
+        let connection = Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| format!("Failed to open database {}: {}", self.name, e))?;
+        connection
+            .pragma_update(None, "query_only", true)
+            .map_err(|e| e.to_string())?;
+        connection.busy_timeout(Duration::from_secs(5)).map_err(|e| e.to_string())?;
+        Ok(connection)
+    }
+
+    pub fn query(&self, sql: &str, max_rows: usize) -> Result<QueryResult, String> {
+        let connection = self.open()?;
+        let sql_error = |e: rusqlite::Error| format!("SQL error: {}", e);
+        let mut statements = Batch::new(&connection, sql);
+        let mut statement = statements.next().map_err(sql_error)?.ok_or("The query is empty")?;
+        if statements.next().map_err(sql_error)?.is_some() {
+            return Err("Run one statement at a time".to_string());
+        }
+        if !statement.readonly() {
+            return Err("Only statements that read data are allowed".to_string());
+        }
+        let headers: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+
+        // Interrupts the query from another thread if it runs too long; the thread ends as soon as
+        // `finished` is dropped, since the receiver then sees the channel disconnect
+        let (finished, watch) = mpsc::channel::<()>();
+        let interrupt = connection.get_interrupt_handle();
+        std::thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = watch.recv_timeout(QUERY_TIMEOUT) {
+                interrupt.interrupt();
+            }
+        });
+
+        let mut read = || -> Result<(Vec<Vec<Datum>>, bool), rusqlite::Error> {
+            let mut rows = statement.query([])?;
+            let mut records = Vec::new();
+            while let Some(row) = rows.next()? {
+                if records.len() == max_rows {
+                    return Ok((records, true));
+                }
+                let record = (0..headers.len())
+                    .map(|i| {
+                        Ok(match row.get_ref(i)? {
+                            ValueRef::Null => Datum::Missing,
+                            ValueRef::Integer(n) => Datum::Number(n as f64),
+                            ValueRef::Real(x) => Datum::Number(x),
+                            ValueRef::Text(text) => Datum::Text(String::from_utf8_lossy(text).to_string()),
+                            ValueRef::Blob(blob) => Datum::Text(format!("<{} bytes>", blob.len())),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+                records.push(record);
+            }
+            Ok((records, false))
+        };
+        let outcome = read();
+        drop(finished);
+        let (records, truncated) = outcome.map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(error, _) if error.code == rusqlite::ErrorCode::OperationInterrupted => {
+                format!("The query took longer than {} seconds", QUERY_TIMEOUT.as_secs())
+            }
+            e => format!("SQL error: {}", e),
+        })?;
+        Ok(QueryResult {
+            frame: DataFrame::from_records(headers.clone(), records),
+            truncated,
+        })
+    }
+
+    // Tables and views with their columns, or one table's columns and a few of its rows
+    pub fn schema(&self, table: Option<&str>) -> Result<String, String> {
+        let connection = self.open()?;
+        let sql_error = |e: rusqlite::Error| format!("SQL error: {}", e);
+        let columns = |name: &str| -> Result<Vec<Vec<String>>, String> {
+            let mut statement = connection
+                .prepare("SELECT name, type, pk, \"notnull\" FROM pragma_table_info(?1)")
+                .map_err(sql_error)?;
+            let rows = statement
+                .query_map([name], |row| {
+                    let pk: i64 = row.get(2)?;
+                    let not_null: i64 = row.get(3)?;
+                    let mut flags = Vec::new();
+                    if pk > 0 {
+                        flags.push("primary key");
+                    }
+                    if not_null > 0 {
+                        flags.push("not null");
+                    }
+                    Ok(vec![row.get::<_, String>(0)?, row.get::<_, String>(1)?, flags.join(", ")])
+                })
+                .map_err(sql_error)?;
+            rows.collect::<Result<_, _>>().map_err(sql_error)
+        };
+
+        if let Some(table) = table {
+            let described = Table::new(
+                vec!["column".to_string(), "type".to_string(), "constraints".to_string()],
+                columns(table)?,
+            );
+            if described.rows.is_empty() {
+                return Err(format!("There is no table or view named {}", table));
+            }
+            let sample = self.query(
+                &format!("SELECT * FROM {} LIMIT {}", quote_identifier(table), SAMPLE_ROWS),
+                SAMPLE_ROWS,
+            )?;
+            return Ok(format!(
+                "{}\n\nFirst rows:\n{}",
+                described.render(),
+                sample.frame.head(SAMPLE_ROWS).render()
+            ));
+        }
+
+        let mut statement = connection
+            .prepare("SELECT type, name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .map_err(sql_error)?;
+        let objects: Vec<(String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sql_error)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_error)?;
+        if objects.is_empty() {
+            return Ok(format!("Database {} has no tables", self.name));
+        }
+        // One line per table rather than a table, so long column lists are not cut off
+        let mut lines = Vec::new();
+        for (kind, name) in objects {
+            let described: Vec<String> = columns(&name)?
+                .into_iter()
+                .map(|c| if c[1].is_empty() { c[0].clone() } else { format!("{} {}", c[0], c[1]) })
+                .collect();
+            lines.push(format!("{} ({}): {}", name, kind, described.join(", ")));
+        }
+        Ok(lines.join("\n"))
+    }
+}
@@ -0,0 +1,63 @@
+// Compact text tables for putting results in front of the model
+
+const MAX_CELL_CHARS: usize = 40;
+
+// Whole numbers without decimals, the rest to four significant-looking places
+pub fn format_number(x: f64) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x.fract() == 0.0 && x.abs() < 1e15 {
+        return format!("{}", x as i64);
+    }
+    if x.abs() >= 1e-3 && x.abs() < 1e6 {
+        let text = format!("{:.4}", x);
+        return text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    format!("{:.3e}", x)
+}
+
+fn cell(text: &str) -> String {
+    let text = text.replace(['\n', '\r'], " ").replace('|', "\\|");
+    if text.chars().count() > MAX_CELL_CHARS {
+        let cut: String = text.chars().take(MAX_CELL_CHARS - 1).collect();
+        format!("{}…", cut)
+    } else {
+        text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    // Rows in the full result, when only some are in `rows`
+    pub total_rows: usize,
+}
+
+impl Table {
+    pub fn new(headers: Vec<String>, rows: Vec<Vec<String>>) -> Self {
+        let total_rows = rows.len();
+        Self {
+            headers,
+            rows,
+            total_rows,
+        }
+    }
+
+    // A Markdown pipe table, with a note when rows were left out
+    pub fn render(&self) -> String {
+        let line = |cells: &[String]| format!("| {} |", cells.iter().map(|c| cell(c)).collect::<Vec<_>>().join(" | "));
+        let mut lines = vec![
+            line(&self.headers),
+            format!("|{}", "---|".repeat(self.headers.len())),
+        ];
+        lines.extend(self.rows.iter().map(|row| line(row)));
+        if self.total_rows > self.rows.len() {
+            lines.push(format!("({} of {} rows shown)", self.rows.len(), self.total_rows));
+        } else if self.rows.is_empty() {
+            lines.push("(no rows)".to_string());
+        }
+        lines.join("\n")
+    }
+}
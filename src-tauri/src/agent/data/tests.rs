@@ -0,0 +1,97 @@
+use super::*;
+use std::path::PathBuf;
+
+const SALES: &str = "region,units,price,note
+north,10,2.5,first
+south,4,3,
+north,6,2.5,\"late, \"\"rush\"\"\"
+east,,4,
+south,8,3,";
+
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ccru-{}-{}.sqlite", name, uuid::Uuid::new_v4()));
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE papers (id INTEGER PRIMARY KEY, title TEXT NOT NULL, year INTEGER, score REAL);
+             INSERT INTO papers (title, year, score) VALUES ('Attention', 2017, 9.5), ('ResNet', 2015, 9.1), ('Dropout', 2014, NULL);
+             CREATE VIEW recent AS SELECT title FROM papers WHERE year > 2015;",
+        )
+        .unwrap();
+    path
+}
+
+#[test]
+fn csv_tables_describe_group_and_correlate() {
+    let frame = DataFrame::from_csv(SALES.as_bytes(), b',').unwrap();
+    assert_eq!(frame.rows, 5);
+    let schema = frame.schema().render();
+    assert!(schema.contains("| units | number |"), "{}", schema);
+    assert!(schema.contains("| note | text |"), "{}", schema);
+
+    let described = frame.describe(&["units".to_string(), "region".to_string()]).unwrap();
+    let units = described.rows.iter().find(|row| row[0] == "units").unwrap();
+    assert_eq!(&units[2..5], ["4", "1", "7"]);
+    assert!(frame.describe(&["missing".to_string()]).is_err());
+
+    let grouped = frame
+        .group_by(&["region".to_string()], Some("units"), Aggregate::Sum, Some(true), 10)
+        .unwrap();
+    assert_eq!(grouped.rows[0], ["north", "16"]);
+    assert_eq!(grouped.rows[1], ["south", "12"]);
+    let counted = frame.group_by(&["region".to_string()], None, Aggregate::Count, None, 1).unwrap();
+    assert_eq!((counted.rows.len(), counted.total_rows), (1, 3));
+    assert!(frame.group_by(&["region".to_string()], Some("note"), Aggregate::Mean, None, 10).is_err());
+
+    let correlation = frame.correlate(&["units".to_string(), "price".to_string()]).unwrap();
+    assert_eq!(correlation.rows[0][1], "1.000");
+    assert_eq!(correlation.rows[0][2], correlation.rows[1][1]);
+}
+
+#[test]
+fn sql_queries_are_read_only_and_capped() {
+    let path = temp_db("data");
+    let database = Database {
+        name: "papers".to_string(),
+        path: path.clone(),
+    };
+
+    let result = database.query("SELECT title, year, score FROM papers ORDER BY year", 2).unwrap();
+    assert!(result.truncated);
+    let rendered = result.frame.head(10).render();
+    assert!(rendered.contains("| Dropout | 2014 |  |"), "{}", rendered);
+
+    assert!(database.query("DELETE FROM papers", 10).is_err());
+    assert!(database.query("DROP TABLE papers", 10).is_err());
+    assert!(database.query("SELECT * FROM papers; DELETE FROM papers", 10).is_err());
+    assert!(database.query("ATTACH DATABASE 'other.db' AS other", 10).is_err());
+    let count = database.query("SELECT count(*) AS n FROM papers", 10).unwrap();
+    assert_eq!(count.frame.head(1).rows[0], ["3"]);
+
+    let overview = database.schema(None).unwrap();
+    assert!(overview.contains("papers (table): id INTEGER, title TEXT, year INTEGER, score REAL"), "{}", overview);
+    assert!(overview.contains("recent (view): title"), "{}", overview);
+    let table = database.schema(Some("papers")).unwrap();
+    assert!(table.contains("| title | TEXT | not null |"), "{}", table);
+    assert!(table.contains("Attention"), "{}", table);
+    assert!(database.schema(Some("nope")).is_err());
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn tables_render_compactly() {
+    let mut table = Table::new(
+        vec!["name".to_string(), "value".to_string()],
+        vec![vec!["a|b\nc".to_string(), "x".repeat(60)]],
+    );
+    table.total_rows = 3;
+    let rendered = table.render();
+    assert!(rendered.starts_with("| name | value |\n|---|---|\n| a\\|b c | "), "{}", rendered);
+    assert!(rendered.contains(&format!("{}… |", "x".repeat(39))), "{}", rendered);
+    assert!(rendered.ends_with("(1 of 3 rows shown)"));
+    assert_eq!(Table::new(vec!["a".to_string()], Vec::new()).render(), "| a |\n|---|\n(no rows)");
+
+    assert_eq!(format_number(12.0), "12");
+    assert_eq!(format_number(0.12345), "0.1235");
+    assert_eq!(format_number(2.5e-7), "2.500e-7");
+}
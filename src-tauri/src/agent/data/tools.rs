@@ -0,0 +1,395 @@
+use super::{Aggregate, DataFrame, Database, MAX_RESULT_ROWS};
+use crate::agent::{tool_failed, AgentError, FileScope, RiskLevel, Tool, ToolContext};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+pub const DATA_TOOL_NAMES: [&str; 4] = ["load_csv", "describe_table", "group_by", "correlate"];
+pub const SQL_TOOL_NAMES: [&str; 2] = ["sql_schema", "sql_query"];
+
+const DEFAULT_SHOWN_ROWS: usize = 20;
+const MAX_SHOWN_ROWS: usize = 200;
+// Runs whose loaded tables are kept; older runs' tables are dropped
+const KEPT_RUNS: usize = 4;
+
+#[derive(Default)]
+struct RunTables {
+    // Run ids, oldest first
+    order: VecDeque<String>,
+    tables: HashMap<String, HashMap<String, Arc<DataFrame>>>,
+}
+
+// Tables loaded by each run, by name
+#[derive(Default)]
+pub struct DataTables {
+    runs: Mutex<RunTables>,
+}
+
+impl DataTables {
+    pub fn insert(&self, run_id: &str, name: &str, frame: DataFrame) {
+        let mut runs = self.runs.lock().unwrap();
+        if !runs.tables.contains_key(run_id) {
+            runs.order.push_back(run_id.to_string());
+            while runs.order.len() > KEPT_RUNS {
+                if let Some(oldest) = runs.order.pop_front() {
+                    runs.tables.remove(&oldest);
+                }
+            }
+        }
+        runs.tables
+            .entry(run_id.to_string())
+            .or_default()
+            .insert(name.to_string(), Arc::new(frame));
+    }
+
+    pub fn get(&self, run_id: &str, name: &str) -> Result<Arc<DataFrame>, String> {
+        let runs = self.runs.lock().unwrap();
+        let tables = runs.tables.get(run_id);
+        tables.and_then(|t| t.get(name)).cloned().ok_or_else(|| {
+            let mut names: Vec<&str> = tables.map(|t| t.keys().map(String::as_str).collect()).unwrap_or_default();
+            names.sort();
+            match names.is_empty() {
+                true => format!("No table named {} is loaded; load one with load_csv first", name),
+                false => format!("No table named {} is loaded; the tables are {}", name, names.join(", ")),
+            }
+        })
+    }
+}
+
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, AgentError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| tool_failed(e.to_string()))?
+        .map_err(tool_failed)
+}
+
+fn text_param<'a>(params: &'a Value, name: &str) -> Option<&'a str> {
+    params.get(name).and_then(|v| v.as_str())
+}
+
+fn names_param(params: &Value, name: &str) -> Vec<String> {
+    match params.get(name) {
+        Some(Value::String(one)) => vec![one.clone()],
+        Some(Value::Array(many)) => many.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn shown_rows(params: &Value) -> usize {
+    params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_SHOWN_ROWS, |n| n as usize)
+        .min(MAX_SHOWN_ROWS)
+}
+
+fn columns_schema() -> Value {
+    json!({ "type": "array", "items": { "type": "string" }, "description": "Columns to use; all of them by default" })
+}
+
+// Runs read-only SQL against the databases named in the config
+pub struct SqlTools {
+    pub databases: Vec<Arc<Database>>,
+    pub tables: Arc<DataTables>,
+}
+
+impl SqlTools {
+    fn database(&self, params: &Value) -> Result<Arc<Database>, String> {
+        match text_param(params, "database") {
+            Some(name) => self.databases.iter().find(|d| d.name == name).cloned(),
+            None if self.databases.len() == 1 => self.databases.first().cloned(),
+            None => return Err("Name the database to use".to_string()),
+        }
+        .ok_or_else(|| "There is no database by that name".to_string())
+    }
+
+    fn database_schema(&self) -> Value {
+        let names: Vec<&str> = self.databases.iter().map(|d| d.name.as_str()).collect();
+        json!({ "type": "string", "enum": names, "description": "Database to use; needed when there are several" })
+    }
+}
+
+pub struct SqlSchemaTool(pub Arc<SqlTools>);
+
+#[async_trait]
+impl Tool for SqlSchemaTool {
+    fn name(&self) -> &str {
+        "sql_schema"
+    }
+
+    fn description(&self) -> &str {
+        "List the tables and views of a SQLite database with their columns, or, given a table, \
+         its columns and first rows."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "database": self.0.database_schema(), "table": { "type": "string" } },
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let database = self.0.database(&params).map_err(tool_failed)?;
+        blocking(move || database.schema(text_param(&params, "table")).map(Value::String)).await
+    }
+}
+
+pub struct SqlQueryTool(pub Arc<SqlTools>);
+
+#[async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &str {
+        "sql_query"
+    }
+
+    fn description(&self) -> &str {
+        "Run one read-only SQL query (SELECT, WITH, read-only PRAGMA) on a SQLite database and return the rows \
+         as a table. Aggregate in SQL where you can. With save_as, the full result is also kept as a table \
+         for describe_table, group_by and correlate."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "database": self.0.database_schema(),
+                "sql": { "type": "string", "minLength": 1 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_SHOWN_ROWS, "description": "Rows to show" },
+                "save_as": { "type": "string", "description": "Name to keep the result under" },
+            },
+            "required": ["sql"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let database = self.0.database(&params).map_err(tool_failed)?;
+        let tables = self.0.tables.clone();
+        let run_id = context.run_id.clone();
+        blocking(move || {
+            let result = database.query(text_param(&params, "sql").unwrap_or_default(), MAX_RESULT_ROWS)?;
+            let mut text = result.frame.head(shown_rows(&params)).render();
+            if result.truncated {
+                text.push_str(&format!("\n(the result has more than {} rows; only those were read)", MAX_RESULT_ROWS));
+            }
+            if let Some(name) = text_param(&params, "save_as").filter(|n| !n.trim().is_empty()) {
+                tables.insert(&run_id, name.trim(), result.frame);
+                text.push_str(&format!("\nSaved as table {}", name.trim()));
+            }
+            Ok(Value::String(text))
+        })
+        .await
+    }
+}
+
+// Loads CSV files from the folders open to the agent into tables it can summarize
+pub struct DataTools {
+    pub scope: Arc<FileScope>,
+    pub tables: Arc<DataTables>,
+    pub max_csv_bytes: u64,
+}
+
+pub struct LoadCsvTool(pub Arc<DataTools>);
+
+#[async_trait]
+impl Tool for LoadCsvTool {
+    fn name(&self) -> &str {
+        "load_csv"
+    }
+
+    fn description(&self) -> &str {
+        "Load a CSV file from the project folder into an in-memory table and show its columns, their types \
+         and the first rows. The table is named after the file unless a name is given."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "name": { "type": "string" },
+                "delimiter": { "type": "string", "minLength": 1, "maxLength": 1, "description": "Comma by default; tab for .tsv files" },
+            },
+            "required": ["path"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let tools = self.0.clone();
+        let run_id = context.run_id.clone();
+        blocking(move || {
+            let path = tools.scope.resolve(text_param(&params, "path").unwrap_or_default())?;
+            let size = std::fs::metadata(&path)
+                .map_err(|e| format!("Cannot read {}: {}", tools.scope.display(&path), e))?
+                .len();
+            if size > tools.max_csv_bytes {
+                return Err(format!("The file is {} bytes; CSV files may be at most {} bytes", size, tools.max_csv_bytes));
+            }
+            let tsv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsv"));
+            let delimiter = match text_param(&params, "delimiter") {
+                Some(d) => *d.as_bytes().first().unwrap_or(&b','),
+                None if tsv => b'\t',
+                None => b',',
+            };
+            let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
+            let frame = DataFrame::from_csv(file, delimiter)?;
+
+            let name = match text_param(&params, "name").map(str::trim).filter(|n| !n.is_empty()) {
+                Some(name) => name.to_string(),
+                None => path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+            };
+            let text = format!(
+                "Loaded {} as table {}: {} rows, {} columns\n\n{}\n\nFirst rows:\n{}",
+                tools.scope.display(&path),
+                name,
+                frame.rows,
+                frame.columns.len(),
+                frame.schema().render(),
+                frame.head(5).render()
+            );
+            tools.tables.insert(&run_id, &name, frame);
+            Ok(Value::String(text))
+        })
+        .await
+    }
+}
+
+pub struct DescribeTableTool(pub Arc<DataTables>);
+
+#[async_trait]
+impl Tool for DescribeTableTool {
+    fn name(&self) -> &str {
+        "describe_table"
+    }
+
+    fn description(&self) -> &str {
+        "Summary statistics of a loaded table: count, missing, mean, standard deviation, min, quartiles and max \
+         of numeric columns, and distinct values and the most common value of text columns."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "table": { "type": "string" }, "columns": columns_schema() },
+            "required": ["table"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let frame = self.0.get(&context.run_id, text_param(&params, "table").unwrap_or_default()).map_err(tool_failed)?;
+        blocking(move || Ok(Value::String(frame.describe(&names_param(&params, "columns"))?.render()))).await
+    }
+}
+
+pub struct GroupByTool(pub Arc<DataTables>);
+
+#[async_trait]
+impl Tool for GroupByTool {
+    fn name(&self) -> &str {
+        "group_by"
+    }
+
+    fn description(&self) -> &str {
+        "Group a loaded table by one or more columns and count the rows of each group, or aggregate a numeric \
+         column (sum, mean, median, min, max). Sort by the result with order asc or desc."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "table": { "type": "string" },
+                "by": { "type": "array", "items": { "type": "string" }, "minItems": 1 },
+                "column": { "type": "string", "description": "Numeric column to aggregate" },
+                "aggregate": { "type": "string", "enum": ["count", "sum", "mean", "median", "min", "max"] },
+                "order": { "type": "string", "enum": ["asc", "desc"] },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_SHOWN_ROWS },
+            },
+            "required": ["table", "by"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let frame = self.0.get(&context.run_id, text_param(&params, "table").unwrap_or_default()).map_err(tool_failed)?;
+        blocking(move || {
+            let column = text_param(&params, "column");
+            let aggregate = match params.get("aggregate") {
+                Some(value) => serde_json::from_value(value.clone()).map_err(|e| e.to_string())?,
+                None if column.is_some() => Aggregate::Mean,
+                None => Aggregate::Count,
+            };
+            let descending = text_param(&params, "order").map(|order| order == "desc");
+            let table = frame.group_by(&names_param(&params, "by"), column, aggregate, descending, shown_rows(&params))?;
+            Ok(Value::String(table.render()))
+        })
+        .await
+    }
+}
+
+pub struct CorrelateTool(pub Arc<DataTables>);
+
+#[async_trait]
+impl Tool for CorrelateTool {
+    fn name(&self) -> &str {
+        "correlate"
+    }
+
+    fn description(&self) -> &str {
+        "Pearson correlation matrix between numeric columns of a loaded table."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "table": { "type": "string" }, "columns": columns_schema() },
+            "required": ["table"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let frame = self.0.get(&context.run_id, text_param(&params, "table").unwrap_or_default()).map_err(tool_failed)?;
+        blocking(move || Ok(Value::String(frame.correlate(&names_param(&params, "columns"))?.render()))).await
+    }
+}
+
+pub fn sql_tools(tools: SqlTools) -> Vec<Arc<dyn Tool>> {
+    let tools = Arc::new(tools);
+    vec![Arc::new(SqlSchemaTool(tools.clone())), Arc::new(SqlQueryTool(tools))]
+}
+
+pub fn data_tools(tools: DataTools) -> Vec<Arc<dyn Tool>> {
+    let tables = tools.tables.clone();
+    vec![
+        Arc::new(LoadCsvTool(Arc::new(tools))),
+        Arc::new(DescribeTableTool(tables.clone())),
+        Arc::new(GroupByTool(tables.clone())),
+        Arc::new(CorrelateTool(tables)),
+    ]
+}
@@ -0,0 +1,122 @@
+// Line diffs for showing what an agent changed
+
+const CONTEXT: usize = 3;
+// Beyond this many cells in the comparison table the changed region is shown as one replacement
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// Longest-common-subsequence edit script over the lines that differ, after common ends are set aside
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![Op::Equal; prefix];
+    if a.len().saturating_mul(b.len()) > MAX_TABLE_CELLS {
+        ops.extend(std::iter::repeat(Op::Delete).take(a.len()));
+        ops.extend(std::iter::repeat(Op::Insert).take(b.len()));
+    } else {
+        // lengths[i][j]: longest common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lengths = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lengths[i * width + j] = if a[i] == b[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push(Op::Equal);
+                i += 1;
+                j += 1;
+            } else if j == b.len() || (i < a.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+                ops.push(Op::Delete);
+                i += 1;
+            } else {
+                ops.push(Op::Insert);
+                j += 1;
+            }
+        }
+    }
+    ops.extend(std::iter::repeat(Op::Equal).take(suffix));
+    ops
+}
+
+// A unified diff with three lines of context; empty when the texts are the same
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = edit_script(&old_lines, &new_lines);
+    let changes: Vec<usize> = (0..ops.len()).filter(|&k| ops[k] != Op::Equal).collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Runs of ops to print, merging changes whose context would overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &k in &changes {
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    // Line numbers in both texts before each op
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut i, mut j) = (0, 0);
+    for op in &ops {
+        positions.push((i, j));
+        match op {
+            Op::Equal => {
+                i += 1;
+                j += 1;
+            }
+            Op::Delete => i += 1,
+            Op::Insert => j += 1,
+        }
+    }
+    positions.push((i, j));
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for (start, end) in hunks {
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let range = |from: usize, to: usize| {
+            let count = to - from;
+            format!("{},{}", if count == 0 { from } else { from + 1 }, count)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_end),
+            range(new_start, new_end)
+        ));
+        for k in start..end {
+            let (i, j) = positions[k];
+            match ops[k] {
+                Op::Equal => out.push_str(&format!(" {}\n", old_lines[i])),
+                Op::Delete => out.push_str(&format!("-{}\n", old_lines[i])),
+                Op::Insert => out.push_str(&format!("+{}\n", new_lines[j])),
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,222 @@
+use super::unified_diff;
+use crate::chat::{check_id, new_id};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// One file write by an agent and where the previous content was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileWrite {
+    pub id: String,
+    pub path: PathBuf,
+    // Backup file in the run's folder; none when the file didn't exist before
+    pub backup: Option<String>,
+    pub bytes: usize,
+    pub written_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+// A file as it was before a run and as it is now
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    pub writes: usize,
+    // Unified diff, or a note for files that aren't text
+    pub diff: String,
+}
+
+fn sync_write(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+fn as_text(content: &[u8]) -> Option<&str> {
+    std::str::from_utf8(content).ok()
+}
+
+// Write-ahead journal of agent file writes, one folder per run under agent-files: the old content is
+// saved and the journal updated before the file is touched, so every write can be undone
+pub struct FileJournal {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileJournal {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, lock: Mutex::new(()) }
+    }
+
+    fn run_dir(&self, run_id: &str) -> Result<PathBuf, String> {
+        check_id(run_id)?;
+        Ok(self.dir.join(run_id))
+    }
+
+    // A run without a journal has no writes; one that can't be read is an error, so that writing
+    // never replaces the history of earlier writes
+    pub fn writes(&self, run_id: &str) -> Result<Vec<FileWrite>, String> {
+        let content = match fs::read_to_string(self.run_dir(run_id)?.join("journal.json")) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read the file journal: {}", e)),
+        };
+        serde_json::from_str(&content).map_err(|e| format!("The file journal of run {} is damaged: {}", run_id, e))
+    }
+
+    fn save(&self, run_id: &str, writes: &[FileWrite]) -> Result<(), String> {
+        let content = serde_json::to_vec_pretty(writes).map_err(|e| e.to_string())?;
+        sync_write(&self.run_dir(run_id)?.join("journal.json"), &content)
+            .map_err(|e| format!("Failed to save the file journal: {}", e))
+    }
+
+    fn backup(&self, run_id: &str, write: &FileWrite) -> Result<Option<Vec<u8>>, String> {
+        match &write.backup {
+            Some(name) => fs::read(self.run_dir(run_id)?.join(name))
+                .map(Some)
+                .map_err(|e| format!("Backup of {} is missing: {}", write.path.display(), e)),
+            None => Ok(None),
+        }
+    }
+
+    // `path` must already be resolved inside the agent's scope
+    pub fn write(&self, run_id: &str, path: &Path, content: &[u8]) -> Result<FileWrite, String> {
+        let _guard = self.lock.lock().unwrap();
+        let run_dir = self.run_dir(run_id)?;
+        fs::create_dir_all(&run_dir).map_err(|e| format!("Failed to create the file journal: {}", e))?;
+        if path.is_dir() {
+            return Err(format!("{} is a folder", path.display()));
+        }
+        let mut writes = self.writes(run_id)?;
+
+        let id = new_id();
+        let backup = match fs::read(path) {
+            Ok(previous) => {
+                let name = format!("{}.bak", id);
+                sync_write(&run_dir.join(&name), &previous).map_err(|e| format!("Failed to back up the file: {}", e))?;
+                Some(name)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        let write = FileWrite {
+            id,
+            path: path.to_path_buf(),
+            backup,
+            bytes: content.len(),
+            written_at: chrono::Utc::now(),
+            undone: false,
+        };
+        writes.push(write.clone());
+        self.save(run_id, &writes)?;
+
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, content));
+        if let Err(e) = written {
+            writes.pop();
+            self.save(run_id, &writes)?;
+            return Err(format!("Failed to write {}: {}", path.display(), e));
+        }
+        Ok(write)
+    }
+
+    // Puts a file back as it was before the given write, undoing the later writes to it as well;
+    // without a write id, every file the run wrote goes back to how it was before the run.
+    // Returns the files restored
+    pub fn undo(&self, run_id: &str, write_id: Option<&str>) -> Result<Vec<PathBuf>, String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut writes = self.writes(run_id)?;
+        // The earliest write still in effect for each file to restore
+        let mut targets: BTreeMap<PathBuf, usize> = BTreeMap::new();
+        match write_id {
+            Some(id) => {
+                let index = writes
+                    .iter()
+                    .position(|w| w.id == id)
+                    .ok_or_else(|| format!("No write {} in this run", id))?;
+                if writes[index].undone {
+                    return Err("That write has already been undone".to_string());
+                }
+                targets.insert(writes[index].path.clone(), index);
+            }
+            None => {
+                for (index, write) in writes.iter().enumerate().filter(|(_, w)| !w.undone) {
+                    targets.entry(write.path.clone()).or_insert(index);
+                }
+            }
+        }
+
+        for (path, &index) in &targets {
+            match self.backup(run_id, &writes[index])? {
+                Some(previous) => fs::write(path, previous),
+                None => match fs::remove_file(path) {
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    other => other,
+                },
+            }
+            .map_err(|e| format!("Failed to restore {}: {}", path.display(), e))?;
+            for write in writes[index..].iter_mut().filter(|w| &w.path == path) {
+                write.undone = true;
+            }
+        }
+        self.save(run_id, &writes)?;
+        Ok(targets.into_keys().collect())
+    }
+
+    // What the run's writes that are still in effect changed, file by file
+    pub fn changes(&self, run_id: &str) -> Result<Vec<FileChange>, String> {
+        let writes = self.writes(run_id)?;
+        let mut first: BTreeMap<&Path, (usize, usize)> = BTreeMap::new();
+        for (index, write) in writes.iter().enumerate().filter(|(_, w)| !w.undone) {
+            first.entry(&write.path).or_insert((index, 0)).1 += 1;
+        }
+
+        let mut changes = Vec::new();
+        for (path, (index, count)) in first {
+            let before = self.backup(run_id, &writes[index])?;
+            let after = fs::read(path).ok();
+            if before == after {
+                continue;
+            }
+            let kind = match (&before, &after) {
+                (None, _) => ChangeKind::Created,
+                (_, None) => ChangeKind::Deleted,
+                _ => ChangeKind::Modified,
+            };
+            let name = path.to_string_lossy();
+            let diff = match (
+                before.as_deref().map(as_text).unwrap_or(Some("")),
+                after.as_deref().map(as_text).unwrap_or(Some("")),
+            ) {
+                (Some(old), Some(new)) => unified_diff(
+                    old,
+                    new,
+                    if before.is_some() { &name } else { "/dev/null" },
+                    if after.is_some() { &name } else { "/dev/null" },
+                ),
+                _ => "Binary file changed".to_string(),
+            };
+            changes.push(FileChange {
+                path: path.to_path_buf(),
+                kind,
+                writes: count,
+                diff,
+            });
+        }
+        Ok(changes)
+    }
+}
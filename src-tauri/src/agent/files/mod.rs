@@ -0,0 +1,11 @@
+mod scope;
+pub use scope::*;
+mod diff;
+pub use diff::*;
+mod journal;
+pub use journal::*;
+mod tools;
+pub use tools::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// The folders agent file tools may touch. Every path is resolved to its real location, following
+// symlinks, and must end up inside one of the roots
+pub struct FileScope {
+    roots: Vec<PathBuf>,
+    pub max_file_bytes: u64,
+}
+
+impl FileScope {
+    // Roots that don't exist are left out with a warning
+    pub fn new(roots: &[String], max_file_bytes: u64) -> Self {
+        let roots = roots
+            .iter()
+            .filter_map(|root| match fs::canonicalize(root) {
+                Ok(path) if path.is_dir() => Some(path),
+                Ok(_) => {
+                    log::warn!("Agent root {} is not a folder", root);
+                    None
+                }
+                Err(e) => {
+                    log::warn!("Agent root {} is not available: {}", root, e);
+                    None
+                }
+            })
+            .collect();
+        Self { roots, max_file_bytes }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    // Resolves a path from the agent, relative to the first root unless absolute. The path doesn't
+    // have to exist yet, but the part that does is resolved through any symlinks before it's checked,
+    // and the rest may not climb out with `..`
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let first = self
+            .roots
+            .first()
+            .ok_or("No folders are open to the agent; list them in allowedRoots under [agent]")?;
+        let requested = Path::new(path.trim());
+        let joined = if requested.is_absolute() {
+            requested.to_path_buf()
+        } else {
+            first.join(requested)
+        };
+
+        let existing = joined
+            .ancestors()
+            .find(|a| fs::symlink_metadata(a).is_ok())
+            .ok_or_else(|| format!("{} is not inside an allowed folder", path))?;
+        let real = fs::canonicalize(existing).map_err(|e| format!("Cannot resolve {}: {}", path, e))?;
+        let mut resolved = real;
+        for component in joined.strip_prefix(existing).unwrap_or(Path::new("")).components() {
+            match component {
+                Component::Normal(part) => resolved.push(part),
+                Component::CurDir => {}
+                _ => return Err(format!("{} leaves the allowed folders", path)),
+            }
+        }
+        if !self.contains(&resolved) {
+            return Err(format!("{} is not inside an allowed folder", path));
+        }
+        Ok(resolved)
+    }
+
+    // How a path is shown to the agent: relative to the first root, absolute otherwise
+    pub fn display(&self, path: &Path) -> String {
+        match self.roots.first().and_then(|root| path.strip_prefix(root).ok()) {
+            Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Some(relative) => relative.to_string_lossy().replace('\\', "/"),
+            None => path.to_string_lossy().to_string(),
+        }
+    }
+}
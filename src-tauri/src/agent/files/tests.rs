@@ -0,0 +1,111 @@
+use super::*;
+use std::fs;
+use std::path::PathBuf;
+
+const RUN: &str = "7f1c2a9e-3b4d-4e5f-8a6b-9c0d1e2f3a4b";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ccru-{}-{}", name, uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn scope_keeps_paths_inside_the_roots() {
+    let root = temp_dir("scope");
+    let outside = temp_dir("outside");
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+    let scope = FileScope::new(&[root.to_string_lossy().to_string()], 1024);
+    let real_root = fs::canonicalize(&root).unwrap();
+
+    assert_eq!(scope.resolve("notes/new/a.md").unwrap(), real_root.join("notes/new/a.md"));
+    assert_eq!(scope.resolve("notes/../b.md").unwrap(), real_root.join("b.md"));
+    assert_eq!(scope.display(&real_root.join("notes/a.md")), "notes/a.md");
+    assert!(scope.resolve("../outside.txt").is_err());
+    assert!(scope.resolve("notes/missing/../../../x").is_err());
+    assert!(scope.resolve(&outside.join("secret.txt").to_string_lossy()).is_err());
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        assert!(scope.resolve("link/secret.txt").is_err());
+        assert!(scope.resolve("link/new.txt").is_err());
+    }
+    assert!(FileScope::new(&[], 1024).resolve("a.txt").is_err());
+}
+
+#[test]
+fn journal_undoes_writes_and_diffs_the_run() {
+    let root = temp_dir("journal-root");
+    let journal = FileJournal::new(temp_dir("journal"));
+    let existing = root.join("notes.md");
+    let created = root.join("new/summary.md");
+    fs::write(&existing, "one\ntwo\nthree\n").unwrap();
+
+    let first = journal.write(RUN, &existing, b"one\n2\nthree\n").unwrap();
+    journal.write(RUN, &existing, b"one\n2\nthree\nfour\n").unwrap();
+    journal.write(RUN, &created, b"summary\n").unwrap();
+    assert_eq!(journal.writes(RUN).unwrap().len(), 3);
+
+    let changes = journal.changes(RUN).unwrap();
+    assert_eq!(changes.len(), 2);
+    let modified = changes.iter().find(|c| c.path == existing).unwrap();
+    assert_eq!(modified.kind, ChangeKind::Modified);
+    assert_eq!(modified.writes, 2);
+    assert!(modified.diff.contains("-two\n+2\n"), "{}", modified.diff);
+    assert!(modified.diff.contains("+four\n"));
+    let new = changes.iter().find(|c| c.path == created).unwrap();
+    assert_eq!(new.kind, ChangeKind::Created);
+    assert!(new.diff.starts_with("--- /dev/null\n"));
+
+    assert_eq!(journal.undo(RUN, Some(&first.id)).unwrap(), vec![existing.clone()]);
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "one\ntwo\nthree\n");
+    assert!(journal.undo(RUN, Some(&first.id)).is_err());
+    assert_eq!(journal.changes(RUN).unwrap().len(), 1);
+
+    journal.undo(RUN, None).unwrap();
+    assert!(!created.exists());
+    assert!(journal.changes(RUN).unwrap().is_empty());
+}
+
+#[test]
+fn damaged_journals_refuse_further_writes() {
+    let root = temp_dir("journal-root");
+    let dir = temp_dir("journal");
+    let journal = FileJournal::new(dir.clone());
+    let notes = root.join("notes.md");
+    journal.write(RUN, &notes, b"first\n").unwrap();
+    fs::write(dir.join(RUN).join("journal.json"), "[{\"id\": ").unwrap();
+
+    assert!(journal.writes(RUN).is_err());
+    assert!(journal.write(RUN, &notes, b"second\n").is_err());
+    assert_eq!(fs::read_to_string(&notes).unwrap(), "first\n");
+    assert_eq!(fs::read_to_string(dir.join(RUN).join("journal.json")).unwrap(), "[{\"id\": ");
+    assert!(journal.undo(RUN, None).is_err());
+}
+
+#[test]
+fn journals_only_accept_generated_run_ids() {
+    let root = temp_dir("journal-root");
+    let dir = temp_dir("journal");
+    let journal = FileJournal::new(dir.join("runs"));
+    fs::write(dir.join("journal.json"), "[]").unwrap();
+
+    assert!(journal.write("..", &root.join("notes.md"), b"x\n").is_err());
+    assert!(journal.changes("../runs").is_err());
+    assert!(journal.undo("..", None).is_err());
+    assert!(!root.join("notes.md").exists());
+}
+
+#[test]
+fn unified_diff_shows_changes_with_context() {
+    let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+    assert_eq!(
+        unified_diff(old, new, "old", "new"),
+        "--- old\n+++ new\n@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n@@ -8,3 +8,4 @@\n h\n i\n j\n+k\n"
+    );
+    assert_eq!(unified_diff(old, old, "old", "new"), "");
+    assert_eq!(unified_diff("", "x\n", "/dev/null", "new"), "--- /dev/null\n+++ new\n@@ -0,0 +1,1 @@\n+x\n");
+}
@@ -0,0 +1,342 @@
+use super::{FileJournal, FileScope};
+use crate::agent::{tool_failed, AgentError, RiskLevel, Tool, ToolContext};
+use async_trait::async_trait;
+use regex::RegexBuilder;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const FILE_TOOL_NAMES: [&str; 4] = ["read_file", "list_files", "search_files", "write_file"];
+
+const MAX_LIST_ENTRIES: usize = 500;
+const MAX_SEARCH_RESULTS: usize = 200;
+const MAX_SEARCHED_FILES: usize = 5000;
+const MAX_MATCH_CHARS: usize = 200;
+
+// Folders that are never listed into or searched
+fn skipped(name: &str) -> bool {
+    matches!(name, ".git" | "node_modules" | "target" | "__pycache__")
+}
+
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, AgentError> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| tool_failed(e.to_string()))?
+        .map_err(tool_failed)
+}
+
+fn text_param<'a>(params: &'a Value, name: &str) -> Option<&'a str> {
+    params.get(name).and_then(|v| v.as_str())
+}
+
+fn count_param(params: &Value, name: &str) -> Option<usize> {
+    params.get(name).and_then(|v| v.as_u64()).map(|n| n as usize)
+}
+
+pub struct ReadFileTool {
+    pub scope: Arc<FileScope>,
+}
+
+#[async_trait]
+impl Tool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file in the project folder. Long files can be read in parts with start_line and max_lines."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path relative to the project folder" },
+                "start_line": { "type": "integer", "minimum": 1 },
+                "max_lines": { "type": "integer", "minimum": 1 },
+            },
+            "required": ["path"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let scope = self.scope.clone();
+        blocking(move || {
+            let path = scope.resolve(text_param(&params, "path").unwrap_or_default())?;
+            if !path.is_file() {
+                return Err(format!("{} is not a file", scope.display(&path)));
+            }
+            let mut bytes = Vec::new();
+            fs::File::open(&path)
+                .and_then(|file| file.take(scope.max_file_bytes + 1).read_to_end(&mut bytes))
+                .map_err(|e| format!("Failed to read {}: {}", scope.display(&path), e))?;
+            let cut = bytes.len() as u64 > scope.max_file_bytes;
+            if cut {
+                // Only whole lines of what fits
+                bytes.truncate(scope.max_file_bytes as usize);
+                let end = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+                bytes.truncate(end);
+            }
+            let text = String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", scope.display(&path)))?;
+
+            let start = count_param(&params, "start_line").unwrap_or(1).max(1);
+            let lines: Vec<&str> = text.lines().skip(start - 1).collect();
+            let shown = count_param(&params, "max_lines").unwrap_or(lines.len()).min(lines.len());
+            Ok(json!({
+                "path": scope.display(&path),
+                "start_line": start,
+                "content": lines[..shown].join("\n"),
+                "lines": shown,
+                // More of the file follows what's shown
+                "truncated": cut || shown < lines.len(),
+            }))
+        })
+        .await
+    }
+}
+
+fn list_into(scope: &FileScope, dir: &Path, depth: usize, entries: &mut Vec<Value>) -> bool {
+    let Ok(children) = fs::read_dir(dir) else {
+        return true;
+    };
+    let mut children: Vec<_> = children.filter_map(|e| e.ok()).collect();
+    children.sort_by_key(|e| e.file_name());
+    for child in children {
+        if entries.len() >= MAX_LIST_ENTRIES {
+            return false;
+        }
+        let Ok(kind) = child.file_type() else {
+            continue;
+        };
+        let path = child.path();
+        // Symlinks are reported but never followed
+        let (kind, size) = if kind.is_symlink() {
+            ("symlink", None)
+        } else if kind.is_dir() {
+            ("folder", None)
+        } else {
+            ("file", child.metadata().ok().map(|m| m.len()))
+        };
+        entries.push(json!({ "path": scope.display(&path), "kind": kind, "size": size }));
+        if kind == "folder" && depth > 1 && !skipped(&child.file_name().to_string_lossy()) && !list_into(scope, &path, depth - 1, entries) {
+            return false;
+        }
+    }
+    true
+}
+
+pub struct ListFilesTool {
+    pub scope: Arc<FileScope>,
+}
+
+#[async_trait]
+impl Tool for ListFilesTool {
+    fn name(&self) -> &str {
+        "list_files"
+    }
+
+    fn description(&self) -> &str {
+        "List the files and folders in a folder of the project, optionally several levels deep."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Folder relative to the project folder; the project folder itself by default" },
+                "depth": { "type": "integer", "minimum": 1, "maximum": 5 },
+            },
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let scope = self.scope.clone();
+        blocking(move || {
+            let dir = scope.resolve(text_param(&params, "path").unwrap_or("."))?;
+            if !dir.is_dir() {
+                return Err(format!("{} is not a folder", scope.display(&dir)));
+            }
+            let mut entries = Vec::new();
+            let complete = list_into(&scope, &dir, count_param(&params, "depth").unwrap_or(1), &mut entries);
+            Ok(json!({ "path": scope.display(&dir), "entries": entries, "truncated": !complete }))
+        })
+        .await
+    }
+}
+
+fn collect_searchable(dir: &Path, max_bytes: u64, files: &mut Vec<PathBuf>) {
+    let Ok(children) = fs::read_dir(dir) else {
+        return;
+    };
+    for child in children.filter_map(|e| e.ok()) {
+        if files.len() >= MAX_SEARCHED_FILES {
+            return;
+        }
+        let name = child.file_name().to_string_lossy().to_string();
+        let Ok(kind) = child.file_type() else {
+            continue;
+        };
+        if kind.is_dir() && !name.starts_with('.') && !skipped(&name) {
+            collect_searchable(&child.path(), max_bytes, files);
+        } else if kind.is_file() && child.metadata().is_ok_and(|m| m.len() <= max_bytes) {
+            files.push(child.path());
+        }
+    }
+}
+
+pub struct SearchFilesTool {
+    pub scope: Arc<FileScope>,
+}
+
+#[async_trait]
+impl Tool for SearchFilesTool {
+    fn name(&self) -> &str {
+        "search_files"
+    }
+
+    fn description(&self) -> &str {
+        "Search the text files under a folder of the project for lines matching a query, \
+         plain text unless regex is true. Returns the matching lines with their paths and line numbers."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "minLength": 1 },
+                "path": { "type": "string", "description": "Folder or file to search; the whole project by default" },
+                "regex": { "type": "boolean" },
+                "case_sensitive": { "type": "boolean" },
+                "max_results": { "type": "integer", "minimum": 1, "maximum": MAX_SEARCH_RESULTS },
+            },
+            "required": ["query"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let scope = self.scope.clone();
+        blocking(move || {
+            let query = text_param(&params, "query").unwrap_or_default();
+            let pattern = if params["regex"].as_bool().unwrap_or(false) {
+                query.to_string()
+            } else {
+                regex::escape(query)
+            };
+            let matcher = RegexBuilder::new(&pattern)
+                .case_insensitive(!params["case_sensitive"].as_bool().unwrap_or(false))
+                .size_limit(1 << 20)
+                .build()
+                .map_err(|e| format!("Invalid regex: {}", e))?;
+            let limit = count_param(&params, "max_results").unwrap_or(50).min(MAX_SEARCH_RESULTS);
+
+            let root = scope.resolve(text_param(&params, "path").unwrap_or("."))?;
+            let mut files = Vec::new();
+            if root.is_file() {
+                files.push(root);
+            } else {
+                collect_searchable(&root, scope.max_file_bytes, &mut files);
+            }
+            files.sort();
+
+            let mut matches = Vec::new();
+            'files: for file in &files {
+                let Ok(text) = fs::read_to_string(file) else {
+                    continue;
+                };
+                for (number, line) in text.lines().enumerate() {
+                    if matcher.is_match(line) {
+                        if matches.len() == limit {
+                            break 'files;
+                        }
+                        let line: String = line.trim().chars().take(MAX_MATCH_CHARS).collect();
+                        matches.push(json!({ "path": scope.display(file), "line": number + 1, "text": line }));
+                    }
+                }
+            }
+            let truncated = matches.len() == limit || files.len() >= MAX_SEARCHED_FILES;
+            Ok(json!({ "matches": matches, "files_searched": files.len(), "truncated": truncated }))
+        })
+        .await
+    }
+}
+
+// Writes go through the journal so they can be undone and shown as a diff of the run
+pub struct WriteFileTool {
+    pub scope: Arc<FileScope>,
+    pub journal: Arc<FileJournal>,
+}
+
+#[async_trait]
+impl Tool for WriteFileTool {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn description(&self) -> &str {
+        "Create a text file in the project folder or replace its whole content. Missing folders are created."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path relative to the project folder" },
+                "content": { "type": "string" },
+            },
+            "required": ["path", "content"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::WritesFiles
+    }
+
+    async fn call(&self, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let scope = self.scope.clone();
+        let journal = self.journal.clone();
+        let run_id = context.run_id.clone();
+        blocking(move || {
+            let path = scope.resolve(text_param(&params, "path").unwrap_or_default())?;
+            let content = text_param(&params, "content").unwrap_or_default();
+            if content.len() as u64 > scope.max_file_bytes {
+                return Err(format!(
+                    "The content is {} bytes; files may be at most {} bytes",
+                    content.len(),
+                    scope.max_file_bytes
+                ));
+            }
+            let write = journal.write(&run_id, &path, content.as_bytes())?;
+            Ok(json!({
+                "path": scope.display(&path),
+                "bytes": write.bytes,
+                "created": write.backup.is_none(),
+            }))
+        })
+        .await
+    }
+}
+
+pub fn file_tools(scope: Arc<FileScope>, journal: Arc<FileJournal>) -> Vec<Arc<dyn Tool>> {
+    vec![
+        Arc::new(ReadFileTool { scope: scope.clone() }),
+        Arc::new(ListFilesTool { scope: scope.clone() }),
+        Arc::new(SearchFilesTool { scope: scope.clone() }),
+        Arc::new(WriteFileTool { scope, journal }),
+    ]
+}
@@ -0,0 +1,132 @@
+use super::{tool_failed, AgentError, RiskLevel, Tool, ToolContext};
+use crate::bibliography::{Library, LibraryQuery};
+use crate::rag::DocumentIndex;
+use crate::vector::MetadataFilter;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const DEFAULT_PASSAGES: u64 = 5;
+const MAX_PASSAGES: u64 = 20;
+const DEFAULT_ENTRIES: usize = 10;
+const MAX_ENTRIES: usize = 50;
+
+// Hybrid search over the indexed documents; takes {"query": "..."} and returns the best passages
+pub struct DocumentSearchTool {
+    pub documents: Arc<DocumentIndex>,
+}
+
+#[async_trait]
+impl Tool for DocumentSearchTool {
+    fn name(&self) -> &str {
+        "search_documents"
+    }
+
+    fn description(&self) -> &str {
+        "Search the indexed local documents by meaning and keywords and return the best matching passages \
+         with their document and page. Name a document to search only that one."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "minLength": 1 },
+                "document": { "type": "string", "description": "Path of an indexed document, as given in earlier results" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_PASSAGES },
+            },
+            "required": ["query"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let query = params["query"].as_str().unwrap_or_default();
+        // The configured number of passages from all documents, unless the call narrows it down
+        let chunks = match (params["document"].as_str(), params["limit"].as_u64()) {
+            (None, None) => self.documents.retrieve(query).await,
+            (document, limit) => {
+                let filter = document
+                    .map(|d| MetadataFilter([("document".to_string(), vec![d.to_string()])].into()))
+                    .unwrap_or_default();
+                let limit = limit.unwrap_or(DEFAULT_PASSAGES) as usize;
+                self.documents.search(query, &filter, limit).await
+            }
+        }
+        .map_err(tool_failed)?;
+        Ok(Value::Array(
+            chunks
+                .into_iter()
+                .map(|chunk| json!({ "document": chunk.document, "page": chunk.page, "text": chunk.text }))
+                .collect(),
+        ))
+    }
+}
+
+// Looks up entries in the imported bibliography so answers can cite papers by their key
+pub struct LibrarySearchTool {
+    pub library: Arc<Library>,
+}
+
+#[async_trait]
+impl Tool for LibrarySearchTool {
+    fn name(&self) -> &str {
+        "search_library"
+    }
+
+    fn description(&self) -> &str {
+        "Search the imported bibliography by author, year range, keyword or words in the title and abstract. \
+         Returns matching entries with their cite key and, if linked, the indexed document of the paper."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "author": { "type": "string", "description": "Part of an author's name" },
+                "year_from": { "type": "integer" },
+                "year_to": { "type": "integer" },
+                "keyword": { "type": "string" },
+                "text": { "type": "string", "description": "Words looked up in titles and abstracts" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_ENTRIES },
+            },
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let mut query: LibraryQuery = serde_json::from_value(params).map_err(|e| tool_failed(e.to_string()))?;
+        query.limit = Some(query.limit.unwrap_or(DEFAULT_ENTRIES).min(MAX_ENTRIES));
+        let entries = self.library.query(&query).await;
+        Ok(Value::Array(
+            entries
+                .into_iter()
+                .map(|entry| {
+                    let authors: Vec<String> = entry
+                        .authors
+                        .iter()
+                        .map(|a| match &a.given {
+                            Some(given) => format!("{}, {}", a.family, given),
+                            None => a.family.clone(),
+                        })
+                        .collect();
+                    json!({
+                        "key": entry.key,
+                        "title": entry.title,
+                        "authors": authors,
+                        "year": entry.year,
+                        "venue": entry.venue,
+                        "doi": entry.doi,
+                        "document": entry.document,
+                    })
+                })
+                .collect(),
+        ))
+    }
+}
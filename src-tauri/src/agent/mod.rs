@@ -2,6 +2,30 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod data;
+pub use data::*;
+mod files;
+pub use files::*;
+mod knowledge;
+pub use knowledge::*;
+mod policy;
+pub use policy::*;
+mod registry;
+pub use registry::*;
+mod run;
+pub use run::*;
+mod runtime;
+pub use runtime::*;
+mod tools;
+pub use tools::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
 
 // Custom error type for agent operations
 #[derive(Debug)]
@@ -56,6 +80,9 @@ pub struct Memory {
     pub observations: Vec<Observation>,
     pub thoughts: Vec<Thought>,
     pub actions_taken: Vec<Action>,
+    // Approval requests for risky actions and how the user answered them
+    #[serde(default)]
+    pub decisions: Vec<ApprovalRecord>,
 }
 
 // Main trait defining the agent's capabilities
@@ -78,7 +105,7 @@ pub trait Agent {
 // Helper struct for implementing agents
 pub struct AgentBuilder {
     pub llm_config: LLMConfig,
-    pub tools: Vec<Tool>,
+    pub tools: Arc<ToolRegistry>,
     pub initial_memory: Option<Memory>,
 }
 
@@ -89,24 +116,38 @@ pub struct LLMConfig {
     pub max_tokens: u32,
 }
 
+// Who a tool is called for
 #[derive(Debug, Clone)]
-pub struct Tool {
-    pub name: String,
-    pub description: String,
-    pub handler: Box<dyn Fn(serde_json::Value) -> Result<serde_json::Value, AgentError> + Send + Sync>,
+pub struct ToolContext {
+    pub run_id: String,
+}
+
+// Something the agent can do. Parameters are described by a JSON schema, which arguments are checked
+// against and which models with function calling are given directly
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    fn parameters(&self) -> serde_json::Value;
+    fn risk(&self) -> RiskLevel;
+    // Overrides the registry's default time limit
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+    async fn call(&self, params: serde_json::Value, context: &ToolContext) -> Result<serde_json::Value, AgentError>;
 }
 
 impl AgentBuilder {
     pub fn new(llm_config: LLMConfig) -> Self {
         Self {
             llm_config,
-            tools: Vec::new(),
+            tools: Arc::new(ToolRegistry::new(ToolLimits::default())),
             initial_memory: None,
         }
     }
 
-    pub fn with_tool(mut self, tool: Tool) -> Self {
-        self.tools.push(tool);
+    pub fn with_tools(mut self, tools: Arc<ToolRegistry>) -> Self {
+        self.tools = tools;
         self
     }
 
@@ -114,6 +155,21 @@ impl AgentBuilder {
         self.initial_memory = Some(memory);
         self
     }
+
+    pub fn build(self, context: AgentContext, record: AgentRun) -> ToolAgent {
+        ToolAgent {
+            context,
+            llm_config: self.llm_config,
+            tools: self.tools,
+            memory: self.initial_memory.unwrap_or(Memory {
+                observations: Vec::new(),
+                thoughts: Vec::new(),
+                actions_taken: Vec::new(),
+                decisions: Vec::new(),
+            }),
+            record,
+        }
+    }
 }
 
 // Default implementation for common agent behaviors
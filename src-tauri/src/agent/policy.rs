@@ -0,0 +1,87 @@
+use super::Action;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::{oneshot, Mutex};
+
+// Ordered from least to most risky
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RiskLevel {
+    ReadOnly,
+    WritesFiles,
+    ExecutesCode,
+    Network,
+}
+
+// Actions of tools at or above the threshold wait for the user
+#[derive(Debug, Clone, Copy)]
+pub struct ApprovalPolicy {
+    pub threshold: RiskLevel,
+}
+
+impl ApprovalPolicy {
+    pub fn requires_approval(&self, risk: RiskLevel) -> bool {
+        risk >= self.threshold
+    }
+}
+
+// Sent to the frontend as an agent-approval-request event
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub run_id: String,
+    pub tool: String,
+    pub risk: RiskLevel,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum ApprovalDecision {
+    Approve,
+    // Run the action with these parameters instead
+    Edit { parameters: Value },
+    Reject {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+// What was asked and what the user decided, kept in the agent's memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub action: Action,
+    pub risk: RiskLevel,
+    pub decision: ApprovalDecision,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    pub decided_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Actions waiting for a decision, shared between the running agents and the commands that resolve them
+#[derive(Default)]
+pub struct ApprovalGate {
+    pending: Mutex<HashMap<String, (ApprovalRequest, oneshot::Sender<ApprovalDecision>)>>,
+}
+
+impl ApprovalGate {
+    pub async fn open(&self, request: ApprovalRequest) -> oneshot::Receiver<ApprovalDecision> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(request.id.clone(), (request, tx));
+        rx
+    }
+
+    pub async fn resolve(&self, request_id: &str, decision: ApprovalDecision) -> Result<(), String> {
+        let (_, tx) = self
+            .pending
+            .lock()
+            .await
+            .remove(request_id)
+            .ok_or_else(|| "No action is waiting for that approval".to_string())?;
+        tx.send(decision).map_err(|_| "The agent run has stopped".to_string())
+    }
+
+    pub async fn pending(&self) -> Vec<ApprovalRequest> {
+        self.pending.lock().await.values().map(|(r, _)| r.clone()).collect()
+    }
+}
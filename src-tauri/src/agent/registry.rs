@@ -0,0 +1,219 @@
+use super::{AgentError, AgentErrorKind, RiskLevel, Tool, ToolContext};
+use crate::llm_bridge::ToolDefinition;
+use crate::structured::SchemaValidator;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub const DEFAULT_TOOL_TIMEOUT_SECONDS: u64 = 60;
+pub const DEFAULT_MAX_TOOL_OUTPUT_BYTES: usize = 16 * 1024;
+
+pub(super) fn tool_failed(message: String) -> AgentError {
+    AgentError {
+        message,
+        kind: AgentErrorKind::ActionFailed,
+    }
+}
+
+// What a tool looks like to the frontend and to the model
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub risk: RiskLevel,
+    // Built-in tools come back with every config load and can't be removed
+    pub builtin: bool,
+}
+
+impl ToolSpec {
+    pub fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            parameters: self.parameters.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolLimits {
+    pub timeout: Duration,
+    // Per tool, overriding both the default and the tool's own timeout
+    pub timeouts: HashMap<String, Duration>,
+    // Results longer than this, as JSON, are cut off before the model sees them
+    pub max_output_bytes: usize,
+}
+
+impl Default for ToolLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TOOL_TIMEOUT_SECONDS),
+            timeouts: HashMap::new(),
+            max_output_bytes: DEFAULT_MAX_TOOL_OUTPUT_BYTES,
+        }
+    }
+}
+
+struct Entry {
+    tool: Arc<dyn Tool>,
+    spec: ToolSpec,
+    validator: Arc<SchemaValidator>,
+}
+
+// Names the model can call: letters, digits, underscores and dashes, as function calling requires
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 64 && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+// The tools agents can use, keyed by name; arguments are checked against each tool's schema before it runs
+pub struct ToolRegistry {
+    tools: RwLock<BTreeMap<String, Entry>>,
+    limits: RwLock<ToolLimits>,
+}
+
+impl ToolRegistry {
+    pub fn new(limits: ToolLimits) -> Self {
+        Self {
+            tools: RwLock::new(BTreeMap::new()),
+            limits: RwLock::new(limits),
+        }
+    }
+
+    pub fn set_limits(&self, limits: ToolLimits) {
+        *self.limits.write().unwrap() = limits;
+    }
+
+    fn insert(&self, tool: Arc<dyn Tool>, builtin: bool) -> Result<ToolSpec, String> {
+        let name = tool.name().to_string();
+        if !valid_name(&name) {
+            return Err(format!(
+                "Tool names may only use letters, digits, underscores and dashes, up to 64 characters: {}",
+                name
+            ));
+        }
+        let parameters = tool.parameters();
+        let validator = SchemaValidator::new(&parameters).map_err(|e| format!("{}: {}", name, e))?;
+        let mut tools = self.tools.write().unwrap();
+        if !builtin && tools.get(&name).is_some_and(|entry| entry.spec.builtin) {
+            return Err(format!("{} is a built-in tool", name));
+        }
+        let spec = ToolSpec {
+            name: name.clone(),
+            description: tool.description().to_string(),
+            parameters,
+            risk: tool.risk(),
+            builtin,
+        };
+        tools.insert(
+            name,
+            Entry {
+                tool,
+                spec: spec.clone(),
+                validator: Arc::new(validator),
+            },
+        );
+        Ok(spec)
+    }
+
+    pub fn register_builtin(&self, tool: Arc<dyn Tool>) -> Result<ToolSpec, String> {
+        self.insert(tool, true)
+    }
+
+    // Adds or replaces a user tool
+    pub fn register(&self, tool: Arc<dyn Tool>) -> Result<ToolSpec, String> {
+        self.insert(tool, false)
+    }
+
+    pub fn unregister(&self, name: &str) -> Result<(), String> {
+        let mut tools = self.tools.write().unwrap();
+        match tools.get(name) {
+            None => Err(format!("There is no tool named {}", name)),
+            Some(entry) if entry.spec.builtin => Err(format!("{} is a built-in tool", name)),
+            Some(_) => {
+                tools.remove(name);
+                Ok(())
+            }
+        }
+    }
+
+    // Drops a built-in tool that no longer applies, e.g. after its settings were removed from the config
+    pub fn remove_builtin(&self, name: &str) {
+        let mut tools = self.tools.write().unwrap();
+        if tools.get(name).is_some_and(|entry| entry.spec.builtin) {
+            tools.remove(name);
+        }
+    }
+
+    pub fn spec(&self, name: &str) -> Option<ToolSpec> {
+        self.tools.read().unwrap().get(name).map(|entry| entry.spec.clone())
+    }
+
+    pub fn specs(&self) -> Vec<ToolSpec> {
+        self.tools.read().unwrap().values().map(|entry| entry.spec.clone()).collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.tools.read().unwrap().keys().cloned().collect()
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.read().unwrap().values().map(|entry| entry.spec.definition()).collect()
+    }
+
+    // Validates the arguments, runs the tool within its time limit and caps the size of the result
+    pub async fn invoke(&self, name: &str, params: Value, context: &ToolContext) -> Result<Value, AgentError> {
+        let (tool, validator) = {
+            let tools = self.tools.read().unwrap();
+            let entry = tools
+                .get(name)
+                .ok_or_else(|| tool_failed(format!("There is no tool named {}", name)))?;
+            (entry.tool.clone(), entry.validator.clone())
+        };
+        let errors = validator.errors(&params);
+        if !errors.is_empty() {
+            return Err(tool_failed(format!(
+                "Invalid parameters for {}:\n- {}",
+                name,
+                errors.join("\n- ")
+            )));
+        }
+        let (timeout, max_output_bytes) = {
+            let limits = self.limits.read().unwrap();
+            let timeout = limits
+                .timeouts
+                .get(name)
+                .copied()
+                .or_else(|| tool.timeout())
+                .unwrap_or(limits.timeout);
+            (timeout, limits.max_output_bytes)
+        };
+        let output = tokio::time::timeout(timeout, tool.call(params, context))
+            .await
+            .map_err(|_| tool_failed(format!("{} timed out after {} seconds", name, timeout.as_secs_f32())))??;
+        Ok(limit_output(output, max_output_bytes))
+    }
+}
+
+// Results over the limit are replaced by the start of their JSON text and a note of how much was cut
+pub fn limit_output(output: Value, max_bytes: usize) -> Value {
+    let text = match &output {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.len() <= max_bytes {
+        return output;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Value::String(format!(
+        "{}\n[Output truncated: showing {} of {} bytes]",
+        &text[..end],
+        end,
+        text.len()
+    ))
+}
@@ -0,0 +1,146 @@
+use super::{Action, Memory, Observation, RiskLevel, Thought};
+use crate::jobs::{JobRecord, JobStore};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+    // Saved as running, but nothing is working on it, e.g. after the app was closed
+    Interrupted,
+}
+
+// What the run was started with, so it resumes the same way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRunConfig {
+    pub provider: String,
+    pub model: String,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    pub max_steps: usize,
+    pub approval_threshold: RiskLevel,
+    pub tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentStep {
+    pub number: usize,
+    pub thought: Thought,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<Action>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observation: Option<Observation>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+}
+
+// A run and its full trace; checkpointed after every step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRun {
+    pub id: String,
+    pub goal: String,
+    pub config: AgentRunConfig,
+    pub status: RunStatus,
+    #[serde(default)]
+    pub steps: Vec<AgentStep>,
+    // What the agent resumes with
+    pub memory: Memory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentRunSummary {
+    pub id: String,
+    pub goal: String,
+    pub status: RunStatus,
+    pub step_count: usize,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&AgentRun> for AgentRunSummary {
+    fn from(run: &AgentRun) -> Self {
+        Self {
+            id: run.id.clone(),
+            goal: run.goal.clone(),
+            status: run.status,
+            step_count: run.steps.len(),
+            error: run.error.clone(),
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+impl JobRecord for AgentRun {
+    const NOUN: &'static str = "Agent run";
+    type Summary = AgentRunSummary;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    fn set_updated_at(&mut self, timestamp: i64) {
+        self.updated_at = timestamp;
+    }
+}
+
+// One JSON file per run under the agent-runs directory
+pub struct AgentRunStore {
+    runs: JobStore<AgentRun>,
+}
+
+impl AgentRunStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            runs: JobStore::new(dir),
+        }
+    }
+
+    pub fn save(&self, run: &mut AgentRun) -> Result<(), String> {
+        self.runs.save(run)
+    }
+
+    // Runs saved as running that no task is working on show up as interrupted
+    async fn settle(&self, mut run: AgentRun) -> AgentRun {
+        if run.status == RunStatus::Running && !self.runs.is_running(&run.id).await {
+            run.status = RunStatus::Interrupted;
+        }
+        run
+    }
+
+    pub async fn load(&self, run_id: &str) -> Result<AgentRun, String> {
+        let run = self.runs.load(run_id)?;
+        Ok(self.settle(run).await)
+    }
+
+    pub async fn list(&self) -> Vec<AgentRunSummary> {
+        let mut runs = Vec::new();
+        for run in self.runs.records() {
+            runs.push(AgentRunSummary::from(&self.settle(run).await));
+        }
+        runs
+    }
+
+    // False if the run is already being worked on
+    pub async fn claim(&self, run_id: &str) -> bool {
+        self.runs.claim(run_id).await
+    }
+
+    pub async fn release(&self, run_id: &str) {
+        self.runs.release(run_id).await;
+    }
+}
@@ -0,0 +1,372 @@
+use super::utils::{create_observation, create_thought};
+use super::{
+    Action, Agent, AgentBehavior, AgentError, AgentErrorKind, AgentRun, AgentRunStore, AgentStep, ApprovalDecision,
+    ApprovalGate, ApprovalPolicy, ApprovalRecord, ApprovalRequest, LLMConfig, Memory, Observation, RunStatus, Thought,
+    ToolContext, ToolRegistry,
+};
+use crate::budget::{complete_with_budget, BudgetTracker, UsageContext};
+use crate::chat::{new_id, split_reasoning};
+use crate::llm_bridge::{GenerationParams, LLMBridge, LLMRequest, Prompt};
+use crate::structured::parse_reply;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::Emitter;
+
+pub const AGENT_PROGRESS_EVENT: &str = "agent-progress";
+pub const AGENT_APPROVAL_EVENT: &str = "agent-approval-request";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentProgress {
+    pub run_id: String,
+    pub step: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thought: Option<Thought>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<Action>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observation: Option<Observation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// What a ReAct-style reply asks for
+#[derive(Debug, Clone)]
+pub enum NextStep {
+    Act(Action),
+    Answer(String),
+}
+
+// Reads "Action: <tool>" with "Action Input: <json>", or "Final Answer: <text>"; a reply in neither
+// form is taken as the answer
+pub fn parse_next_step(content: &str) -> NextStep {
+    let (_, reply) = split_reasoning(content);
+    if let Some(start) = reply.find("Final Answer:") {
+        return NextStep::Answer(reply[start + "Final Answer:".len()..].trim().to_string());
+    }
+    let Some(start) = reply.find("Action:") else {
+        let answer = reply.trim();
+        return NextStep::Answer(answer.strip_prefix("Thought:").unwrap_or(answer).trim().to_string());
+    };
+    let rest = &reply[start + "Action:".len()..];
+    let (tool, input) = match rest.find("Action Input:") {
+        Some(at) => (&rest[..at], &rest[at + "Action Input:".len()..]),
+        None => (rest, ""),
+    };
+    NextStep::Act(Action {
+        action_type: tool.trim().trim_matches('`').to_string(),
+        // Left as text when it isn't JSON, so the tool can say what's wrong
+        parameters: parse_reply(input).unwrap_or_else(|_| Value::String(input.trim().to_string())),
+    })
+}
+
+// Everything an agent run needs from the app
+pub struct AgentContext {
+    pub app: tauri::AppHandle,
+    pub run_id: String,
+    pub llm_bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub budget: Option<Arc<BudgetTracker>>,
+    pub gate: Arc<ApprovalGate>,
+    pub policy: ApprovalPolicy,
+    pub store: Arc<AgentRunStore>,
+}
+
+// Works towards a goal by alternating thoughts and tool calls, asking before risky actions
+pub struct ToolAgent {
+    pub context: AgentContext,
+    pub llm_config: LLMConfig,
+    pub tools: Arc<ToolRegistry>,
+    pub memory: Memory,
+    // The durable record of this run, saved after every step
+    pub record: AgentRun,
+}
+
+impl ToolAgent {
+    fn system_prompt(&self) -> String {
+        let tools: Vec<String> = self
+            .tools
+            .specs()
+            .iter()
+            .map(|t| format!("- {}: {} Parameters: {}", t.name, t.description, t.parameters))
+            .collect();
+        format!(
+            "You are a research assistant working towards the user's goal step by step with these tools:\n{}\n\n\
+             Reply in exactly this form:\nThought: your reasoning\nAction: the tool's name\nAction Input: its parameters as a JSON object\n\n\
+             You will get the tool's result as an observation. Once you can answer, reply instead with:\n\
+             Thought: your reasoning\nFinal Answer: the answer",
+            tools.join("\n")
+        )
+    }
+
+    // Thoughts and observations in the order they happened
+    fn transcript(&self) -> Vec<Prompt> {
+        let thoughts = self.memory.thoughts.iter().map(|t| {
+            (
+                t.timestamp,
+                Prompt {
+                    role: "assistant".to_string(),
+                    content: t.content.clone(),
+                },
+            )
+        });
+        let observations = self.memory.observations.iter().map(|o| {
+            (
+                o.timestamp,
+                Prompt {
+                    role: "user".to_string(),
+                    content: format!("Observation: {}", o.content),
+                },
+            )
+        });
+        let mut steps: Vec<_> = thoughts.chain(observations).collect();
+        steps.sort_by_key(|(timestamp, _)| *timestamp);
+        steps.into_iter().map(|(_, prompt)| prompt).collect()
+    }
+
+    pub fn emit(&self, progress: AgentProgress) {
+        if let Err(e) = self.context.app.emit(AGENT_PROGRESS_EVENT, progress) {
+            log::warn!("Failed to emit agent progress: {}", e);
+        }
+    }
+
+    // Waits for the user on actions the policy holds back; returns the action to run, or why it was rejected
+    async fn approve(&mut self, action: Action) -> Result<Action, String> {
+        let Some(tool) = self.tools.spec(&action.action_type) else {
+            return Ok(action);
+        };
+        let risk = tool.risk;
+        if !self.context.policy.requires_approval(risk) {
+            return Ok(action);
+        }
+        let request = ApprovalRequest {
+            id: new_id(),
+            run_id: self.context.run_id.clone(),
+            tool: action.action_type.clone(),
+            risk,
+            parameters: action.parameters.clone(),
+        };
+        let requested_at = chrono::Utc::now();
+        let decision = self.context.gate.open(request.clone()).await;
+        if let Err(e) = self.context.app.emit(AGENT_APPROVAL_EVENT, request) {
+            log::warn!("Failed to emit approval request: {}", e);
+        }
+        let decision = decision.await.unwrap_or(ApprovalDecision::Reject {
+            reason: Some("The approval request was dropped".to_string()),
+        });
+        self.memory.decisions.push(ApprovalRecord {
+            action: action.clone(),
+            risk,
+            decision: decision.clone(),
+            requested_at,
+            decided_at: chrono::Utc::now(),
+        });
+        match decision {
+            ApprovalDecision::Approve => Ok(action),
+            ApprovalDecision::Edit { parameters } => Ok(Action { parameters, ..action }),
+            ApprovalDecision::Reject { reason: Some(reason) } => Err(format!("The user rejected this action: {}", reason)),
+            ApprovalDecision::Reject { reason: None } => Err("The user rejected this action".to_string()),
+        }
+    }
+
+    fn checkpoint(&mut self) -> Result<(), AgentError> {
+        self.record.memory = self.memory.clone();
+        self.context.store.save(&mut self.record).map_err(|message| AgentError {
+            message,
+            kind: AgentErrorKind::MemoryAccessFailed,
+        })
+    }
+
+    // Steps until the model gives an answer or the run reaches its step limit, counting steps from before a resume
+    async fn advance(&mut self) -> Result<String, AgentError> {
+        while self.record.steps.len() < self.record.config.max_steps {
+            let started_at = chrono::Utc::now();
+            let clock = Instant::now();
+            let goal = self.record.goal.clone();
+            let observations = self.run_flow_of_thoughts(&goal).await?;
+            let Some(thought) = self.memory.thoughts.last().cloned() else {
+                break;
+            };
+            let answer = match parse_next_step(&thought.content) {
+                NextStep::Answer(answer) => Some(answer),
+                NextStep::Act(_) => None,
+            };
+            let step = AgentStep {
+                number: self.record.steps.len() + 1,
+                thought,
+                action: self.memory.actions_taken.last().cloned().filter(|_| !observations.is_empty()),
+                observation: observations.into_iter().last(),
+                started_at,
+                duration_ms: clock.elapsed().as_millis() as u64,
+            };
+            self.record.steps.push(step.clone());
+            self.checkpoint()?;
+            self.emit(AgentProgress {
+                run_id: self.context.run_id.clone(),
+                step: step.number,
+                thought: Some(step.thought),
+                action: step.action,
+                observation: step.observation,
+                answer: answer.clone(),
+                error: None,
+            });
+            if let Some(answer) = answer {
+                return Ok(answer);
+            }
+        }
+        Err(AgentError {
+            message: format!("Stopped after {} steps without an answer", self.record.config.max_steps),
+            kind: AgentErrorKind::ActionFailed,
+        })
+    }
+
+    // Runs to the end and leaves the outcome in the record; failed and interrupted runs can be resumed later
+    pub async fn run(mut self) {
+        let run_id = self.context.run_id.clone();
+        if !self.context.store.claim(&run_id).await {
+            log::warn!("Agent run {} is already running", run_id);
+            return;
+        }
+        self.record.status = RunStatus::Running;
+        self.record.error = None;
+        let outcome = match self.checkpoint() {
+            Ok(()) => self.advance().await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(answer) => {
+                self.record.status = RunStatus::Completed;
+                self.record.answer = Some(answer);
+            }
+            Err(e) => {
+                log::warn!("Agent run {} failed: {}", run_id, e);
+                self.record.status = RunStatus::Failed;
+                self.record.error = Some(e.message.clone());
+                self.emit(AgentProgress {
+                    run_id: run_id.clone(),
+                    step: self.record.steps.len(),
+                    thought: None,
+                    action: None,
+                    observation: None,
+                    answer: None,
+                    error: Some(e.message),
+                });
+            }
+        }
+        if let Err(e) = self.checkpoint() {
+            log::warn!("Failed to save agent run {}: {}", run_id, e);
+        }
+        self.context.store.release(&run_id).await;
+    }
+}
+
+#[async_trait]
+impl Agent for ToolAgent {
+    async fn think(&mut self, context: &str) -> Result<Thought, AgentError> {
+        let mut messages = vec![
+            Prompt {
+                role: "system".to_string(),
+                content: self.system_prompt(),
+            },
+            Prompt {
+                role: "user".to_string(),
+                content: format!("Goal: {}", context),
+            },
+        ];
+        messages.extend(self.transcript());
+        let request = LLMRequest {
+            messages,
+            params: GenerationParams {
+                temperature: Some(self.llm_config.temperature),
+                max_tokens: Some(self.llm_config.max_tokens),
+            },
+            format: None,
+            tools: self.tools.definitions(),
+        };
+        let ctx = UsageContext {
+            provider: self.context.provider.clone(),
+            model: self.context.llm_bridge.model().to_string(),
+            session_id: None,
+            agent_run_id: Some(self.context.run_id.clone()),
+        };
+        let response = complete_with_budget(
+            &self.context.app,
+            self.context.llm_bridge.as_ref(),
+            self.context.budget.as_deref(),
+            ctx,
+            request,
+        )
+        .await
+        .map_err(|e| AgentError {
+            message: e.error,
+            kind: AgentErrorKind::LLMError,
+        })?;
+        // Native tool calls are written into the thought in the ReAct form, so the transcript and
+        // resumed runs read the same whichever way the model called the tool
+        let content = match response.tool_calls.first() {
+            Some(call) => format!(
+                "{}\nAction: {}\nAction Input: {}",
+                response.content.trim(),
+                call.name,
+                call.arguments
+            )
+            .trim_start()
+            .to_string(),
+            None => response.content,
+        };
+        Ok(create_thought(content))
+    }
+
+    async fn interpret_thought(&self, thought: &Thought) -> Result<Option<Action>, AgentError> {
+        Ok(match parse_next_step(&thought.content) {
+            NextStep::Act(action) => Some(action),
+            NextStep::Answer(_) => None,
+        })
+    }
+
+    async fn observe(&mut self, target: &str) -> Result<Observation, AgentError> {
+        let observation = create_observation(target.to_string(), "user".to_string(), None);
+        self.update_memory(None, Some(observation.clone()), None);
+        Ok(observation)
+    }
+
+    // Tool failures and rejected actions become observations, so the model can change course
+    async fn execute(&mut self, action: Action) -> Result<Observation, AgentError> {
+        let source = action.action_type.clone();
+        let action = match self.approve(action).await {
+            Ok(action) => action,
+            Err(rejection) => return Ok(create_observation(rejection, source, None)),
+        };
+        Ok(match self.use_tool(&action.action_type, action.parameters.clone()).await {
+            Ok(result) => {
+                let content = match &result {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                create_observation(content, source, Some(result))
+            }
+            Err(e) => create_observation(format!("Error: {}", e), source, None),
+        })
+    }
+
+    fn get_memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    fn update_memory(&mut self, thought: Option<Thought>, observation: Option<Observation>, action: Option<Action>) {
+        self.memory.thoughts.extend(thought);
+        self.memory.observations.extend(observation);
+        self.memory.actions_taken.extend(action);
+    }
+
+    async fn use_tool(&self, tool_name: &str, params: Value) -> Result<Value, AgentError> {
+        let context = ToolContext {
+            run_id: self.context.run_id.clone(),
+        };
+        self.tools.invoke(tool_name, params, &context).await
+    }
+}
@@ -0,0 +1,177 @@
+use super::*;
+use serde_json::json;
+
+const RUN: &str = "7f1c2a9e-3b4d-4e5f-8a6b-9c0d1e2f3a4b";
+
+#[test]
+fn reads_actions_and_answers_from_replies() {
+    let reply = "Thought: I should compute the mean.\nAction: run_python\nAction Input: {\"code\": \"print(2)\"}";
+    let NextStep::Act(action) = parse_next_step(reply) else {
+        panic!("expected an action");
+    };
+    assert_eq!(action.action_type, "run_python");
+    assert_eq!(action.parameters, json!({ "code": "print(2)" }));
+
+    let NextStep::Act(action) = parse_next_step("Action: `run_python`\nAction Input: print(2)") else {
+        panic!("expected an action");
+    };
+    assert_eq!(action.action_type, "run_python");
+    assert_eq!(action.parameters, json!("print(2)"));
+
+    let answer = "<think>\nDone.\n</think>\n\nThought: I know it.\nFinal Answer: The mean is 2.";
+    assert!(matches!(parse_next_step(answer), NextStep::Answer(a) if a == "The mean is 2."));
+    assert!(matches!(parse_next_step("Thought: It is 2."), NextStep::Answer(a) if a == "It is 2."));
+}
+
+#[test]
+fn policy_holds_back_actions_at_or_above_the_threshold() {
+    let policy = ApprovalPolicy {
+        threshold: RiskLevel::ExecutesCode,
+    };
+    assert!(!policy.requires_approval(RiskLevel::ReadOnly));
+    assert!(!policy.requires_approval(RiskLevel::WritesFiles));
+    assert!(policy.requires_approval(RiskLevel::ExecutesCode));
+    assert!(policy.requires_approval(RiskLevel::Network));
+    assert_eq!(serde_json::from_value::<RiskLevel>(json!("writes-files")).unwrap(), RiskLevel::WritesFiles);
+}
+
+#[tokio::test]
+async fn gate_delivers_decisions_to_the_waiting_run() {
+    let gate = ApprovalGate::default();
+    let request = ApprovalRequest {
+        id: "a1".to_string(),
+        run_id: RUN.to_string(),
+        tool: "run_python".to_string(),
+        risk: RiskLevel::ExecutesCode,
+        parameters: json!({ "code": "import os" }),
+    };
+    let waiting = gate.open(request).await;
+    assert_eq!(gate.pending().await.len(), 1);
+
+    let decision: ApprovalDecision =
+        serde_json::from_value(json!({ "decision": "edit", "parameters": { "code": "print(1)" } })).unwrap();
+    gate.resolve("a1", decision.clone()).await.unwrap();
+    assert_eq!(waiting.await.unwrap(), decision);
+    assert!(gate.pending().await.is_empty());
+    assert!(gate.resolve("a1", ApprovalDecision::Approve).await.is_err());
+    assert_eq!(
+        serde_json::from_value::<ApprovalDecision>(json!({ "decision": "reject" })).unwrap(),
+        ApprovalDecision::Reject { reason: None }
+    );
+}
+
+#[tokio::test]
+async fn saved_runs_left_running_are_reported_as_interrupted() {
+    let store = AgentRunStore::new(std::env::temp_dir().join(format!("ccru-agent-runs-{}", uuid::Uuid::new_v4())));
+    let thought = Thought {
+        content: "Action: run_python\nAction Input: print(1)".to_string(),
+        timestamp: chrono::Utc::now(),
+    };
+    let mut run = AgentRun {
+        id: RUN.to_string(),
+        goal: "Print one".to_string(),
+        config: AgentRunConfig {
+            provider: "mock".to_string(),
+            model: "m".to_string(),
+            temperature: 0.2,
+            max_tokens: 100,
+            max_steps: 3,
+            approval_threshold: RiskLevel::WritesFiles,
+            tools: vec!["run_python".to_string()],
+        },
+        status: RunStatus::Running,
+        steps: vec![AgentStep {
+            number: 1,
+            thought: thought.clone(),
+            action: None,
+            observation: None,
+            started_at: thought.timestamp,
+            duration_ms: 5,
+        }],
+        memory: Memory {
+            observations: Vec::new(),
+            thoughts: vec![thought],
+            actions_taken: Vec::new(),
+            decisions: Vec::new(),
+        },
+        answer: None,
+        error: None,
+        created_at: 1,
+        updated_at: 1,
+    };
+    store.save(&mut run).unwrap();
+
+    assert!(store.claim(RUN).await);
+    assert_eq!(store.load(RUN).await.unwrap().status, RunStatus::Running);
+    assert!(!store.claim(RUN).await);
+    store.release(RUN).await;
+
+    let loaded = store.load(RUN).await.unwrap();
+    assert_eq!(loaded.status, RunStatus::Interrupted);
+    assert_eq!(loaded.memory.thoughts.len(), 1);
+    assert_eq!(loaded.steps[0].duration_ms, 5);
+    let runs = store.list().await;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].step_count, 1);
+    assert!(store.load("missing").await.is_err());
+    assert!(store.load("../../x").await.unwrap_err().starts_with("Invalid id"));
+}
+
+struct SleepTool;
+
+#[async_trait::async_trait]
+impl Tool for SleepTool {
+    fn name(&self) -> &str {
+        "sleep"
+    }
+
+    fn description(&self) -> &str {
+        "Waits, then repeats the text"
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": { "ms": { "type": "integer" }, "text": { "type": "string" } },
+            "required": ["ms"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ReadOnly
+    }
+
+    async fn call(&self, params: serde_json::Value, _context: &ToolContext) -> Result<serde_json::Value, AgentError> {
+        tokio::time::sleep(std::time::Duration::from_millis(params["ms"].as_u64().unwrap())).await;
+        Ok(params["text"].clone())
+    }
+}
+
+#[tokio::test]
+async fn registry_checks_arguments_and_enforces_limits() {
+    let registry = ToolRegistry::new(ToolLimits {
+        timeout: std::time::Duration::from_millis(200),
+        timeouts: std::collections::HashMap::new(),
+        max_output_bytes: 10,
+    });
+    let spec = registry.register_builtin(std::sync::Arc::new(SleepTool)).unwrap();
+    assert_eq!(registry.definitions()[0].parameters, spec.parameters);
+    let context = ToolContext {
+        run_id: RUN.to_string(),
+    };
+
+    let short = registry.invoke("sleep", json!({ "ms": 1, "text": "hi" }), &context).await.unwrap();
+    assert_eq!(short, json!("hi"));
+    let long = registry.invoke("sleep", json!({ "ms": 1, "text": "ééééééééé" }), &context).await.unwrap();
+    assert!(long.as_str().unwrap().starts_with("ééééé\n[Output truncated"));
+
+    let invalid = registry.invoke("sleep", json!({ "ms": "soon" }), &context).await.unwrap_err();
+    assert!(invalid.message.contains("/ms"), "{}", invalid.message);
+    let slow = registry.invoke("sleep", json!({ "ms": 5000 }), &context).await.unwrap_err();
+    assert!(slow.message.contains("timed out"));
+    assert!(registry.invoke("missing", json!({}), &context).await.is_err());
+
+    assert!(registry.register(std::sync::Arc::new(SleepTool)).is_err());
+    assert!(registry.unregister("sleep").is_err());
+    assert_eq!(registry.names(), vec!["sleep".to_string()]);
+}
@@ -0,0 +1,146 @@
+use super::{
+    data_tools, file_tools, sql_tools, tool_failed, AgentError, DataTools, DocumentSearchTool, FileJournal, FileScope,
+    LibrarySearchTool, RiskLevel, SqlTools, Tool, ToolContext, ToolRegistry, DATA_TOOL_NAMES, FILE_TOOL_NAMES,
+    SQL_TOOL_NAMES,
+};
+use crate::bibliography::Library;
+use crate::rag::DocumentIndex;
+use crate::sandbox::CodeRunner;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+async fn run_python(runner: Arc<CodeRunner>, code: String) -> Result<Value, AgentError> {
+    let result = tauri::async_runtime::spawn_blocking(move || runner.run_python(&code))
+        .await
+        .map_err(|e| tool_failed(e.to_string()))?
+        .map_err(tool_failed)?;
+    serde_json::to_value(result).map_err(|e| tool_failed(e.to_string()))
+}
+
+// The sandbox stops scripts itself; this leaves it time to report what happened
+fn sandbox_timeout(runner: &CodeRunner) -> Option<Duration> {
+    Some(Duration::from_secs(runner.config.timeout_seconds + 5))
+}
+
+// Runs Python in the sandbox; takes {"code": "..."} and returns the execution result
+pub struct CodeTool {
+    runner: Arc<CodeRunner>,
+}
+
+#[async_trait]
+impl Tool for CodeTool {
+    fn name(&self) -> &str {
+        "run_python"
+    }
+
+    fn description(&self) -> &str {
+        "Run a Python script in a sandbox and return its stdout, stderr and the files it wrote. \
+         Use it for calculations, data analysis and plots (save figures to files)."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "code": { "type": "string", "description": "The Python script to run" },
+            },
+            "required": ["code"],
+        })
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ExecutesCode
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        sandbox_timeout(&self.runner)
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        let code = params["code"].as_str().unwrap_or_default().to_string();
+        run_python(self.runner.clone(), code).await
+    }
+}
+
+// A tool defined by the user: a Python script that finds its arguments in a `params` dict
+pub struct ScriptTool {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub code: String,
+    pub runner: Arc<CodeRunner>,
+}
+
+#[async_trait]
+impl Tool for ScriptTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn risk(&self) -> RiskLevel {
+        RiskLevel::ExecutesCode
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        sandbox_timeout(&self.runner)
+    }
+
+    async fn call(&self, params: Value, _context: &ToolContext) -> Result<Value, AgentError> {
+        // A JSON string is also a valid Python string literal
+        let code = format!(
+            "import json\nparams = json.loads({})\n{}",
+            serde_json::to_string(&params.to_string()).map_err(|e| tool_failed(e.to_string()))?,
+            self.code
+        );
+        run_python(self.runner.clone(), code).await
+    }
+}
+
+// The tools every agent gets, with the file tools when folders are open to it; registering them again
+// replaces the old ones, e.g. after a config change
+pub fn register_builtin_tools(
+    registry: &ToolRegistry,
+    runner: Arc<CodeRunner>,
+    files: Option<(Arc<FileScope>, Arc<FileJournal>)>,
+    sql: Option<SqlTools>,
+    data: Option<DataTools>,
+    documents: Option<Arc<DocumentIndex>>,
+    library: Option<Arc<Library>>,
+) {
+    let mut tools: Vec<Arc<dyn Tool>> = vec![Arc::new(CodeTool { runner })];
+    match documents {
+        Some(documents) => tools.push(Arc::new(DocumentSearchTool { documents })),
+        None => registry.remove_builtin("search_documents"),
+    }
+    match library {
+        Some(library) => tools.push(Arc::new(LibrarySearchTool { library })),
+        None => registry.remove_builtin("search_library"),
+    }
+    match files {
+        Some((scope, journal)) => tools.extend(file_tools(scope, journal)),
+        None => FILE_TOOL_NAMES.iter().for_each(|name| registry.remove_builtin(name)),
+    }
+    match sql {
+        Some(sql) => tools.extend(sql_tools(sql)),
+        None => SQL_TOOL_NAMES.iter().for_each(|name| registry.remove_builtin(name)),
+    }
+    match data {
+        Some(data) => tools.extend(data_tools(data)),
+        None => DATA_TOOL_NAMES.iter().for_each(|name| registry.remove_builtin(name)),
+    }
+    for tool in tools {
+        if let Err(e) = registry.register_builtin(tool) {
+            log::warn!("Failed to register tool: {}", e);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::bibliography::{BibEntry, ImportReport, Library, LibraryQuery};
+use crate::chat::save_file;
+use crate::AppState;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
+
+fn library(holder: &AppState) -> Result<Arc<Library>, String> {
+    holder
+        .library
+        .clone()
+        .ok_or_else(|| "The library is not loaded yet".to_string())
+}
+
+// Imports a .bib file; opens a file picker when no path is given and returns None if cancelled
+#[tauri::command]
+pub async fn import_bibtex(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    path: Option<String>,
+) -> Result<Option<ImportReport>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let (tx, rx) = oneshot::channel();
+            app.dialog()
+                .file()
+                .add_filter("BibTeX", &["bib"])
+                .pick_file(move |path| {
+                    let _ = tx.send(path);
+                });
+            let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+
+    let (library, index) = {
+        let holder = app_state.lock().await;
+        (library(&holder)?, holder.documents.clone())
+    };
+    let documents: Vec<String> = match index {
+        Some(index) => index.documents().await.into_iter().map(|d| d.path).collect(),
+        None => Vec::new(),
+    };
+    let report = library.import_file(&path, &documents).await?;
+    Ok(Some(report))
+}
+
+#[tauri::command]
+pub async fn search_library(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    query: LibraryQuery,
+) -> Result<Vec<BibEntry>, String> {
+    let holder = app_state.lock().await;
+    Ok(library(&holder)?.query(&query).await)
+}
+
+#[tauri::command]
+pub async fn get_library_entry(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    key: String,
+) -> Result<BibEntry, String> {
+    let holder = app_state.lock().await;
+    library(&holder)?
+        .get(&key)
+        .await
+        .ok_or_else(|| format!("No library entry with cite key {}", key))
+}
+
+// Writes the entries cited anywhere in the session as a .bib file; returns None if cancelled
+#[tauri::command]
+pub async fn export_session_bibliography(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    let entries = {
+        let holder = app_state.lock().await;
+        let chat_history = holder.chat_history.as_ref().unwrap();
+        let session = chat_history
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| "Session not found".to_string())?;
+        let mut keys: Vec<String> = Vec::new();
+        for key in session.messages.iter().flat_map(|m| &m.references) {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        library(&holder)?.entries_for(&keys).await
+    };
+    if entries.is_empty() {
+        return Err("The session doesn't cite any library entries".to_string());
+    }
+
+    let bibtex: Vec<String> = entries.iter().map(BibEntry::to_bibtex).collect();
+    save_file(&app, &format!("ccru-{}", session_id), "BibTeX", "bib", bibtex.join("\n")).await
+}
@@ -0,0 +1,282 @@
+use super::RawEntry;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Author {
+    pub family: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given: Option<String>,
+}
+
+impl Author {
+    // "Last, First", "First Last", "von Last, First" or a braced organisation name
+    fn parse(name: &str) -> Self {
+        let name = name.trim();
+        if let Some((family, given)) = name.split_once(',') {
+            return Self {
+                family: clean_text(family),
+                given: Some(clean_text(given)).filter(|g| !g.is_empty()),
+            };
+        }
+        if name.starts_with('{') && name.ends_with('}') {
+            return Self {
+                family: clean_text(name),
+                given: None,
+            };
+        }
+        let words: Vec<&str> = name.split_whitespace().collect();
+        match words.split_last() {
+            Some((family, given)) if !given.is_empty() => Self {
+                family: clean_text(family),
+                given: Some(clean_text(&given.join(" "))),
+            },
+            _ => Self {
+                family: clean_text(name),
+                given: None,
+            },
+        }
+    }
+}
+
+// A normalized bibliography entry; `fields` keeps the original values for writing BibTeX back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BibEntry {
+    pub key: String,
+    pub entry_type: String,
+    pub title: Option<String>,
+    pub authors: Vec<Author>,
+    pub year: Option<i32>,
+    // Journal, book title or publisher, whichever the entry has
+    pub venue: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    pub keywords: Vec<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub fields: Vec<(String, String)>,
+    // Path of the ingested document holding the full text, once linked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    pub source_file: String,
+}
+
+// The common LaTeX accent commands, mapped to the combining characters they stand for
+const ACCENTS: [(char, char); 10] = [
+    ('"', '\u{308}'),
+    ('\'', '\u{301}'),
+    ('`', '\u{300}'),
+    ('^', '\u{302}'),
+    ('~', '\u{303}'),
+    ('=', '\u{304}'),
+    ('.', '\u{307}'),
+    ('c', '\u{327}'),
+    ('v', '\u{30C}'),
+    ('u', '\u{306}'),
+];
+
+const SYMBOLS: [(&str, &str); 9] = [
+    ("\\ss", "ß"),
+    ("\\ae", "æ"),
+    ("\\AE", "Æ"),
+    ("\\oe", "œ"),
+    ("\\o", "ø"),
+    ("\\O", "Ø"),
+    ("\\aa", "å"),
+    ("\\l", "ł"),
+    ("\\&", "&"),
+];
+
+fn compose(base: char, mark: char) -> String {
+    // Precomposed forms for the letters that come up in names; otherwise keep the combining mark
+    const PRECOMPOSED: &[(char, char, char)] = &[
+        ('a', '\u{308}', 'ä'), ('o', '\u{308}', 'ö'), ('u', '\u{308}', 'ü'), ('e', '\u{308}', 'ë'), ('i', '\u{308}', 'ï'),
+        ('A', '\u{308}', 'Ä'), ('O', '\u{308}', 'Ö'), ('U', '\u{308}', 'Ü'),
+        ('a', '\u{301}', 'á'), ('e', '\u{301}', 'é'), ('i', '\u{301}', 'í'), ('o', '\u{301}', 'ó'), ('u', '\u{301}', 'ú'),
+        ('E', '\u{301}', 'É'), ('a', '\u{300}', 'à'), ('e', '\u{300}', 'è'), ('o', '\u{300}', 'ò'),
+        ('a', '\u{302}', 'â'), ('e', '\u{302}', 'ê'), ('o', '\u{302}', 'ô'), ('n', '\u{303}', 'ñ'), ('a', '\u{303}', 'ã'),
+        ('c', '\u{327}', 'ç'), ('c', '\u{30C}', 'č'), ('s', '\u{30C}', 'š'), ('z', '\u{30C}', 'ž'), ('r', '\u{30C}', 'ř'),
+    ];
+    match PRECOMPOSED.iter().find(|(b, m, _)| *b == base && *m == mark) {
+        Some((_, _, composed)) => composed.to_string(),
+        None => format!("{}{}", base, mark),
+    }
+}
+
+// Plain text of a BibTeX value: accents resolved, braces dropped, whitespace collapsed
+pub fn clean_text(value: &str) -> String {
+    let mut text = value.to_string();
+    for (command, symbol) in SYMBOLS {
+        text = text.replace(&format!("{{{}}}", command), symbol);
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && i + 1 < chars.len() {
+            if let Some((_, mark)) = ACCENTS.iter().find(|(a, _)| *a == chars[i + 1]) {
+                // \"o, \"{o} and \c{c}; letters after \c, \v and \u need braces or a space
+                let mut j = i + 2;
+                while j < chars.len() && (chars[j] == '{' || chars[j] == ' ') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_alphabetic() {
+                    out.push_str(&compose(chars[j], *mark));
+                    i = j + 1;
+                    while i < chars.len() && chars[i] == '}' {
+                        i += 1;
+                    }
+                    continue;
+                }
+            }
+            if let Some((command, symbol)) = SYMBOLS.iter().find(|(cmd, _)| text_at(&chars, i, cmd)) {
+                out.push_str(symbol);
+                i += command.chars().count();
+                continue;
+            }
+        }
+        if c != '{' && c != '}' {
+            out.push(c);
+        }
+        i += 1;
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn text_at(chars: &[char], at: usize, text: &str) -> bool {
+    let needle: Vec<char> = text.chars().collect();
+    chars.get(at..at + needle.len()) == Some(&needle[..])
+        && !chars.get(at + needle.len()).is_some_and(|c| c.is_alphabetic())
+}
+
+// Splits on " and " at brace depth zero, so "{Barnes and Noble}" stays one name
+fn split_names(value: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ if depth == 0 && value[i..].to_lowercase().starts_with(" and ") => {
+                names.push(value[start..i].to_string());
+                i += 5;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    names.push(value[start..].to_string());
+    names.into_iter().filter(|n| !n.trim().is_empty()).collect()
+}
+
+impl BibEntry {
+    pub fn from_raw(raw: RawEntry, source_file: &str) -> Self {
+        let field = |name: &str| {
+            raw.fields
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| clean_text(v))
+                .filter(|v| !v.is_empty())
+        };
+        let year = field("year")
+            .or_else(|| field("date"))
+            .and_then(|y| y.get(..4).and_then(|y| y.parse().ok()));
+        let authors = raw
+            .fields
+            .iter()
+            .find(|(n, _)| n == "author" || n == "editor")
+            .map(|(_, v)| split_names(v).iter().map(|n| Author::parse(n)).collect())
+            .unwrap_or_default();
+        let keywords = field("keywords")
+            .map(|k| {
+                k.split([',', ';'])
+                    .map(|k| k.trim().to_lowercase())
+                    .filter(|k| !k.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let doi = field("doi").map(|d| {
+            d.trim_start_matches("https://doi.org/")
+                .trim_start_matches("doi:")
+                .to_lowercase()
+        });
+
+        BibEntry {
+            title: field("title"),
+            venue: field("journal")
+                .or_else(|| field("journaltitle"))
+                .or_else(|| field("booktitle"))
+                .or_else(|| field("publisher")),
+            url: field("url"),
+            abstract_text: field("abstract"),
+            doi,
+            year,
+            authors,
+            keywords,
+            key: raw.key,
+            entry_type: raw.entry_type,
+            fields: raw.fields,
+            document: None,
+            source_file: source_file.to_string(),
+        }
+    }
+
+    // Files attached by reference managers, e.g. JabRef's ":papers/x.pdf:PDF" or a plain path
+    pub fn attached_files(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|(n, _)| n == "file" || n == "pdf")
+            .flat_map(|(_, v)| v.split(';').map(str::to_string).collect::<Vec<_>>())
+            .map(|f| {
+                let parts: Vec<&str> = f.split(':').collect();
+                match parts.as_slice() {
+                    // Windows drive letters put an extra colon into the path
+                    [_, drive, path, _] if drive.len() == 1 => format!("{}:{}", drive, path),
+                    [_, path, _] => path.to_string(),
+                    _ => f.clone(),
+                }
+            })
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    }
+
+    // A plain reference-list line, e.g. "Blei, David M., Kucukelbir, Alp (2017). Title. Venue."
+    pub fn reference(&self) -> String {
+        let authors: Vec<String> = self
+            .authors
+            .iter()
+            .map(|a| match &a.given {
+                Some(given) => format!("{}, {}", a.family, given),
+                None => a.family.clone(),
+            })
+            .collect();
+        let mut reference = authors.join(", ");
+        if let Some(year) = self.year {
+            reference.push_str(&format!(" ({})", year));
+        }
+        for part in [&self.title, &self.venue].into_iter().flatten() {
+            if !reference.is_empty() {
+                reference.push_str(". ");
+            }
+            reference.push_str(part);
+        }
+        reference.push('.');
+        reference
+    }
+
+    pub fn to_bibtex(&self) -> String {
+        let mut out = format!("@{}{{{},\n", self.entry_type, self.key);
+        for (name, value) in &self.fields {
+            out.push_str(&format!("  {} = {{{}}},\n", name, value));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
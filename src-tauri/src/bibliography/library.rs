@@ -0,0 +1,277 @@
+use super::{parse_bibtex, BibEntry};
+use crate::rag::{DocumentIndex, RetrievedChunk};
+use crate::search::terms;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub file: String,
+    pub added: usize,
+    pub updated: usize,
+    pub linked: usize,
+    // Entries that couldn't be parsed, with their line numbers
+    pub errors: Vec<String>,
+}
+
+// Every given criterion has to match; author and keyword are case-insensitive substrings
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LibraryQuery {
+    pub author: Option<String>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub keyword: Option<String>,
+    // Terms looked up in titles and abstracts
+    pub text: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl LibraryQuery {
+    fn matches(&self, entry: &BibEntry) -> bool {
+        if let Some(author) = &self.author {
+            let author = author.to_lowercase();
+            let found = entry.authors.iter().any(|a| {
+                a.family.to_lowercase().contains(&author)
+                    || format!("{} {}", a.given.as_deref().unwrap_or_default(), a.family)
+                        .to_lowercase()
+                        .contains(&author)
+            });
+            if !found {
+                return false;
+            }
+        }
+        if self.year_from.is_some() || self.year_to.is_some() {
+            let Some(year) = entry.year else {
+                return false;
+            };
+            if self.year_from.is_some_and(|from| year < from) || self.year_to.is_some_and(|to| year > to) {
+                return false;
+            }
+        }
+        if let Some(keyword) = &self.keyword {
+            let keyword = keyword.to_lowercase();
+            if !entry.keywords.iter().any(|k| k.contains(&keyword)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// Lowercase words of a title or file name, for comparing one against the other
+fn title_words(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// An entry belongs to a document named in its file field, named after its cite key, or named after its title
+fn links_to(entry: &BibEntry, document: &Path) -> bool {
+    let base = Path::new(&entry.source_file).parent().unwrap_or(Path::new(""));
+    if entry
+        .attached_files()
+        .iter()
+        .any(|file| base.join(file) == document || Path::new(file).file_name() == document.file_name())
+    {
+        return true;
+    }
+    let Some(stem) = document.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+        return false;
+    };
+    if stem.eq_ignore_ascii_case(&entry.key) {
+        return true;
+    }
+    match &entry.title {
+        Some(title) => {
+            let title = title_words(title);
+            !title.is_empty() && title == title_words(&stem)
+        }
+        None => false,
+    }
+}
+
+fn cite_key_regex() -> Regex {
+    Regex::new(r"\[(@[^\[\]\s;,]+(?:\s*[;,]\s*@[^\[\]\s;,]+)*)\]").unwrap()
+}
+
+// Keys of [@key] and [@a; @b] markers in an answer, in order of first appearance
+pub fn parse_cite_keys(content: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for captures in cite_key_regex().captures_iter(content) {
+        for key in captures[1].split([';', ',']) {
+            let key = key.trim().trim_start_matches('@').to_string();
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    keys
+}
+
+// Imported bibliography entries, kept by cite key
+pub struct Library {
+    path: PathBuf,
+    entries: Mutex<Vec<BibEntry>>,
+}
+
+impl Library {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable library file {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    fn save(&self, entries: &[BibEntry]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| format!("Failed to save library: {}", e))
+    }
+
+    // Adds the file's entries, replacing earlier ones with the same cite key
+    pub async fn import_file(&self, path: &Path, documents: &[String]) -> Result<ImportReport, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let parsed = parse_bibtex(&text);
+        let file = path.display().to_string();
+
+        let mut entries = self.entries.lock().await;
+        let (mut added, mut updated) = (0, 0);
+        for raw in parsed.entries {
+            let entry = BibEntry::from_raw(raw, &file);
+            match entries.iter_mut().find(|e| e.key == entry.key) {
+                Some(existing) => {
+                    *existing = entry;
+                    updated += 1;
+                }
+                None => {
+                    entries.push(entry);
+                    added += 1;
+                }
+            }
+        }
+        Self::link(&mut entries, documents);
+        self.save(&entries)?;
+        Ok(ImportReport {
+            file,
+            added,
+            updated,
+            linked: entries.iter().filter(|e| e.document.is_some()).count(),
+            errors: parsed.errors,
+        })
+    }
+
+    fn link(entries: &mut [BibEntry], documents: &[String]) -> bool {
+        let mut changed = false;
+        for entry in entries.iter_mut() {
+            let document = documents.iter().find(|d| links_to(entry, Path::new(d))).cloned();
+            if entry.document != document {
+                entry.document = document;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // Re-matches entries against the ingested documents, e.g. after new PDFs were indexed
+    pub async fn link_documents(&self, documents: &[String]) -> Result<(), String> {
+        let mut entries = self.entries.lock().await;
+        if Self::link(&mut entries, documents) {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+
+    // Links against everything in the index; called whenever documents are ingested or removed
+    pub async fn relink(&self, index: &DocumentIndex) {
+        let documents: Vec<String> = index.documents().await.into_iter().map(|d| d.path).collect();
+        if let Err(e) = self.link_documents(&documents).await {
+            log::warn!("Failed to link library entries to documents: {}", e);
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<BibEntry> {
+        let entries = self.entries.lock().await;
+        entries.iter().find(|e| e.key == key).cloned()
+    }
+
+    pub async fn entry_for_document(&self, document: &str) -> Option<BibEntry> {
+        let entries = self.entries.lock().await;
+        entries.iter().find(|e| e.document.as_deref() == Some(document)).cloned()
+    }
+
+    pub async fn entries_for(&self, keys: &[String]) -> Vec<BibEntry> {
+        let entries = self.entries.lock().await;
+        keys.iter()
+            .filter_map(|key| entries.iter().find(|e| &e.key == key).cloned())
+            .collect()
+    }
+
+    // Matching entries, those sharing most text terms first, then newest first
+    pub async fn query(&self, query: &LibraryQuery) -> Vec<BibEntry> {
+        let entries = self.entries.lock().await;
+        let wanted = query.text.as_deref().map(terms).unwrap_or_default();
+        let mut found: Vec<(usize, &BibEntry)> = entries
+            .iter()
+            .filter(|e| query.matches(e))
+            .map(|e| {
+                let text = format!(
+                    "{} {}",
+                    e.title.as_deref().unwrap_or_default(),
+                    e.abstract_text.as_deref().unwrap_or_default()
+                );
+                let have = terms(&text);
+                (wanted.iter().filter(|t| have.contains(t)).count(), e)
+            })
+            .filter(|(overlap, _)| wanted.is_empty() || *overlap > 0)
+            .collect();
+        found.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.year.cmp(&a.1.year)));
+        found
+            .into_iter()
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|(_, e)| e.clone())
+            .collect()
+    }
+
+    // Marks chunks of linked documents with their cite key so the model can cite the paper
+    pub async fn annotate(&self, chunks: &mut [RetrievedChunk]) {
+        let entries = self.entries.lock().await;
+        for chunk in chunks.iter_mut() {
+            chunk.cite_key = entries
+                .iter()
+                .find(|e| e.document.as_deref() == Some(chunk.document.as_str()))
+                .map(|e| e.key.clone());
+        }
+    }
+
+    // Entries an answer cites, either directly as [@key] or through a numbered excerpt of a linked paper
+    pub async fn cited_keys(&self, content: &str, cited_chunks: &[&RetrievedChunk]) -> Vec<String> {
+        let entries = self.entries.lock().await;
+        let mut keys: Vec<String> = parse_cite_keys(content)
+            .into_iter()
+            .filter(|key| entries.iter().any(|e| &e.key == key))
+            .collect();
+        for key in cited_chunks.iter().filter_map(|c| c.cite_key.as_ref()) {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
+}
@@ -0,0 +1,11 @@
+mod parser;
+pub use parser::*;
+mod entry;
+pub use entry::*;
+mod library;
+pub use library::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
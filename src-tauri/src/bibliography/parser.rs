@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+// An entry as written in the .bib file; values keep their braces so they can be written back
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawEntry {
+    pub entry_type: String,
+    pub key: String,
+    pub fields: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedBib {
+    pub entries: Vec<RawEntry>,
+    pub errors: Vec<String>,
+}
+
+// The usual month macros, available without an @string definition
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected '{}' but found '{}'", expected, c)),
+            None => Err(format!("expected '{}' but the file ended", expected)),
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"'))
+        {
+            self.bump();
+        }
+        self.text[start..self.pos].to_string()
+    }
+
+    // Contents of a {...} group, nested braces included
+    fn braced(&mut self) -> Result<String, String> {
+        self.expect('{')?;
+        let start = self.pos;
+        let mut depth = 1;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(self.text[start..self.pos - 1].to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err("unbalanced braces".to_string())
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '"' if depth == 0 => return Ok(self.text[start..self.pos - 1].to_string()),
+                _ => {}
+            }
+        }
+        Err("unterminated quoted value".to_string())
+    }
+
+    // A value made of braced, quoted, numeric or macro parts joined with #
+    fn value(&mut self, strings: &HashMap<String, String>) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => value.push_str(&self.braced()?),
+                Some('"') => value.push_str(&self.quoted()?),
+                Some(_) => {
+                    let name = self.identifier();
+                    if name.is_empty() {
+                        return Err("missing field value".to_string());
+                    }
+                    let lower = name.to_lowercase();
+                    match strings.get(&lower) {
+                        Some(expansion) => value.push_str(expansion),
+                        None if MONTHS.contains(&lower.as_str()) => value.push_str(&name),
+                        None if name.chars().all(|c| c.is_ascii_digit()) => value.push_str(&name),
+                        None => return Err(format!("undefined macro '{}'", name)),
+                    }
+                }
+                None => return Err("the file ended inside a value".to_string()),
+            }
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.bump();
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    // Skips to just past the delimiter closing the current entry, so one bad entry doesn't stop the file
+    fn recover(&mut self, close: char) {
+        let open = if close == '}' { '{' } else { '(' };
+        let mut depth = 1;
+        while let Some(c) = self.bump() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn parse_fields(
+    cursor: &mut Cursor,
+    close: char,
+    strings: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut fields = Vec::new();
+    loop {
+        cursor.skip_whitespace();
+        match cursor.peek() {
+            Some(c) if c == close => {
+                cursor.bump();
+                return Ok(fields);
+            }
+            Some(',') => {
+                cursor.bump();
+            }
+            Some(_) => {
+                let name = cursor.identifier().to_lowercase();
+                if name.is_empty() {
+                    return Err(format!("unexpected '{}'", cursor.peek().unwrap()));
+                }
+                cursor.expect('=')?;
+                let value = cursor.value(strings)?;
+                fields.push((name, value));
+            }
+            None => return Err("the file ended inside an entry".to_string()),
+        }
+    }
+}
+
+pub fn parse_bibtex(text: &str) -> ParsedBib {
+    let mut cursor = Cursor { text, pos: 0 };
+    let mut strings: HashMap<String, String> = HashMap::new();
+    let mut parsed = ParsedBib::default();
+
+    // Anything outside an @entry is a comment
+    while let Some(at) = cursor.text[cursor.pos..].find('@') {
+        cursor.pos += at + 1;
+        let entry_type = cursor.identifier().to_lowercase();
+        cursor.skip_whitespace();
+        let close = match cursor.bump() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => continue,
+        };
+        let line = text[..cursor.pos].lines().count();
+
+        match entry_type.as_str() {
+            "comment" | "preamble" => cursor.recover(close),
+            "string" => match parse_fields(&mut cursor, close, &strings) {
+                Ok(fields) => strings.extend(fields),
+                Err(e) => {
+                    parsed.errors.push(format!("line {}: @string: {}", line, e));
+                    cursor.recover(close);
+                }
+            },
+            _ => {
+                let key = cursor.identifier();
+                let result = if key.is_empty() {
+                    Err("missing cite key".to_string())
+                } else {
+                    parse_fields(&mut cursor, close, &strings)
+                };
+                match result {
+                    Ok(fields) => parsed.entries.push(RawEntry { entry_type, key, fields }),
+                    Err(e) => {
+                        parsed.errors.push(format!("line {}: @{}{{{}: {}", line, entry_type, key, e));
+                        cursor.recover(close);
+                    }
+                }
+            }
+        }
+    }
+    parsed
+}
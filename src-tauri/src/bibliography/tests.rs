@@ -0,0 +1,98 @@
+use super::*;
+
+const BIB: &str = r#"
+Exported from a reference manager.
+
+@string{jmlr = "Journal of Machine Learning Research"}
+
+@article{blei2017,
+  author = {Blei, David M. and Kucukelbir, Alp and McAuliffe, Jon D.},
+  title = {Variational Inference: A Review for {S}tatisticians},
+  journal = {Journal of the American Statistical Association},
+  year = 2017,
+  keywords = {variational inference, bayesian},
+  file = {:papers/Blei - VI review.pdf:PDF},
+}
+
+@article{broken,
+  title = {Uses an undefined macro},
+  journal = nips,
+}
+
+@inproceedings(hoffman2013,
+  author = "Matthew D. Hoffman and M{\"u}ller, J{\"{o}}rg and {Barnes and Noble}",
+  title = "Stochastic " # "Variational Inference",
+  journal = jmlr,
+  month = may,
+  year = {2013}
+)
+"#;
+
+#[test]
+fn parses_and_normalizes_entries_despite_errors() {
+    let parsed = parse_bibtex(BIB);
+    assert_eq!(parsed.entries.len(), 2);
+    assert_eq!(parsed.errors.len(), 1);
+    assert!(parsed.errors[0].contains("broken"), "{:?}", parsed.errors);
+
+    let entries: Vec<BibEntry> = parsed
+        .entries
+        .into_iter()
+        .map(|raw| BibEntry::from_raw(raw, "/refs/library.bib"))
+        .collect();
+    let blei = &entries[0];
+    assert_eq!(blei.title.as_deref(), Some("Variational Inference: A Review for Statisticians"));
+    assert_eq!(blei.year, Some(2017));
+    assert_eq!(blei.keywords, ["variational inference", "bayesian"]);
+    assert_eq!(blei.attached_files(), ["papers/Blei - VI review.pdf"]);
+
+    let hoffman = &entries[1];
+    assert_eq!(hoffman.title.as_deref(), Some("Stochastic Variational Inference"));
+    assert_eq!(hoffman.venue.as_deref(), Some("Journal of Machine Learning Research"));
+    let families: Vec<&str> = hoffman.authors.iter().map(|a| a.family.as_str()).collect();
+    assert_eq!(families, ["Hoffman", "Müller", "Barnes and Noble"]);
+    assert_eq!(hoffman.authors[1].given.as_deref(), Some("Jörg"));
+    assert!(hoffman.to_bibtex().starts_with("@inproceedings{hoffman2013,\n"));
+}
+
+#[tokio::test]
+async fn imports_link_documents_and_answer_queries() {
+    let dir = std::env::temp_dir().join(format!("ccru-library-{}", crate::chat::new_id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bib = dir.join("library.bib");
+    std::fs::write(&bib, BIB).unwrap();
+    let pdf = dir.join("papers").join("Blei - VI review.pdf").display().to_string();
+    let named_by_key = "/elsewhere/hoffman2013.pdf".to_string();
+
+    let library = Library::load(dir.join("library.json"));
+    let report = library.import_file(&bib, &[pdf.clone(), named_by_key.clone()]).await.unwrap();
+    assert_eq!((report.added, report.updated, report.linked), (2, 0, 2));
+    assert_eq!(library.get("blei2017").await.unwrap().document, Some(pdf));
+    assert_eq!(library.get("hoffman2013").await.unwrap().document, Some(named_by_key));
+
+    let by_author = LibraryQuery {
+        author: Some("müller".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(library.query(&by_author).await[0].key, "hoffman2013");
+    let by_year_and_keyword = LibraryQuery {
+        year_from: Some(2015),
+        keyword: Some("bayes".to_string()),
+        ..Default::default()
+    };
+    let found = library.query(&by_year_and_keyword).await;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].key, "blei2017");
+
+    // Reloading keeps the entries, and re-importing replaces them by key
+    let reloaded = Library::load(dir.join("library.json"));
+    let report = reloaded.import_file(&bib, &[]).await.unwrap();
+    assert_eq!((report.added, report.updated, report.linked), (0, 2, 0));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn cite_key_markers_are_collected_once() {
+    let answer = "Mean-field VI is common [@blei2017]. SVI scales it [@hoffman2013; @blei2017], see [2].";
+    assert_eq!(parse_cite_keys(answer), ["blei2017", "hoffman2013"]);
+}
@@ -0,0 +1,53 @@
+use super::{BudgetOverride, BudgetStatus, UsageContext};
+use crate::AppState;
+use tokio::sync::Mutex;
+
+#[tauri::command]
+pub async fn get_budget_status(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: Option<String>,
+) -> Result<BudgetStatus, String> {
+    let holder = app_state.lock().await;
+    let budget = holder
+        .budget
+        .as_ref()
+        .ok_or_else(|| "Budget tracking is not initialized".to_string())?;
+    let ctx = UsageContext {
+        provider: holder.config.as_ref().unwrap().provider().to_string(),
+        model: holder
+            .llm_bridge
+            .as_ref()
+            .map(|b| b.model().to_string())
+            .unwrap_or_default(),
+        session_id,
+        agent_run_id: None,
+    };
+    Ok(budget.status(&ctx).await)
+}
+
+#[tauri::command]
+pub async fn override_budget(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    minutes: Option<i64>,
+    requests: Option<u32>,
+) -> Result<BudgetOverride, String> {
+    let holder = app_state.lock().await;
+    let budget = holder
+        .budget
+        .as_ref()
+        .ok_or_else(|| "Budget tracking is not initialized".to_string())?;
+    Ok(budget.set_override(minutes, requests).await)
+}
+
+#[tauri::command]
+pub async fn clear_budget_override(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let holder = app_state.lock().await;
+    let budget = holder
+        .budget
+        .as_ref()
+        .ok_or_else(|| "Budget tracking is not initialized".to_string())?;
+    budget.clear_override().await;
+    Ok(())
+}
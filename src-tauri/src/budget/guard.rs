@@ -0,0 +1,111 @@
+use super::{estimate_tokens, BudgetTracker, BudgetWarning, UsageContext};
+use crate::llm_bridge::{ChunkHandler, LLMBridge, LLMRequest, LLMResponse, LLMServiceError, Usage};
+use std::sync::Arc;
+use tauri::Emitter;
+
+pub const BUDGET_WARNING_EVENT: &str = "budget-warning";
+
+// Sends a request through the bridge after checking it against the configured budgets
+pub async fn complete_with_budget(
+    app: &tauri::AppHandle,
+    llm_bridge: &dyn LLMBridge,
+    budget: Option<&BudgetTracker>,
+    ctx: UsageContext,
+    request: LLMRequest,
+) -> Result<LLMResponse, LLMServiceError> {
+    send_with_budget(app, llm_bridge, budget, ctx, request, None).await
+}
+
+pub async fn stream_with_budget(
+    app: &tauri::AppHandle,
+    llm_bridge: &dyn LLMBridge,
+    budget: Option<&BudgetTracker>,
+    ctx: UsageContext,
+    request: LLMRequest,
+    on_chunk: &ChunkHandler<'_>,
+) -> Result<LLMResponse, LLMServiceError> {
+    send_with_budget(app, llm_bridge, budget, ctx, request, Some(on_chunk)).await
+}
+
+async fn send_with_budget(
+    app: &tauri::AppHandle,
+    llm_bridge: &dyn LLMBridge,
+    budget: Option<&BudgetTracker>,
+    ctx: UsageContext,
+    request: LLMRequest,
+    on_chunk: Option<&ChunkHandler<'_>>,
+) -> Result<LLMResponse, LLMServiceError> {
+    let estimate = BudgetTracker::estimate(&request);
+    let reservation = match budget {
+        Some(budget) => Some(budget.check(&ctx, &estimate).await?),
+        None => None,
+    };
+
+    // A failed request drops its reservation, which frees the estimate held for it
+    let response = match on_chunk {
+        Some(on_chunk) => llm_bridge.complete_stream(request, on_chunk).await?,
+        None => llm_bridge.complete(request).await?,
+    };
+    let (Some(budget), Some(reservation)) = (budget, reservation) else {
+        return Ok(response);
+    };
+    // Providers that don't report usage are charged by the same estimate used for the check
+    let usage = response.usage.unwrap_or(Usage {
+        prompt_tokens: estimate.prompt_tokens,
+        completion_tokens: super::estimate_tokens(response.content.len()),
+    });
+    let warnings = budget.record(reservation, &ctx, &usage).await;
+    emit_warnings(app, &warnings);
+
+    Ok(response)
+}
+
+pub fn emit_warnings(app: &tauri::AppHandle, warnings: &[BudgetWarning]) {
+    for warning in warnings {
+        if let Err(e) = app.emit(BUDGET_WARNING_EVENT, warning) {
+            log::warn!("Failed to emit budget warning: {}", e);
+        }
+    }
+}
+
+// The embedding provider, with every request checked against the budgets and recorded like a completion
+pub struct BudgetedEmbedder {
+    pub bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub budget: Option<Arc<BudgetTracker>>,
+    // Budget warnings are emitted here; without an app handle they are only logged
+    pub app: Option<tauri::AppHandle>,
+}
+
+impl BudgetedEmbedder {
+    pub fn embedding_model(&self) -> Option<&str> {
+        self.bridge.embedding_model()
+    }
+
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        let Some(budget) = self.budget.as_deref() else {
+            return self.bridge.embed(texts).await;
+        };
+        let ctx = UsageContext {
+            provider: self.provider.clone(),
+            model: self.embedding_model().unwrap_or_default().to_string(),
+            session_id: None,
+            agent_run_id: None,
+        };
+        // Embedding endpoints don't all report usage, so the input length is what gets charged
+        let usage = Usage {
+            prompt_tokens: estimate_tokens(texts.iter().map(String::len).sum()),
+            completion_tokens: 0,
+        };
+        let reservation = budget.check(&ctx, &usage).await?;
+        let embeddings = self.bridge.embed(texts).await?;
+        let warnings = budget.record(reservation, &ctx, &usage).await;
+        match &self.app {
+            Some(app) => emit_warnings(app, &warnings),
+            None => warnings
+                .iter()
+                .for_each(|w| log::warn!("Budget for {} is at {:.0}%", w.status.limit, w.status.fraction * 100.0)),
+        }
+        Ok(embeddings)
+    }
+}
@@ -0,0 +1,9 @@
+mod tracker;
+pub use tracker::*;
+mod guard;
+pub use guard::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
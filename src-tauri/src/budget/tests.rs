@@ -0,0 +1,147 @@
+use super::*;
+use crate::config::{BudgetConfig, BudgetLimit, BudgetPeriod, BudgetScope};
+use crate::llm_bridge::Usage;
+use chrono::{Local, TimeZone};
+
+fn limit(scope: BudgetScope, max_tokens: u64, hard: bool) -> BudgetLimit {
+    BudgetLimit {
+        scope,
+        provider: None,
+        period: None,
+        max_cost: None,
+        max_tokens: Some(max_tokens),
+        hard,
+    }
+}
+
+async fn tracker(limits: Vec<BudgetLimit>) -> BudgetTracker {
+    let config = BudgetConfig {
+        limits,
+        ..BudgetConfig::default()
+    };
+    let ledger = std::env::temp_dir().join(format!("ccru-budget-{}.jsonl", uuid::Uuid::new_v4()));
+    BudgetTracker::load(config, ledger).await
+}
+
+fn ctx() -> UsageContext {
+    UsageContext {
+        provider: "openai".to_string(),
+        model: "gpt-4o-mini".to_string(),
+        session_id: Some("s1".to_string()),
+        agent_run_id: None,
+    }
+}
+
+fn tokens(n: u64) -> Usage {
+    Usage {
+        prompt_tokens: n,
+        completion_tokens: 0,
+    }
+}
+
+#[test]
+fn periods_and_scopes_select_what_counts() {
+    let now = Local.with_ymd_and_hms(2024, 5, 15, 10, 30, 0).unwrap();
+    let midnight = |day| Local.with_ymd_and_hms(2024, 5, day, 0, 0, 0).unwrap().timestamp();
+    assert_eq!(period_start(BudgetPeriod::Daily, now), midnight(15));
+    assert_eq!(period_start(BudgetPeriod::Weekly, now), midnight(13));
+    assert_eq!(period_start(BudgetPeriod::Monthly, now), midnight(1));
+
+    let chat = ctx();
+    let agent = UsageContext {
+        session_id: None,
+        agent_run_id: Some("run".to_string()),
+        ..ctx()
+    };
+    assert!(applies(&limit(BudgetScope::Session, 10, true), &chat));
+    assert!(!applies(&limit(BudgetScope::Session, 10, true), &agent));
+    assert!(applies(&limit(BudgetScope::AgentRun, 10, true), &agent));
+    assert!(!applies(&limit(BudgetScope::AgentRun, 10, true), &chat));
+    let other_provider = BudgetLimit {
+        provider: Some("deepseek".to_string()),
+        ..limit(BudgetScope::Provider, 10, true)
+    };
+    assert!(!applies(&other_provider, &chat));
+    assert!(applies(&limit(BudgetScope::Total, 10, true), &agent));
+}
+
+#[tokio::test]
+async fn hard_limits_count_requests_in_flight_and_soft_limits_only_warn() {
+    let budget = tracker(vec![limit(BudgetScope::Total, 1000, true), limit(BudgetScope::Session, 500, false)]).await;
+    let ctx = ctx();
+
+    let first = budget.check(&ctx, &tokens(400)).await.unwrap();
+    let second = budget.check(&ctx, &tokens(400)).await.unwrap();
+    assert!(budget.check(&ctx, &tokens(400)).await.is_err());
+    // A failed request gives its reservation back
+    drop(second);
+
+    // Past the soft 500, while 700 of the hard 1000 is under the 0.8 threshold
+    let warnings = budget.record(first, &ctx, &tokens(700)).await;
+    assert_eq!(warnings.len(), 1);
+    assert!(!warnings[0].status.hard && warnings[0].exceeded);
+    assert!(budget.check(&ctx, &tokens(300)).await.is_ok());
+    let err = budget.check(&ctx, &tokens(301)).await.err().unwrap();
+    assert_eq!(err.status.used_tokens, 700);
+
+    // Thresholds are reported once, when they are crossed
+    let next = budget.check(&ctx, &tokens(100)).await.unwrap();
+    let warnings = budget.record(next, &ctx, &tokens(100)).await;
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].status.hard && !warnings[0].exceeded);
+    let next = budget.check(&ctx, &tokens(50)).await.unwrap();
+    assert!(budget.record(next, &ctx, &tokens(50)).await.is_empty());
+    let next = budget.check(&ctx, &tokens(150)).await.unwrap();
+    let warnings = budget.record(next, &ctx, &tokens(150)).await;
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].status.hard && warnings[0].exceeded);
+}
+
+#[tokio::test]
+async fn overrides_are_used_up_by_successful_requests_and_expire() {
+    let budget = tracker(vec![limit(BudgetScope::Total, 100, true), limit(BudgetScope::AgentRun, 100, true)]).await;
+    let ctx = ctx();
+    let first = budget.check(&ctx, &tokens(50)).await.unwrap();
+    budget.record(first, &ctx, &tokens(100)).await;
+    assert!(budget.check(&ctx, &tokens(10)).await.is_err());
+
+    budget.set_override(None, None).await;
+    let failed = budget.check(&ctx, &tokens(10)).await.unwrap();
+    // A one-request override lets one request through at a time
+    assert!(budget.check(&ctx, &tokens(10)).await.is_err());
+    drop(failed);
+    let sent = budget.check(&ctx, &tokens(10)).await.unwrap();
+    budget.record(sent, &ctx, &tokens(10)).await;
+    assert!(budget.check(&ctx, &tokens(10)).await.is_err());
+    assert!(budget.status(&ctx).await.budget_override.is_none());
+
+    // Agent run ceilings hold whatever is overridden
+    budget.set_override(Some(10), None).await;
+    let agent = UsageContext {
+        agent_run_id: Some("run".to_string()),
+        ..ctx.clone()
+    };
+    let run = budget.check(&agent, &tokens(50)).await.unwrap();
+    budget.record(run, &agent, &tokens(100)).await;
+    assert!(budget.check(&agent, &tokens(10)).await.is_err());
+    assert!(budget.check(&ctx, &tokens(10)).await.is_ok());
+
+    budget.set_override(Some(-1), None).await;
+    assert!(budget.check(&ctx, &tokens(10)).await.is_err());
+}
+
+#[tokio::test]
+async fn embeddings_are_checked_and_recorded() {
+    let script: crate::llm_bridge::MockScript = toml::from_str("[fallback]\ncontent = \"ok\"").unwrap();
+    let embedder = BudgetedEmbedder {
+        bridge: std::sync::Arc::new(crate::llm_bridge::MockBridge::new(script).unwrap()),
+        provider: "mock".to_string(),
+        budget: Some(std::sync::Arc::new(tracker(vec![limit(BudgetScope::Total, 100, true)]).await)),
+        app: None,
+    };
+    assert_eq!(embedder.embed(&["x".repeat(200)]).await.unwrap().len(), 1);
+    let budget = embedder.budget.as_deref().unwrap();
+    assert_eq!(budget.status(&ctx()).await.limits[0].used_tokens, 50);
+    assert!(embedder.embed(&["x".repeat(400)]).await.is_err());
+    assert_eq!(budget.status(&ctx()).await.limits[0].used_tokens, 50);
+}
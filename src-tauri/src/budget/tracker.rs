@@ -0,0 +1,441 @@
+use crate::config::{BudgetConfig, BudgetLimit, BudgetPeriod, BudgetScope};
+use crate::llm_bridge::{LLMRequest, LLMServiceError, Usage};
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Upper bound on completion length assumed when checking a request before it is sent
+pub const ESTIMATED_COMPLETION_TOKENS: u64 = 800;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub provider: String,
+    pub model: String,
+    pub session_id: Option<String>,
+    pub agent_run_id: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UsageContext {
+    pub provider: String,
+    pub model: String,
+    pub session_id: Option<String>,
+    // Only agent runs set this: the agent-run scope has no override, so other jobs would stall on it
+    pub agent_run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitStatus {
+    pub limit: String,
+    pub hard: bool,
+    pub used_cost: f64,
+    pub max_cost: Option<f64>,
+    pub used_tokens: u64,
+    pub max_tokens: Option<u64>,
+    pub fraction: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetWarning {
+    #[serde(flatten)]
+    pub status: LimitStatus,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetExceeded {
+    #[serde(flatten)]
+    pub status: LimitStatus,
+    pub estimated_cost: f64,
+    pub estimated_tokens: u64,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Budget exceeded for {}: used {} tokens / {:.4} spent, request needs about {} tokens / {:.4}",
+            self.status.limit,
+            self.status.used_tokens,
+            self.status.used_cost,
+            self.estimated_tokens,
+            self.estimated_cost
+        )
+    }
+}
+
+impl From<BudgetExceeded> for LLMServiceError {
+    fn from(e: BudgetExceeded) -> Self {
+        LLMServiceError {
+            error: e.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetOverride {
+    pub expires_at: Option<i64>,
+    pub remaining_requests: Option<u32>,
+}
+
+impl BudgetOverride {
+    fn is_active(&self, now: i64) -> bool {
+        self.expires_at.map_or(true, |t| now < t) && self.remaining_requests != Some(0)
+    }
+}
+
+struct PendingRequest {
+    // The request's estimate, counted against the limits like usage already recorded
+    estimate: UsageRecord,
+    overridden: bool,
+}
+
+type PendingRequests = Arc<std::sync::Mutex<HashMap<u64, PendingRequest>>>;
+
+// A request let through by `check`. Its estimate is held against the limits until it is
+// recorded, or released when the reservation is dropped because the request failed
+pub struct Reservation {
+    id: u64,
+    overridden: bool,
+    pending: PendingRequests,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub limits: Vec<LimitStatus>,
+    pub budget_override: Option<BudgetOverride>,
+}
+
+pub struct BudgetTracker {
+    config: BudgetConfig,
+    ledger_path: PathBuf,
+    records: Mutex<Vec<UsageRecord>>,
+    budget_override: Mutex<Option<BudgetOverride>>,
+    pending: PendingRequests,
+    next_reservation: AtomicU64,
+}
+
+impl BudgetTracker {
+    pub async fn load(config: BudgetConfig, ledger_path: PathBuf) -> Self {
+        let records = fs::read_to_string(&ledger_path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            config,
+            ledger_path,
+            records: Mutex::new(records),
+            budget_override: Mutex::new(None),
+            pending: Arc::default(),
+            next_reservation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn cost(&self, provider: &str, usage: &Usage) -> f64 {
+        match self.config.pricing.get(provider) {
+            Some(pricing) => {
+                (usage.prompt_tokens as f64 * pricing.input_per_million
+                    + usage.completion_tokens as f64 * pricing.output_per_million)
+                    / 1_000_000.0
+            }
+            None => 0.0,
+        }
+    }
+
+    pub fn estimate(request: &LLMRequest) -> Usage {
+        let chars: usize = request.messages.iter().map(|p| p.content.len()).sum();
+        Usage {
+            prompt_tokens: estimate_tokens(chars),
+            completion_tokens: request
+                .params
+                .max_tokens
+                .map_or(ESTIMATED_COMPLETION_TOKENS, u64::from),
+        }
+    }
+
+    fn usage_record(&self, ctx: &UsageContext, usage: &Usage) -> UsageRecord {
+        UsageRecord {
+            provider: ctx.provider.clone(),
+            model: ctx.model.clone(),
+            session_id: ctx.session_id.clone(),
+            agent_run_id: ctx.agent_run_id.clone(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            cost: self.cost(&ctx.provider, usage),
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    // Refuses the request if it would push any hard limit over its ceiling, counting the requests
+    // still in flight; otherwise reserves its estimate until it is recorded
+    pub async fn check(&self, ctx: &UsageContext, estimate: &Usage) -> Result<Reservation, BudgetExceeded> {
+        let estimated_cost = self.cost(&ctx.provider, estimate);
+        let estimated_tokens = estimate.total_tokens();
+        let records = self.records.lock().await;
+        let budget_override = self.budget_override.lock().await;
+        let mut pending = self.pending.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let mut overridden = false;
+
+        // An override for n requests lets through n at a time, however many are in flight
+        let overrides_in_flight = pending.values().filter(|p| p.overridden).count();
+        let override_active = budget_override.as_ref().is_some_and(|o| {
+            o.is_active(now) && o.remaining_requests.map_or(true, |n| n as usize > overrides_in_flight)
+        });
+
+        for limit in self.config.limits.iter().filter(|l| l.hard && applies(l, ctx)) {
+            let counted = records.iter().chain(pending.values().map(|p| &p.estimate));
+            let status = limit_status(limit, ctx, counted);
+            let over_cost = limit
+                .max_cost
+                .is_some_and(|max| status.used_cost + estimated_cost > max);
+            let over_tokens = limit
+                .max_tokens
+                .is_some_and(|max| status.used_tokens + estimated_tokens > max);
+            if !over_cost && !over_tokens {
+                continue;
+            }
+
+            // Agent runs always stop at their ceiling, whatever the user has overridden
+            let can_override = limit.scope != BudgetScope::AgentRun && override_active;
+            if !can_override {
+                return Err(BudgetExceeded {
+                    status,
+                    estimated_cost,
+                    estimated_tokens,
+                });
+            }
+            overridden = true;
+        }
+
+        let id = self.next_reservation.fetch_add(1, Ordering::Relaxed);
+        pending.insert(
+            id,
+            PendingRequest {
+                estimate: self.usage_record(ctx, estimate),
+                overridden,
+            },
+        );
+        Ok(Reservation {
+            id,
+            overridden,
+            pending: self.pending.clone(),
+        })
+    }
+
+    // Stores the usage of a completed request in place of its reservation, uses up a request of the
+    // override that let it through, and reports limits it pushed past a threshold
+    pub async fn record(&self, reservation: Reservation, ctx: &UsageContext, usage: &Usage) -> Vec<BudgetWarning> {
+        let record = self.usage_record(ctx, usage);
+
+        let mut records = self.records.lock().await;
+        if reservation.overridden {
+            if let Some(remaining) = self
+                .budget_override
+                .lock()
+                .await
+                .as_mut()
+                .and_then(|o| o.remaining_requests.as_mut())
+            {
+                *remaining = remaining.saturating_sub(1);
+            }
+        }
+        let before: Vec<_> = self
+            .config
+            .limits
+            .iter()
+            .map(|l| applies(l, ctx).then(|| limit_status(l, ctx, records.iter())))
+            .collect();
+
+        if let Err(e) = self.append_to_ledger(&record) {
+            log::warn!("Failed to write usage ledger: {}", e);
+        }
+        records.push(record);
+        drop(reservation);
+
+        let threshold = self.config.soft_threshold;
+        self.config
+            .limits
+            .iter()
+            .zip(before)
+            .filter_map(|(limit, before)| {
+                let before = before?;
+                let after = limit_status(limit, ctx, records.iter());
+                let crossed_soft = before.fraction < threshold && after.fraction >= threshold;
+                let crossed_max = before.fraction < 1.0 && after.fraction >= 1.0;
+                (crossed_soft || crossed_max).then_some(BudgetWarning {
+                    exceeded: after.fraction >= 1.0,
+                    status: after,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn status(&self, ctx: &UsageContext) -> BudgetStatus {
+        let records = self.records.lock().await;
+        let limits = self
+            .config
+            .limits
+            .iter()
+            .filter(|l| applies(l, ctx))
+            .map(|l| limit_status(l, ctx, records.iter()))
+            .collect();
+        let now = chrono::Utc::now().timestamp();
+        let budget_override = self
+            .budget_override
+            .lock()
+            .await
+            .clone()
+            .filter(|o| o.is_active(now));
+
+        BudgetStatus {
+            limits,
+            budget_override,
+        }
+    }
+
+    pub async fn set_override(&self, minutes: Option<i64>, requests: Option<u32>) -> BudgetOverride {
+        let expires_at = minutes.map(|m| chrono::Utc::now().timestamp() + m * 60);
+        // Without an explicit window the override lets exactly one request through
+        let remaining_requests = match (minutes, requests) {
+            (None, None) => Some(1),
+            (_, requests) => requests,
+        };
+        let budget_override = BudgetOverride {
+            expires_at,
+            remaining_requests,
+        };
+        *self.budget_override.lock().await = Some(budget_override.clone());
+        budget_override
+    }
+
+    pub async fn clear_override(&self) {
+        *self.budget_override.lock().await = None;
+    }
+
+    fn append_to_ledger(&self, record: &UsageRecord) -> std::io::Result<()> {
+        if let Some(parent) = self.ledger_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.ledger_path)?;
+        let line = serde_json::to_string(record).map_err(std::io::Error::other)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+pub fn estimate_tokens(chars: usize) -> u64 {
+    chars.div_ceil(4) as u64
+}
+
+pub(super) fn applies(limit: &BudgetLimit, ctx: &UsageContext) -> bool {
+    if limit.provider.as_ref().is_some_and(|p| p != &ctx.provider) {
+        return false;
+    }
+    match limit.scope {
+        BudgetScope::Total | BudgetScope::Provider => true,
+        BudgetScope::Session => ctx.session_id.is_some(),
+        BudgetScope::AgentRun => ctx.agent_run_id.is_some(),
+    }
+}
+
+fn counts_towards(limit: &BudgetLimit, ctx: &UsageContext, record: &UsageRecord, since: i64) -> bool {
+    if record.timestamp < since {
+        return false;
+    }
+    if limit.provider.as_ref().is_some_and(|p| p != &record.provider) {
+        return false;
+    }
+    match limit.scope {
+        BudgetScope::Total => true,
+        BudgetScope::Provider => record.provider == ctx.provider,
+        BudgetScope::Session => record.session_id == ctx.session_id,
+        BudgetScope::AgentRun => record.agent_run_id == ctx.agent_run_id,
+    }
+}
+
+fn limit_status<'a>(
+    limit: &BudgetLimit,
+    ctx: &UsageContext,
+    records: impl Iterator<Item = &'a UsageRecord>,
+) -> LimitStatus {
+    let since = limit.period.map_or(i64::MIN, |p| period_start(p, Local::now()));
+    let (used_cost, used_tokens) = records
+        .filter(|r| counts_towards(limit, ctx, r, since))
+        .fold((0.0, 0), |(cost, tokens), r| {
+            (cost + r.cost, tokens + r.prompt_tokens + r.completion_tokens)
+        });
+
+    let cost_fraction = limit.max_cost.map_or(0.0, |max| used_cost / max);
+    let token_fraction = limit
+        .max_tokens
+        .map_or(0.0, |max| used_tokens as f64 / max as f64);
+
+    LimitStatus {
+        limit: describe(limit, ctx),
+        hard: limit.hard,
+        used_cost,
+        max_cost: limit.max_cost,
+        used_tokens,
+        max_tokens: limit.max_tokens,
+        fraction: cost_fraction.max(token_fraction),
+    }
+}
+
+fn describe(limit: &BudgetLimit, ctx: &UsageContext) -> String {
+    let period = match limit.period {
+        Some(BudgetPeriod::Daily) => "daily ",
+        Some(BudgetPeriod::Weekly) => "weekly ",
+        Some(BudgetPeriod::Monthly) => "monthly ",
+        None => "",
+    };
+    let scope = match limit.scope {
+        BudgetScope::Total => "total".to_string(),
+        BudgetScope::Provider => format!(
+            "provider {}",
+            limit.provider.as_deref().unwrap_or(&ctx.provider)
+        ),
+        BudgetScope::Session => "session".to_string(),
+        BudgetScope::AgentRun => "agent run".to_string(),
+    };
+    format!("{}{}", period, scope)
+}
+
+pub(super) fn period_start(period: BudgetPeriod, now: DateTime<Local>) -> i64 {
+    let today = now.date_naive();
+    let start = match period {
+        BudgetPeriod::Daily => today,
+        BudgetPeriod::Weekly => {
+            today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+        BudgetPeriod::Monthly => today.with_day(1).unwrap_or(today),
+    };
+    start
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .map(|t| t.timestamp())
+        .unwrap_or(0)
+}
@@ -1,23 +1,91 @@
-use crate::llm_bridge::Prompt;
+use crate::chat::{SessionFilter, SessionMetadataPatch, SessionSort, SessionSummary};
+use crate::llm_bridge::{GenerationParams, Prompt};
+use crate::paper::{PaperSession, PaperSummary};
+use crate::rag::Citation;
+use crate::sandbox::ExecutionResult;
+use crate::search::{MessageIndex, SearchFilters, SearchHit};
+use crate::workspace::Workspace;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChatMessage {
+    #[serde(default = "new_id")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
     pub role: String,
     pub content: String,
     pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    // Markers in a grounded answer and the document passages they point at
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    // Cite keys of the library entries the answer refers to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    // Runs of code blocks from this message, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub executions: Vec<ExecutionResult>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChatSession {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    // Inherited from the workspace at creation; sent ahead of the messages on every request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub params: GenerationParams,
     pub messages: Vec<ChatMessage>,
+    // Messages off the active branch of an imported conversation tree, linked by parent_id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_messages: Vec<ChatMessage>,
+    // Identifies where an imported session came from, e.g. "chatgpt:<conversation id>"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    // Set for paper-reading sessions, which are about a single document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper: Option<PaperSession>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
+pub fn new_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+// Ids that name files or folders come from the frontend, so only ids new_id could have made are accepted
+pub fn check_id(id: &str) -> Result<(), String> {
+    if id.len() == 36 && Uuid::parse_str(id).is_ok() {
+        Ok(())
+    } else {
+        Err(format!("Invalid id {:?}", id))
+    }
+}
+
 impl Into<Vec<ChatMessage>> for ChatSession {
     fn into(self) -> Vec<ChatMessage> {
         self.messages
@@ -26,48 +94,106 @@ impl Into<Vec<ChatMessage>> for ChatSession {
 
 impl Into<Vec<Prompt>> for ChatSession {
     fn into(self) -> Vec<Prompt> {
-        self.messages
+        let system = self.system_prompt.map(|content| Prompt {
+            role: "system".to_string(),
+            content,
+        });
+        system
             .into_iter()
-            .map(|m| Prompt {
-                role: m.role,
-                content: m.content,
-            })
+            .chain(self.messages.into_iter().map(|m| {
+                // The model sees what its code printed, as if it had been part of the reply
+                let content = m
+                    .executions
+                    .iter()
+                    .fold(m.content, |content, e| format!("{}\n\n{}", content, e.report()));
+                Prompt { role: m.role, content }
+            }))
             .collect()
     }
 }
 
 pub struct ChatHistory {
     sessions: Mutex<Vec<ChatSession>>,
+    // Always locked after `sessions` so the two stay in step
+    index: Mutex<MessageIndex>,
 }
 
 impl ChatHistory {
     pub fn new() -> Self {
         Self {
             sessions: Mutex::new(Vec::new()),
+            index: Mutex::new(MessageIndex::new()),
         }
     }
 
-    pub async fn create_session(&self) -> String {
+    pub async fn create_session(&self, workspace: Option<&Workspace>) -> String {
         let mut sessions = self.sessions.lock().await;
-        let session_id = Uuid::new_v4().to_string();
+        let session_id = new_id();
         let now = chrono::Utc::now().timestamp();
 
-        sessions.push(ChatSession {
+        let mut session = ChatSession {
             id: session_id.clone(),
-            messages: Vec::new(),
             created_at: now,
             updated_at: now,
-        });
+            ..Default::default()
+        };
+        if let Some(workspace) = workspace {
+            workspace.apply_to(&mut session);
+        }
+        sessions.push(session);
 
         session_id
     }
 
+    pub async fn insert_session(&self, session: ChatSession) {
+        let mut sessions = self.sessions.lock().await;
+        self.index.lock().await.add_session(&session);
+        sessions.push(session);
+    }
+
     pub async fn add_message(
         &self,
         session_id: &str,
         role: &str,
         content: &str,
     ) -> Result<(), String> {
+        self.push_message(
+            session_id,
+            ChatMessage {
+                role: role.to_string(),
+                content: content.to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn add_assistant_message(
+        &self,
+        session_id: &str,
+        content: &str,
+        provider: &str,
+        model: &str,
+        citations: Vec<Citation>,
+        references: Vec<String>,
+    ) -> Result<(), String> {
+        self.push_message(
+            session_id,
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: content.to_string(),
+                provider: Some(provider.to_string()),
+                model: Some(model.to_string()),
+                citations,
+                references,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    // Appends the message to the session, filling in its id, parent and timestamp
+    async fn push_message(&self, session_id: &str, message: ChatMessage) -> Result<(), String> {
         let mut sessions = self.sessions.lock().await;
         let session = sessions
             .iter_mut()
@@ -75,11 +201,15 @@ impl ChatHistory {
             .ok_or_else(|| "Session not found".to_string())?;
 
         let now = chrono::Utc::now().timestamp();
-        session.messages.push(ChatMessage {
-            role: role.to_string(),
-            content: content.to_string(),
+        let parent_id = session.messages.last().map(|m| m.id.clone());
+        let message = ChatMessage {
+            id: new_id(),
+            parent_id,
             timestamp: now,
-        });
+            ..message
+        };
+        self.index.lock().await.add_message(session_id, &message);
+        session.messages.push(message);
 
         session.updated_at = now;
 
@@ -96,6 +226,91 @@ impl ChatHistory {
         sessions.clone()
     }
 
+    pub async fn list_summaries(&self, filter: &SessionFilter, sort: SessionSort) -> Vec<SessionSummary> {
+        let sessions = self.sessions.lock().await;
+        let mut summaries: Vec<SessionSummary> = sessions
+            .iter()
+            .map(SessionSummary::from)
+            .filter(|s| filter.matches(s))
+            .collect();
+        sort.sort(&mut summaries);
+        summaries
+    }
+
+    pub async fn update_metadata(
+        &self,
+        session_id: &str,
+        patch: SessionMetadataPatch,
+    ) -> Result<SessionSummary, String> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+
+        patch.apply(session);
+        Ok(SessionSummary::from(&*session))
+    }
+
+    // Stores generated values only where the user hasn't set their own; None if nothing changed
+    pub async fn fill_generated_metadata(
+        &self,
+        session_id: &str,
+        title: Option<String>,
+        description: Option<String>,
+    ) -> Result<Option<SessionSummary>, String> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+
+        let mut changed = false;
+        if session.title.is_none() && title.is_some() {
+            session.title = title;
+            changed = true;
+        }
+        if session.description.is_none() && description.is_some() {
+            session.description = description;
+            changed = true;
+        }
+        Ok(changed.then(|| SessionSummary::from(&*session)))
+    }
+
+    pub async fn set_paper_summary(&self, session_id: &str, summary: PaperSummary) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().await;
+        let paper = sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| "Session not found".to_string())?
+            .paper
+            .as_mut()
+            .ok_or_else(|| "Not a paper-reading session".to_string())?;
+        paper.summary = Some(summary);
+        Ok(())
+    }
+
+    pub async fn attach_execution(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        execution: ExecutionResult,
+    ) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        let message = session
+            .messages
+            .iter_mut()
+            .find(|m| m.id == message_id)
+            .ok_or_else(|| "Message not found".to_string())?;
+        message.executions.push(execution);
+        session.updated_at = chrono::Utc::now().timestamp();
+        Ok(())
+    }
+
     pub async fn delete_session(&self, session_id: &str) -> Result<(), String> {
         let mut sessions = self.sessions.lock().await;
         let position = sessions
@@ -104,9 +319,27 @@ impl ChatHistory {
             .ok_or_else(|| "Session not found".to_string())?;
 
         sessions.remove(position);
+        self.index.lock().await.remove_session(session_id);
         Ok(())
     }
 
+    // Deletes the workspace's sessions, or keeps them as standalone sessions
+    pub async fn release_workspace(&self, workspace_id: &str, delete_sessions: bool) {
+        let mut sessions = self.sessions.lock().await;
+        let mut index = self.index.lock().await;
+        sessions.retain_mut(|session| {
+            if session.workspace_id.as_deref() != Some(workspace_id) {
+                return true;
+            }
+            if delete_sessions {
+                index.remove_session(&session.id);
+                return false;
+            }
+            session.workspace_id = None;
+            true
+        });
+    }
+
     pub async fn clear_session(&self, session_id: &str) -> Result<(), String> {
         let mut sessions = self.sessions.lock().await;
         let session = sessions
@@ -116,6 +349,23 @@ impl ChatHistory {
 
         session.messages.clear();
         session.updated_at = chrono::Utc::now().timestamp();
+        self.index.lock().await.remove_session(session_id);
         Ok(())
     }
+
+    pub async fn search(&self, query: &str, filters: &SearchFilters, limit: usize) -> Vec<SearchHit> {
+        let mut hits = self.index.lock().await.search(query, filters, limit);
+        self.fill_session_titles(&mut hits).await;
+        hits
+    }
+
+    pub async fn fill_session_titles(&self, hits: &mut [SearchHit]) {
+        let sessions = self.sessions.lock().await;
+        for hit in hits {
+            hit.session_title = sessions
+                .iter()
+                .find(|s| s.id == hit.session_id)
+                .and_then(|s| s.title.clone());
+        }
+    }
 }
@@ -1,17 +1,49 @@
-use crate::chat::{ChatHistory, ChatMessage};
-use crate::llm_bridge::{LLMRequest, LLMServiceError};
+use crate::budget::{complete_with_budget, UsageContext};
+use crate::chat::{
+    render_sessions, spawn_session_metadata, ChatHistory, ChatMessage, ChatSession, ExportFormat, SessionFilter,
+    SessionMetadataPatch, SessionSort, SessionSummary,
+};
+use crate::llm_bridge::{LLMBridge, LLMRequest, LLMServiceError};
+use crate::rag::{citations, ground_messages, locate_source, parse_citations, GroundedResponse, SourcePassage};
+use crate::workspace::workspace_store;
 use crate::AppState;
+use std::fs;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
 
 #[tauri::command]
-pub async fn create_session(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<String, ()> {
+pub async fn create_session(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: Option<String>,
+) -> Result<String, String> {
     let mut holder = app_state.lock().await;
     if holder.chat_history.is_none() {
         holder.chat_history = Some(Arc::new(ChatHistory::new()));
     }
+    let workspace = match &workspace_id {
+        Some(workspace_id) => Some(
+            workspace_store(&holder)?
+                .get(workspace_id)
+                .await
+                .ok_or_else(|| "Workspace not found".to_string())?,
+        ),
+        None => None,
+    };
     let chat_history = holder.chat_history.as_ref().unwrap();
-    Ok(chat_history.create_session().await)
+    Ok(chat_history.create_session(workspace.as_ref()).await)
+}
+
+// The session's own provider and model if it has any, otherwise the configured bridge
+pub fn session_bridge(holder: &AppState, session: &ChatSession) -> Result<(String, Arc<dyn LLMBridge>), String> {
+    let config = holder.config.as_ref().unwrap();
+    let provider = session.provider.as_deref().unwrap_or(config.provider());
+    if session.provider.is_none() && session.model.is_none() {
+        let llm_bridge = holder.llm_bridge.clone().ok_or("No LLM provider is configured")?;
+        return Ok((provider.to_string(), llm_bridge));
+    }
+    let llm_bridge = config.create_bridge(provider, session.model.as_deref())?;
+    Ok((provider.to_string(), llm_bridge))
 }
 
 #[tauri::command]
@@ -43,17 +75,25 @@ pub async fn get_session(
 #[tauri::command]
 pub async fn list_sessions(
     app_state: tauri::State<'_, Mutex<AppState>>,
-) -> Result<Vec<String>, ()> {
+    filter: Option<SessionFilter>,
+    sort: Option<SessionSort>,
+) -> Result<Vec<SessionSummary>, ()> {
     let holder = app_state.lock().await;
     let chat_history = holder.chat_history.as_ref().unwrap();
     Ok(chat_history
-        .list_sessions()
-
+        .list_summaries(&filter.unwrap_or_default(), sort.unwrap_or_default())
+        .await)
+}
 
-        .await
-        .into_iter()
-        .map(|session| session.id)
-        .collect())
+#[tauri::command]
+pub async fn update_session(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+    patch: SessionMetadataPatch,
+) -> Result<SessionSummary, String> {
+    let holder = app_state.lock().await;
+    let chat_history = holder.chat_history.as_ref().unwrap();
+    chat_history.update_metadata(&session_id, patch).await
 }
 
 #[tauri::command]
@@ -63,30 +103,215 @@ pub async fn delete_session(
 ) -> Result<(), String> {
     let holder = app_state.lock().await;
     let chat_history = holder.chat_history.as_ref().unwrap();
-    chat_history.delete_session(&session_id).await
+    chat_history.delete_session(&session_id).await?;
+    if let Some(message_vectors) = holder.message_vectors.as_ref() {
+        message_vectors.remove_session(&session_id).await?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn ask_question(
+    app: tauri::AppHandle,
     app_state: tauri::State<'_, Mutex<AppState>>,
     session_id: String,
     question: String,
-) -> Result<String, LLMServiceError> {
-    let holder = app_state.lock().await;
-    let chat_history = holder.chat_history.as_ref().unwrap();
-    chat_history
-        .add_message(&session_id, "user", &question)
-        .await
-        .map_err(|e| LLMServiceError { error: e })?;
-    let session = chat_history.get_session(&session_id).await.unwrap();
-    let llm_bridge = holder.llm_bridge.as_ref().unwrap();
+) -> Result<GroundedResponse, LLMServiceError> {
+    // Only what is needed is taken from the app state, so other commands aren't held up by the
+    // embedding, retrieval and completion requests below
+    let (chat_history, session, provider, llm_bridge, documents, library, budget, message_vectors, titler) = {
+        let holder = app_state.lock().await;
+        let chat_history = holder.chat_history.clone().unwrap();
+        chat_history
+            .add_message(&session_id, "user", &question)
+            .await
+            .map_err(|e| LLMServiceError { error: e })?;
+        let session = chat_history.get_session(&session_id).await.unwrap();
+        let (provider, llm_bridge) =
+            session_bridge(&holder, &session).map_err(|e| LLMServiceError { error: e })?;
+        (
+            chat_history,
+            session,
+            provider,
+            llm_bridge,
+            holder.documents.clone(),
+            holder.library.clone(),
+            holder.budget.clone(),
+            holder.message_vectors.clone(),
+            holder.session_titler.clone(),
+        )
+    };
+    // Retrieval problems shouldn't stop the question from being answered
+    let mut retrieved = match documents.as_ref().filter(|d| d.enabled()) {
+        Some(documents) => documents.retrieve(&question).await.unwrap_or_else(|e| {
+            log::warn!("Document retrieval failed: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    if let Some(library) = library.as_ref().filter(|_| !retrieved.is_empty()) {
+        library.annotate(&mut retrieved).await;
+    }
     let llm_request = LLMRequest {
-        messages: session.into(),
+        params: session.params,
+        messages: ground_messages(session.into(), &retrieved),
+        format: None,
+        tools: Vec::new(),
+    };
+    let ctx = UsageContext {
+        provider: provider.clone(),
+        model: llm_bridge.model().to_string(),
+        session_id: Some(session_id.clone()),
+        agent_run_id: None,
+    };
+    let response = complete_with_budget(&app, llm_bridge.as_ref(), budget.as_deref(), ctx, llm_request).await?;
+    let cited = parse_citations(&response.content, &retrieved);
+    let references = match library.as_ref() {
+        Some(library) => {
+            let cited_chunks: Vec<_> = cited
+                .iter()
+                .filter_map(|c| c.number.checked_sub(1).and_then(|i| retrieved.get(i)))
+                .collect();
+            library.cited_keys(&response.content, &cited_chunks).await
+        }
+        None => Vec::new(),
     };
-    let response = llm_bridge.complete(llm_request).await?;
     chat_history
-        .add_message(&session_id, "assistant", &response.content)
+        .add_assistant_message(
+            &session_id,
+            &response.content,
+            &provider,
+            llm_bridge.model(),
+            cited.clone(),
+            references.clone(),
+        )
         .await
         .map_err(|e| LLMServiceError { error: e })?;
-    Ok(response.content)
+
+    // The session may have been deleted while the answer was being written
+    if let Some(session) = chat_history.get_session(&session_id).await {
+        if let Some(message_vectors) = message_vectors {
+            let session_id = session_id.clone();
+            let messages = session.messages.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = message_vectors.add_messages(&session_id, &messages).await {
+                    log::warn!("Failed to embed messages for semantic search: {}", e);
+                }
+            });
+        }
+
+        if let Some(titler) = titler {
+            let first_exchange = session.messages.iter().filter(|m| m.role == "assistant").count() == 1;
+            if first_exchange {
+                spawn_session_metadata(app.clone(), titler, budget, chat_history.clone(), session);
+            }
+        }
+    }
+    Ok(GroundedResponse {
+        response,
+        sources: citations(&retrieved),
+        citations: cited,
+        references,
+    })
+}
+
+// The document passage behind the [number] marker of an answer, with the quote located in it
+#[tauri::command]
+pub async fn open_citation(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+    message_id: String,
+    number: usize,
+) -> Result<SourcePassage, String> {
+    let source = {
+        let holder = app_state.lock().await;
+        let chat_history = holder.chat_history.as_ref().unwrap();
+        let session = chat_history
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| "Session not found".to_string())?;
+        let message = session
+            .messages
+            .iter()
+            .find(|m| m.id == message_id)
+            .ok_or_else(|| "Message not found".to_string())?;
+        let citation = message
+            .citations
+            .iter()
+            .find(|c| c.number == number)
+            .ok_or_else(|| format!("The answer has no citation [{}]", number))?;
+        citation
+            .source
+            .clone()
+            .ok_or_else(|| format!("[{}] doesn't match any retrieved excerpt", number))?
+    };
+    tauri::async_runtime::spawn_blocking(move || locate_source(&source))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Renders the sessions and writes them wherever the user picks; returns None if cancelled
+#[tauri::command]
+pub async fn export_sessions(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_ids: Vec<String>,
+    format: ExportFormat,
+) -> Result<Option<String>, String> {
+    let sessions = {
+        let holder = app_state.lock().await;
+        let chat_history = holder.chat_history.as_ref().unwrap();
+        let mut sessions = Vec::new();
+        for session_id in &session_ids {
+            let session = chat_history
+                .get_session(session_id)
+                .await
+                .ok_or_else(|| format!("Session {} not found", session_id))?;
+            sessions.push(session);
+        }
+        sessions
+    };
+
+    let rendered = render_sessions(&sessions, format);
+    let file_name = match sessions.as_slice() {
+        [session] => format!("ccru-{}", session.id),
+        _ => "ccru-export".to_string(),
+    };
+    save_export(&app, &file_name, format, rendered).await
+}
+
+// Asks where to save a rendered export; returns the chosen path, or None if cancelled
+pub async fn save_export(
+    app: &tauri::AppHandle,
+    file_stem: &str,
+    format: ExportFormat,
+    rendered: String,
+) -> Result<Option<String>, String> {
+    save_file(app, file_stem, format.label(), format.extension(), rendered).await
+}
+
+// Asks where to save a file of the given type; returns the chosen path, or None if cancelled
+pub async fn save_file(
+    app: &tauri::AppHandle,
+    file_stem: &str,
+    label: &str,
+    extension: &str,
+    contents: String,
+) -> Result<Option<String>, String> {
+    let file_name = format!("{}.{}", file_stem, extension);
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_file_name(&file_name)
+        .add_filter(label, &[extension])
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let path = path.into_path().map_err(|e| e.to_string())?;
+    fs::write(&path, contents).map_err(|e| format!("Failed to write export: {}", e))?;
+    Ok(Some(path.display().to_string()))
 }
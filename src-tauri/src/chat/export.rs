@@ -0,0 +1,201 @@
+use crate::chat::{ChatMessage, ChatSession};
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+
+pub const EXPORT_FORMAT_NAME: &str = "ccru-chat-export";
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Html => "HTML",
+        }
+    }
+}
+
+// Canonical export document, readable back by the importer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatExport {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+    pub sessions: Vec<ChatSession>,
+}
+
+pub fn render_sessions(sessions: &[ChatSession], format: ExportFormat) -> String {
+    render(None, sessions, format)
+}
+
+// JSON exports carry the workspace itself; the other formats hold just its sessions
+pub fn render_workspace(workspace: &Workspace, sessions: &[ChatSession], format: ExportFormat) -> String {
+    render(Some(workspace), sessions, format)
+}
+
+fn render(workspace: Option<&Workspace>, sessions: &[ChatSession], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => sessions
+            .iter()
+            .map(render_markdown)
+            .collect::<Vec<_>>()
+            .join("\n---\n\n"),
+        ExportFormat::Json => {
+            let export = ChatExport {
+                format: EXPORT_FORMAT_NAME.to_string(),
+                version: EXPORT_FORMAT_VERSION,
+                exported_at: chrono::Utc::now().timestamp(),
+                workspace: workspace.cloned(),
+                sessions: sessions.to_vec(),
+            };
+            serde_json::to_string_pretty(&export).expect("Failed to serialize chat export")
+        }
+        ExportFormat::Html => render_html(sessions),
+    }
+}
+
+// Splits a DeepSeek-R1 style `<think>...</think>` prefix from the visible answer
+pub fn split_reasoning(content: &str) -> (Option<&str>, &str) {
+    let trimmed = content.trim_start();
+    if let Some(rest) = trimmed.strip_prefix("<think>") {
+        if let Some(end) = rest.find("</think>") {
+            let reasoning = rest[..end].trim();
+            let answer = rest[end + "</think>".len()..].trim_start();
+            return ((!reasoning.is_empty()).then_some(reasoning), answer);
+        }
+    }
+    (None, content)
+}
+
+fn session_heading(session: &ChatSession) -> String {
+    match &session.title {
+        Some(title) => title.clone(),
+        None => format!("Session {}", session.id),
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn role_label(role: &str) -> String {
+    let mut chars = role.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn message_meta(message: &ChatMessage) -> String {
+    let mut parts = vec![format_timestamp(message.timestamp)];
+    match (&message.provider, &message.model) {
+        (Some(provider), Some(model)) => parts.push(format!("{} / {}", provider, model)),
+        (Some(provider), None) => parts.push(provider.clone()),
+        (None, Some(model)) => parts.push(model.clone()),
+        (None, None) => {}
+    }
+    parts.join(" · ")
+}
+
+fn render_markdown(session: &ChatSession) -> String {
+    let mut out = format!("# {}\n\n", session_heading(session));
+    out.push_str(&format!("- Created: {}\n", format_timestamp(session.created_at)));
+    out.push_str(&format!("- Updated: {}\n\n", format_timestamp(session.updated_at)));
+
+    for message in &session.messages {
+        out.push_str(&format!(
+            "## {}\n\n_{}_\n\n",
+            role_label(&message.role),
+            message_meta(message)
+        ));
+        let (reasoning, answer) = split_reasoning(&message.content);
+        if let Some(reasoning) = reasoning {
+            out.push_str("<details>\n<summary>Reasoning</summary>\n\n");
+            out.push_str(reasoning);
+            out.push_str("\n\n</details>\n\n");
+        }
+        out.push_str(answer.trim_end());
+        out.push_str("\n\n");
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const HTML_STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #1f2937; }
+section.session { margin-bottom: 3rem; }
+.meta { color: #6b7280; font-size: 0.85rem; }
+.message { border-radius: 0.5rem; padding: 0.75rem 1rem; margin: 1rem 0; }
+.message.user { background: #eff6ff; }
+.message.assistant { background: #f9fafb; border: 1px solid #e5e7eb; }
+.message.system { background: #fefce8; }
+.content { white-space: pre-wrap; }
+details { margin: 0.5rem 0; color: #4b5563; }
+details .content { font-size: 0.9rem; }
+"#;
+
+fn render_html(sessions: &[ChatSession]) -> String {
+    let mut body = String::new();
+    for session in sessions {
+        body.push_str("<section class=\"session\">\n");
+        body.push_str(&format!("<h1>{}</h1>\n", escape_html(&session_heading(session))));
+        body.push_str(&format!(
+            "<p class=\"meta\">Created {} · Updated {}</p>\n",
+            format_timestamp(session.created_at),
+            format_timestamp(session.updated_at)
+        ));
+        for message in &session.messages {
+            let (reasoning, answer) = split_reasoning(&message.content);
+            body.push_str(&format!(
+                "<div class=\"message {}\">\n<h2>{}</h2>\n<p class=\"meta\">{}</p>\n",
+                escape_html(&message.role),
+                escape_html(&role_label(&message.role)),
+                escape_html(&message_meta(message))
+            ));
+            if let Some(reasoning) = reasoning {
+                body.push_str(&format!(
+                    "<details><summary>Reasoning</summary><div class=\"content\">{}</div></details>\n",
+                    escape_html(reasoning)
+                ));
+            }
+            body.push_str(&format!(
+                "<div class=\"content\">{}</div>\n</div>\n",
+                escape_html(answer.trim_end())
+            ));
+        }
+        body.push_str("</section>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>CCRU chat export</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        HTML_STYLE, body
+    )
+}
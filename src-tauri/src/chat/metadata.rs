@@ -0,0 +1,289 @@
+use crate::budget::{complete_with_budget, BudgetTracker, UsageContext};
+use crate::chat::{split_reasoning, ChatHistory, ChatSession};
+use crate::llm_bridge::{LLMBridge, LLMRequest, Prompt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Emitter;
+
+pub const SESSION_UPDATED_EVENT: &str = "session-updated";
+
+const PREVIEW_LENGTH: usize = 120;
+const MAX_TITLE_LENGTH: usize = 80;
+// Only the opening of the conversation is sent to the metadata model
+const METADATA_CONTEXT_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub folder: Option<String>,
+    pub pinned: bool,
+    pub archived: bool,
+    pub workspace_id: Option<String>,
+    // The document of a paper-reading session
+    pub paper: Option<String>,
+    pub message_count: usize,
+    // Start of the first user message, for sessions without a title
+    pub preview: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&ChatSession> for SessionSummary {
+    fn from(session: &ChatSession) -> Self {
+        let preview = session
+            .messages
+            .iter()
+            .find(|m| m.role == "user")
+            .map(|m| truncate(m.content.trim(), PREVIEW_LENGTH));
+        Self {
+            id: session.id.clone(),
+            title: session.title.clone(),
+            description: session.description.clone(),
+            tags: session.tags.clone(),
+            folder: session.folder.clone(),
+            pinned: session.pinned,
+            archived: session.archived,
+            workspace_id: session.workspace_id.clone(),
+            paper: session.paper.as_ref().map(|p| p.structure.document.clone()),
+            message_count: session.messages.len(),
+            preview,
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+        }
+    }
+}
+
+// Fields left out are unchanged; an empty string clears title, description or folder
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionMetadataPatch {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub folder: Option<String>,
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+}
+
+impl SessionMetadataPatch {
+    pub fn apply(self, session: &mut ChatSession) {
+        fn non_empty(value: String) -> Option<String> {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        }
+
+        if let Some(title) = self.title {
+            session.title = non_empty(title);
+        }
+        if let Some(description) = self.description {
+            session.description = non_empty(description);
+        }
+        if let Some(folder) = self.folder {
+            session.folder = non_empty(folder);
+        }
+        if let Some(tags) = self.tags {
+            session.tags = Vec::new();
+            for tag in tags.into_iter().filter_map(non_empty) {
+                if !session.tags.contains(&tag) {
+                    session.tags.push(tag);
+                }
+            }
+        }
+        if let Some(pinned) = self.pinned {
+            session.pinned = pinned;
+        }
+        if let Some(archived) = self.archived {
+            session.archived = archived;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionFilter {
+    pub workspace_id: Option<String>,
+    pub folder: Option<String>,
+    // Sessions must carry every listed tag
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub pinned: Option<bool>,
+    #[serde(default)]
+    pub include_archived: bool,
+    // Case-insensitive match against title, description and preview
+    pub text: Option<String>,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, summary: &SessionSummary) -> bool {
+        let contains = |field: &Option<String>, needle: &str| {
+            field.as_ref().is_some_and(|f| f.to_lowercase().contains(needle))
+        };
+        (self.include_archived || !summary.archived)
+            && self.workspace_id.as_ref().map_or(true, |w| summary.workspace_id.as_ref() == Some(w))
+            && self.folder.as_ref().map_or(true, |f| summary.folder.as_ref() == Some(f))
+            && self.tags.iter().all(|t| summary.tags.contains(t))
+            && self.pinned.map_or(true, |p| summary.pinned == p)
+            && self.text.as_ref().map_or(true, |text| {
+                let needle = text.to_lowercase();
+                contains(&summary.title, &needle)
+                    || contains(&summary.description, &needle)
+                    || contains(&summary.preview, &needle)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionSort {
+    #[default]
+    RecentlyUpdated,
+    RecentlyCreated,
+    Oldest,
+    Title,
+}
+
+impl SessionSort {
+    // Pinned sessions always come first
+    pub fn sort(&self, summaries: &mut [SessionSummary]) {
+        summaries.sort_by(|a, b| {
+            let order = match self {
+                SessionSort::RecentlyUpdated => b.updated_at.cmp(&a.updated_at),
+                SessionSort::RecentlyCreated => b.created_at.cmp(&a.created_at),
+                SessionSort::Oldest => a.created_at.cmp(&b.created_at),
+                SessionSort::Title => display_title(a).cmp(&display_title(b)),
+            };
+            b.pinned.cmp(&a.pinned).then(order)
+        });
+    }
+}
+
+fn display_title(summary: &SessionSummary) -> String {
+    summary
+        .title
+        .as_ref()
+        .or(summary.preview.as_ref())
+        .map(|t| t.to_lowercase())
+        .unwrap_or_default()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+// Generates titles and summaries for new sessions with a (usually cheaper) dedicated model
+pub struct SessionTitler {
+    pub llm_bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub auto_title: bool,
+    pub auto_summary: bool,
+}
+
+impl SessionTitler {
+    fn request(&self, session: &ChatSession) -> LLMRequest {
+        let mut transcript = String::new();
+        for message in &session.messages {
+            let (_, answer) = split_reasoning(&message.content);
+            transcript.push_str(&format!("{}: {}\n\n", message.role, answer.trim()));
+        }
+        let transcript = truncate(&transcript, METADATA_CONTEXT_CHARS);
+
+        let mut instructions = String::from(
+            "Read the conversation below and describe it. Reply with exactly these lines and nothing else:\n",
+        );
+        if self.auto_title {
+            instructions.push_str("Title: <a short title of at most 8 words>\n");
+        }
+        if self.auto_summary {
+            instructions.push_str("Summary: <one or two sentences on what was discussed>\n");
+        }
+
+        LLMRequest {
+            messages: vec![Prompt {
+                role: "user".to_string(),
+                content: format!("{}\n---\n{}", instructions, transcript),
+            }],
+            ..Default::default()
+        }
+    }
+
+    // Pulls "Title:" and "Summary:" lines out of the reply, ignoring any reasoning block
+    fn parse_reply(content: &str) -> (Option<String>, Option<String>) {
+        let (_, answer) = split_reasoning(content);
+        let field = |name: &str| {
+            answer.lines().find_map(|line| {
+                let line = line.trim().trim_start_matches(['*', '#', ' ']);
+                let (key, value) = line.split_once(':')?;
+                if !key.trim_matches('*').trim().eq_ignore_ascii_case(name) {
+                    return None;
+                }
+                let value = value.trim().trim_matches(['*', '"', '\'', ' ']);
+                (!value.is_empty()).then(|| value.to_string())
+            })
+        };
+        (
+            field("title").map(|t| truncate(&t, MAX_TITLE_LENGTH)),
+            field("summary"),
+        )
+    }
+
+    pub async fn generate(
+        &self,
+        app: &tauri::AppHandle,
+        budget: Option<&BudgetTracker>,
+        session: &ChatSession,
+    ) -> Result<(Option<String>, Option<String>), String> {
+        let ctx = UsageContext {
+            provider: self.provider.clone(),
+            model: self.llm_bridge.model().to_string(),
+            session_id: Some(session.id.clone()),
+            agent_run_id: None,
+        };
+        let response = complete_with_budget(
+            app,
+            self.llm_bridge.as_ref(),
+            budget,
+            ctx,
+            self.request(session),
+        )
+        .await
+        .map_err(|e| e.error)?;
+        let (title, summary) = Self::parse_reply(&response.content);
+        Ok((
+            title.filter(|_| self.auto_title),
+            summary.filter(|_| self.auto_summary),
+        ))
+    }
+}
+
+// Runs after the first exchange of a session; failures are only logged
+pub fn spawn_session_metadata(
+    app: tauri::AppHandle,
+    titler: Arc<SessionTitler>,
+    budget: Option<Arc<BudgetTracker>>,
+    chat_history: Arc<ChatHistory>,
+    session: ChatSession,
+) {
+    tauri::async_runtime::spawn(async move {
+        let generated = titler.generate(&app, budget.as_deref(), &session).await;
+        let (title, summary) = match generated {
+            Ok(generated) => generated,
+            Err(e) => {
+                log::warn!("Failed to generate metadata for session {}: {}", session.id, e);
+                return;
+            }
+        };
+        match chat_history.fill_generated_metadata(&session.id, title, summary).await {
+            Ok(Some(summary)) => {
+                if let Err(e) = app.emit(SESSION_UPDATED_EVENT, summary) {
+                    log::warn!("Failed to emit session update: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to store metadata for session {}: {}", session.id, e),
+        }
+    });
+}
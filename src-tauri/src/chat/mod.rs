@@ -2,3 +2,10 @@ mod chat_history;
 pub use chat_history::*;
 mod commands;
 pub use commands::*;
+mod export;
+pub use export::*;
+mod metadata;
+pub use metadata::*;
+
+#[cfg(test)]
+mod tests;
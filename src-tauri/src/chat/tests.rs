@@ -0,0 +1,152 @@
+use super::*;
+use crate::import::{parse_file, ImportFormat};
+
+fn message(role: &str, content: &str) -> ChatMessage {
+    ChatMessage {
+        id: new_id(),
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp: 1_700_000_000,
+        ..Default::default()
+    }
+}
+
+fn session(title: &str, messages: Vec<ChatMessage>) -> ChatSession {
+    ChatSession {
+        id: new_id(),
+        title: Some(title.to_string()),
+        messages,
+        created_at: 1_700_000_000,
+        updated_at: 1_700_000_000,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reasoning_is_split_and_folded() {
+    let content = "  <think>\nWeigh both options.\n</think>\n\nTake the second.";
+    assert_eq!(split_reasoning(content), (Some("Weigh both options."), "Take the second."));
+    assert_eq!(split_reasoning("<think> </think>Answer"), (None, "Answer"));
+    assert_eq!(split_reasoning("<think>never closed"), (None, "<think>never closed"));
+
+    let chat = session("Choices", vec![message("user", "Which?"), message("assistant", content)]);
+    let markdown = render_sessions(&[chat], ExportFormat::Markdown);
+    assert!(markdown.contains("<details>\n<summary>Reasoning</summary>\n\nWeigh both options.\n\n</details>\n\nTake the second."));
+    assert!(!markdown.contains("<think>"));
+}
+
+#[test]
+fn html_export_is_self_contained_and_escaped() {
+    let chat = session(
+        "<script>alert('title')</script>",
+        vec![
+            message("user", "Is 1 < 2 && \"yes\"?"),
+            message("assistant", "<think>a <b>bold</b> guess</think>\n\n<img src=x onerror=alert(1)>"),
+        ],
+    );
+    let html = render_sessions(&[chat], ExportFormat::Html);
+
+    assert!(html.starts_with("<!DOCTYPE html>") && html.contains("<style>"));
+    assert!(!html.contains("<script") && !html.contains("<link") && !html.contains("src=\"http"));
+    assert!(html.contains("&lt;script&gt;alert(&#39;title&#39;)&lt;/script&gt;"));
+    assert!(html.contains("Is 1 &lt; 2 &amp;&amp; &quot;yes&quot;?"));
+    assert!(html.contains("<details><summary>Reasoning</summary><div class=\"content\">a &lt;b&gt;bold&lt;/b&gt; guess</div></details>"));
+    assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+}
+
+#[test]
+fn json_export_round_trips_through_the_importer() {
+    let mut chat = session(
+        "Round trip",
+        vec![
+            message("user", "Show a script"),
+            message("assistant", "```python\n# Comment\nprint('hi')\n```\n\n---"),
+        ],
+    );
+    chat.tags = vec!["code".to_string()];
+    chat.messages[1].provider = Some("openai".to_string());
+    chat.messages[1].model = Some("gpt-4o-mini".to_string());
+    let json = render_sessions(std::slice::from_ref(&chat), ExportFormat::Json);
+    let export: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(export["format"], EXPORT_FORMAT_NAME);
+    assert_eq!(export["version"], EXPORT_FORMAT_VERSION);
+
+    let path = std::env::temp_dir().join(format!("ccru-export-{}.json", new_id()));
+    std::fs::write(&path, &json).unwrap();
+    let parsed = parse_file(&path, ImportFormat::Auto).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(parsed.len(), 1);
+    let imported = parsed[0].as_ref().unwrap();
+    assert_eq!(imported.source_id, Some(format!("ccru:{}", chat.id)));
+    assert_eq!(imported.title, chat.title);
+    assert_eq!(imported.tags, chat.tags);
+    assert_eq!(
+        serde_json::to_value(&imported.messages).unwrap(),
+        serde_json::to_value(&chat.messages).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn sessions_are_patched_filtered_and_sorted() {
+    let history = ChatHistory::new();
+    let mut ids = Vec::new();
+    for (title, created_at) in [("beta", 3), ("Alpha", 1), ("gamma", 2)] {
+        let mut chat = session(title, vec![message("user", &format!("About {}", title))]);
+        chat.created_at = created_at;
+        chat.updated_at = 10 - created_at;
+        ids.push(chat.id.clone());
+        history.insert_session(chat).await;
+    }
+    let patch = |value: serde_json::Value| serde_json::from_value::<SessionMetadataPatch>(value).unwrap();
+    let summary = history
+        .update_metadata(&ids[2], patch(serde_json::json!({ "tags": ["ml", " ml ", "", "nlp"], "folder": "Reading", "pinned": true })))
+        .await
+        .unwrap();
+    assert_eq!(summary.tags, ["ml", "nlp"]);
+    assert_eq!(summary.title.as_deref(), Some("gamma"));
+    history.update_metadata(&ids[0], patch(serde_json::json!({ "title": " ", "archived": true }))).await.unwrap();
+
+    let titles = |summaries: Vec<SessionSummary>| -> Vec<Option<String>> { summaries.into_iter().map(|s| s.title).collect() };
+    let all = SessionFilter {
+        include_archived: true,
+        ..Default::default()
+    };
+    // Pinned first, then by the chosen order; the cleared title sorts by its preview
+    assert_eq!(
+        titles(history.list_summaries(&all, SessionSort::Title).await),
+        [Some("gamma".to_string()), None, Some("Alpha".to_string())]
+    );
+    assert_eq!(
+        titles(history.list_summaries(&all, SessionSort::Oldest).await),
+        [Some("gamma".to_string()), Some("Alpha".to_string()), None]
+    );
+    assert_eq!(history.list_summaries(&SessionFilter::default(), SessionSort::default()).await.len(), 2);
+    let filter = |value: serde_json::Value| serde_json::from_value::<SessionFilter>(value).unwrap();
+    let found = history.list_summaries(&filter(serde_json::json!({ "tags": ["nlp"], "text": "GAM" })), SessionSort::default()).await;
+    assert_eq!(titles(found), [Some("gamma".to_string())]);
+    let found = history.list_summaries(&filter(serde_json::json!({ "text": "about beta", "include_archived": true })), SessionSort::default()).await;
+    assert_eq!(found[0].id, ids[0]);
+    assert!(history.list_summaries(&filter(serde_json::json!({ "folder": "Other" })), SessionSort::default()).await.is_empty());
+}
+
+#[tokio::test]
+async fn generated_metadata_never_replaces_what_the_user_set() {
+    let history = ChatHistory::new();
+    let chat = session("Mine", vec![message("user", "Hi")]);
+    let id = chat.id.clone();
+    history.insert_session(chat).await;
+
+    let filled = history
+        .fill_generated_metadata(&id, Some("Generated".to_string()), Some("A greeting".to_string()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(filled.title.as_deref(), Some("Mine"));
+    assert_eq!(filled.description.as_deref(), Some("A greeting"));
+    let unchanged = history
+        .fill_generated_metadata(&id, Some("Generated".to_string()), Some("Another".to_string()))
+        .await
+        .unwrap();
+    assert!(unchanged.is_none());
+    assert!(history.fill_generated_metadata("missing", None, None).await.is_err());
+}
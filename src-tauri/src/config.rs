@@ -1,11 +1,28 @@
-use crate::llm_bridge::{AzureBridge, DeepSeekBridge, OllamaBridge, AzureDeepSeekBridge};
+use crate::agent::{
+    register_builtin_tools, AgentRunStore, DataTables, DataTools, Database, FileJournal, FileScope, RiskLevel, SqlTools,
+    ToolLimits, ToolRegistry, DEFAULT_MAX_TOOL_OUTPUT_BYTES, DEFAULT_TOOL_TIMEOUT_SECONDS,
+};
+use crate::bibliography::Library;
+use crate::budget::{BudgetTracker, BudgetedEmbedder};
+use crate::chat::SessionTitler;
+use crate::extraction::TableStore;
+use crate::rag::{DocumentIndex, FolderWatcher};
+use crate::review::ReviewStore;
+use crate::sandbox::CodeRunner;
+use crate::search::SemanticMessageIndex;
+use crate::summarize::{Summarizer, SummaryCache, SummaryStyle};
+use crate::workspace::WorkspaceStore;
+use crate::llm_bridge::{AzureBridge, DeepSeekBridge, OllamaBridge, AzureDeepSeekBridge, LLMBridge, MockBridge, OpenAIBridge};
 use crate::AppState;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::sync::Arc;
+use std::time::Duration;
+use tauri::Manager;
 use tokio::sync::Mutex;
 use toml;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 
@@ -13,6 +30,8 @@ pub struct OllamaConfig {
     #[serde(rename = "baseUrl")]
     base_url: String,
     model: String,
+    #[serde(rename = "embeddingModel")]
+    embedding_model: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -22,6 +41,20 @@ pub struct AzureConfig {
     #[serde(rename = "apiKey")]
     api_key: String,
     model: String,
+    #[serde(rename = "embeddingModel")]
+    embedding_model: Option<String>,
+}
+
+// Any endpoint speaking the OpenAI chat completions and embeddings API
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OpenAIConfig {
+    #[serde(rename = "baseUrl")]
+    base_url: String,
+    #[serde(rename = "apiKey", default)]
+    api_key: String,
+    model: String,
+    #[serde(rename = "embeddingModel")]
+    embedding_model: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -32,20 +65,365 @@ pub struct DeepSeekConfig {
 }
 
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MockConfig {
+    script: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AIService {
     provider: String,
+    // Provider whose embedding model is used; defaults to the chat provider
+    #[serde(rename = "embeddingProvider")]
+    embedding_provider: Option<String>,
     ollama: Option<OllamaConfig>,
     azure: Option<AzureConfig>,
+    openai: Option<OpenAIConfig>,
     deepseek: Option<DeepSeekConfig>,
     #[serde(rename = "azure-deepseek", alias = "azureDeepSeek")]
     azure_deepseek: Option<AzureDeepSeekConfig>,
+    mock: Option<MockConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     #[serde(rename = "ai-service")]
     ai_service: AIService,
+    budget: Option<BudgetConfig>,
+    #[serde(rename = "session-metadata")]
+    session_metadata: Option<SessionMetadataConfig>,
+    rag: Option<RagConfig>,
+    search: Option<SearchConfig>,
+    summarization: Option<SummarizationConfig>,
+    sandbox: Option<SandboxConfig>,
+    agent: Option<AgentConfig>,
+}
+
+impl Config {
+    pub fn provider(&self) -> &str {
+        &self.ai_service.provider
+    }
+
+    pub fn agent(&self) -> AgentConfig {
+        self.agent.clone().unwrap_or_default()
+    }
+
+    pub fn create_bridge(&self, provider: &str, model: Option<&str>) -> Result<Arc<dyn LLMBridge>, String> {
+        create_bridge(&self.ai_service, provider, model)
+    }
+}
+
+// Automatic titles and summaries for new sessions; provider and model default to the main ones
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionMetadataConfig {
+    #[serde(rename = "autoTitle", default)]
+    pub auto_title: bool,
+    #[serde(rename = "autoSummary", default)]
+    pub auto_summary: bool,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+// Retrieval over local documents; embeddings come from the embedding provider configured above
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RagConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // Approximate chunk length and overlap, in bytes of text
+    #[serde(rename = "chunkSize", default = "default_chunk_size")]
+    pub chunk_size: usize,
+    #[serde(rename = "chunkOverlap", default = "default_chunk_overlap")]
+    pub chunk_overlap: usize,
+    #[serde(rename = "topK", default = "default_top_k")]
+    pub top_k: usize,
+    #[serde(rename = "minScore", default)]
+    pub min_score: f32,
+    // Folders kept indexed automatically as files are added, changed or deleted
+    #[serde(rename = "watchedFolders", default, skip_serializing_if = "Vec::is_empty")]
+    pub watched_folders: Vec<String>,
+}
+
+fn default_chunk_size() -> usize {
+    1200
+}
+
+fn default_chunk_overlap() -> usize {
+    200
+}
+
+fn default_top_k() -> usize {
+    5
+}
+
+// Semantic search embeds every chat message with the embedding provider
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchConfig {
+    #[serde(default)]
+    pub semantic: bool,
+}
+
+// Long documents are summarized in pieces that are then merged; provider and model default to the main ones
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SummarizationConfig {
+    // Approximate length of each piece, in tokens
+    #[serde(rename = "chunkTokens", default = "default_chunk_tokens")]
+    pub chunk_tokens: usize,
+    // How many summaries are merged at once
+    #[serde(rename = "mergeFanIn", default = "default_merge_fan_in")]
+    pub merge_fan_in: usize,
+    // Requests in flight at the same time
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default)]
+    pub style: SummaryStyle,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+impl Default for SummarizationConfig {
+    fn default() -> Self {
+        Self {
+            chunk_tokens: default_chunk_tokens(),
+            merge_fan_in: default_merge_fan_in(),
+            concurrency: default_concurrency(),
+            style: SummaryStyle::default(),
+            provider: None,
+            model: None,
+        }
+    }
+}
+
+fn default_chunk_tokens() -> usize {
+    3000
+}
+
+fn default_merge_fan_in() -> usize {
+    8
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+// Tool actions at or above `approvalThreshold` wait for the user's approval
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentConfig {
+    #[serde(rename = "approvalThreshold", default = "default_approval_threshold")]
+    pub approval_threshold: RiskLevel,
+    #[serde(rename = "maxSteps", default = "default_max_steps")]
+    pub max_steps: usize,
+    #[serde(default = "default_agent_temperature")]
+    pub temperature: f32,
+    #[serde(rename = "maxTokens", default = "default_agent_max_tokens")]
+    pub max_tokens: u32,
+    #[serde(rename = "toolTimeoutSeconds", default = "default_tool_timeout_seconds")]
+    pub tool_timeout_seconds: u64,
+    // Seconds per tool name, for tools that need more or less than the default
+    #[serde(rename = "toolTimeouts", default)]
+    pub tool_timeouts: HashMap<String, u64>,
+    #[serde(rename = "maxToolOutputBytes", default = "default_max_tool_output_bytes")]
+    pub max_tool_output_bytes: usize,
+    // Folders the file tools may read and write; without any, the agent gets no file tools
+    #[serde(rename = "allowedRoots", default)]
+    pub allowed_roots: Vec<String>,
+    // Largest file the file tools read, search or write
+    #[serde(rename = "maxFileBytes", default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    // SQLite files the agent may query read-only, by the name it uses for them
+    #[serde(default)]
+    pub databases: HashMap<String, String>,
+    // Largest CSV file load_csv reads into memory
+    #[serde(rename = "maxCsvBytes", default = "default_max_csv_bytes")]
+    pub max_csv_bytes: u64,
+}
+
+impl AgentConfig {
+    pub fn tool_limits(&self) -> ToolLimits {
+        ToolLimits {
+            timeout: Duration::from_secs(self.tool_timeout_seconds),
+            timeouts: self
+                .tool_timeouts
+                .iter()
+                .map(|(name, seconds)| (name.clone(), Duration::from_secs(*seconds)))
+                .collect(),
+            max_output_bytes: self.max_tool_output_bytes,
+        }
+    }
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            approval_threshold: default_approval_threshold(),
+            max_steps: default_max_steps(),
+            temperature: default_agent_temperature(),
+            max_tokens: default_agent_max_tokens(),
+            tool_timeout_seconds: default_tool_timeout_seconds(),
+            tool_timeouts: HashMap::new(),
+            max_tool_output_bytes: default_max_tool_output_bytes(),
+            allowed_roots: Vec::new(),
+            max_file_bytes: default_max_file_bytes(),
+            databases: HashMap::new(),
+            max_csv_bytes: default_max_csv_bytes(),
+        }
+    }
+}
+
+fn default_approval_threshold() -> RiskLevel {
+    RiskLevel::WritesFiles
+}
+
+fn default_max_steps() -> usize {
+    10
+}
+
+fn default_agent_temperature() -> f32 {
+    0.2
+}
+
+fn default_agent_max_tokens() -> u32 {
+    2000
+}
+
+fn default_tool_timeout_seconds() -> u64 {
+    DEFAULT_TOOL_TIMEOUT_SECONDS
+}
+
+fn default_max_tool_output_bytes() -> usize {
+    DEFAULT_MAX_TOOL_OUTPUT_BYTES
+}
+
+fn default_max_file_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_max_csv_bytes() -> u64 {
+    50 * 1024 * 1024
+}
+
+// Limits for code run by the agent or from chat; network access is off unless allowed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SandboxConfig {
+    #[serde(default = "default_python")]
+    pub python: String,
+    #[serde(rename = "cpuSeconds", default = "default_cpu_seconds")]
+    pub cpu_seconds: u64,
+    #[serde(rename = "memoryMb", default = "default_memory_mb")]
+    pub memory_mb: u64,
+    #[serde(rename = "timeoutSeconds", default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+    #[serde(rename = "allowNetwork", default)]
+    pub allow_network: bool,
+    // Per stream; stdout and stderr are each cut off beyond this
+    #[serde(rename = "maxOutputBytes", default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+    // Largest file the code may write, and the most kept of all its files together
+    #[serde(rename = "maxFileMb", default = "default_max_file_mb")]
+    pub max_file_mb: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            python: default_python(),
+            cpu_seconds: default_cpu_seconds(),
+            memory_mb: default_memory_mb(),
+            timeout_seconds: default_timeout_seconds(),
+            allow_network: false,
+            max_output_bytes: default_max_output_bytes(),
+            max_file_mb: default_max_file_mb(),
+        }
+    }
+}
+
+fn default_python() -> String {
+    if cfg!(windows) { "python" } else { "python3" }.to_string()
+}
+
+fn default_cpu_seconds() -> u64 {
+    10
+}
+
+fn default_memory_mb() -> u64 {
+    1024
+}
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_max_output_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_max_file_mb() -> u64 {
+    100
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BudgetConfig {
+    #[serde(rename = "softThreshold", default = "default_soft_threshold")]
+    pub soft_threshold: f64,
+    #[serde(default)]
+    pub pricing: HashMap<String, PricingConfig>,
+    #[serde(default)]
+    pub limits: Vec<BudgetLimit>,
+}
+
+fn default_soft_threshold() -> f64 {
+    0.8
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            soft_threshold: default_soft_threshold(),
+            pricing: HashMap::new(),
+            limits: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PricingConfig {
+    #[serde(rename = "inputPerMillion")]
+    pub input_per_million: f64,
+    #[serde(rename = "outputPerMillion")]
+    pub output_per_million: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BudgetScope {
+    Total,
+    Provider,
+    Session,
+    AgentRun,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BudgetLimit {
+    pub scope: BudgetScope,
+    pub provider: Option<String>,
+    pub period: Option<BudgetPeriod>,
+    #[serde(rename = "maxCost")]
+    pub max_cost: Option<f64>,
+    #[serde(rename = "maxTokens")]
+    pub max_tokens: Option<u64>,
+    #[serde(default = "default_hard_limit")]
+    pub hard: bool,
+}
+
+fn default_hard_limit() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -65,6 +443,7 @@ pub struct LoadConfigResponse {
 
 #[tauri::command]
 pub async fn load_config(
+    app: tauri::AppHandle,
     app_state: tauri::State<'_,Mutex<AppState>>,
     config_path: String,
     use_default_when_missing: bool,
@@ -85,34 +464,200 @@ pub async fn load_config(
     let mut app_state = app_state.lock().await;
     app_state.config = Some(config.clone());
 
-    match config.ai_service.provider.as_str() {
-        "ollama" => {
-            app_state.llm_bridge = Some(Arc::new(OllamaBridge::new(
-                &config.ai_service.ollama.as_ref().unwrap().base_url,
-                &config.ai_service.ollama.as_ref().unwrap().model,
-            )));
+    // A bad provider section leaves the app without a model instead of crashing it; commands then say so
+    app_state.mock_bridge = None;
+    let llm_bridge = match config.ai_service.provider.as_str() {
+        "mock" => create_mock_bridge(&config.ai_service).map(|mock_bridge| {
+            let mock_bridge = Arc::new(mock_bridge);
+            app_state.mock_bridge = Some(mock_bridge.clone());
+            mock_bridge as Arc<dyn LLMBridge>
+        }),
+        provider => create_bridge(&config.ai_service, provider, None),
+    };
+    app_state.llm_bridge = match llm_bridge {
+        Ok(llm_bridge) => Some(llm_bridge),
+        Err(e) => {
+            log::error!("No LLM provider is available: {}", e);
+            None
         }
-        "azure" => {
-            app_state.llm_bridge = Some(Arc::new(AzureBridge::new(
-                &config.ai_service.azure.as_ref().unwrap().base_url,
-                &config.ai_service.azure.as_ref().unwrap().model,
-                &config.ai_service.azure.as_ref().unwrap().api_key,
-            )));
+    };
+
+    app_state.session_titler = config
+        .session_metadata
+        .as_ref()
+        .filter(|m| m.auto_title || m.auto_summary)
+        .and_then(|m| {
+            let provider = m.provider.as_deref().unwrap_or(config.provider());
+            // A bridge of its own, so title requests never share state with chat, such as the
+            // turn a mock script is on
+            let llm_bridge = match create_bridge(&config.ai_service, provider, m.model.as_deref()) {
+                Ok(llm_bridge) => llm_bridge,
+                Err(e) => {
+                    log::warn!("Automatic session metadata disabled: {}", e);
+                    return None;
+                }
+            };
+            Some(Arc::new(SessionTitler {
+                llm_bridge,
+                provider: provider.to_string(),
+                auto_title: m.auto_title,
+                auto_summary: m.auto_summary,
+            }))
+        });
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .expect("Failed to resolve app data directory");
+    let summarization = config.summarization.clone().unwrap_or_default();
+    let summary_provider = summarization.provider.clone().unwrap_or_else(|| config.provider().to_string());
+    let summary_bridge = if summarization.provider.is_none() && summarization.model.is_none() {
+        app_state.llm_bridge.clone()
+    } else {
+        match create_bridge(&config.ai_service, &summary_provider, summarization.model.as_deref()) {
+            Ok(llm_bridge) => Some(llm_bridge),
+            Err(e) => {
+                log::warn!("Summarization disabled: {}", e);
+                None
+            }
         }
-        "deepseek" => {
-            app_state.llm_bridge = Some(Arc::new(DeepSeekBridge::new(
-                &config.ai_service.deepseek.as_ref().unwrap().api_key,
-                &config.ai_service.deepseek.as_ref().unwrap().model,
-            )));
+    };
+    // The cache outlives configuration reloads
+    let summary_cache = match &app_state.summarizer {
+        Some(summarizer) => summarizer.cache.clone(),
+        None => Arc::new(SummaryCache::load(data_dir.join("summary-cache.json"))),
+    };
+    app_state.summarizer = summary_bridge.map(|llm_bridge| {
+        Arc::new(Summarizer {
+            llm_bridge,
+            provider: summary_provider,
+            config: summarization,
+            cache: summary_cache,
+        })
+    });
+    let budget_config = config.budget.clone().unwrap_or_default();
+    app_state.budget = Some(Arc::new(
+        BudgetTracker::load(budget_config, data_dir.join("usage.jsonl")).await,
+    ));
+    let embedding_provider = config.ai_service.embedding_provider.as_deref().unwrap_or(config.provider());
+    let embedding_bridge = match embedding_provider == config.provider() {
+        true => app_state.llm_bridge.clone(),
+        false => match create_bridge(&config.ai_service, embedding_provider, None) {
+            Ok(bridge) => Some(bridge),
+            Err(e) => {
+                log::warn!("Embeddings disabled: {}", e);
+                None
+            }
+        },
+    };
+    app_state.embedder = embedding_bridge.map(|bridge| {
+        Arc::new(BudgetedEmbedder {
+            bridge,
+            provider: embedding_provider.to_string(),
+            budget: app_state.budget.clone(),
+            app: Some(app.clone()),
+        })
+    });
+    app_state.documents = match (&config.rag, &app_state.embedder) {
+        (Some(rag), Some(embedder)) if embedder.embedding_model().is_some() => {
+            Some(Arc::new(DocumentIndex::load(
+                rag.clone(),
+                embedder.clone(),
+                &data_dir,
+            )))
         }
-        "azure-deepseek" => {
-            app_state.llm_bridge = Some(Arc::new(AzureDeepSeekBridge::new(
-                &config.ai_service.azure_deepseek.as_ref().unwrap().base_url,
-                "DeepSeek-R1",
-                &config.ai_service.azure_deepseek.as_ref().unwrap().api_key,
-            )));
+        (Some(_), _) => {
+            log::warn!("Document retrieval needs an embeddingModel on the embedding provider");
+            None
+        }
+        (None, _) => None,
+    };
+    if app_state.library.is_none() {
+        app_state.library = Some(Arc::new(Library::load(data_dir.join("library.json"))));
+    }
+    // Replacing the watcher stops the one started by an earlier load
+    app_state.folder_watcher = None;
+    if let (Some(rag), Some(documents)) = (&config.rag, &app_state.documents) {
+        if !rag.watched_folders.is_empty() {
+            let folders = rag.watched_folders.iter().map(PathBuf::from).collect();
+            let library = app_state.library.clone();
+            app_state.folder_watcher = match FolderWatcher::start(app.clone(), documents.clone(), library, folders) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    log::warn!("Watched folders disabled: {}", e);
+                    None
+                }
+            };
+        }
+    }
+    app_state.message_vectors = match (&config.search, &app_state.embedder) {
+        (Some(search), Some(embedder)) if search.semantic && embedder.embedding_model().is_some() => {
+            Some(Arc::new(SemanticMessageIndex::load(
+                embedder.clone(),
+                data_dir.join("message-vectors.json"),
+            )))
+        }
+        (Some(search), _) if search.semantic => {
+            log::warn!("Semantic search needs an embeddingModel on the embedding provider");
+            None
         }
-        _ => panic!("Unsupported AI service provider"),
+        _ => None,
+    };
+    if app_state.workspaces.is_none() {
+        app_state.workspaces = Some(Arc::new(WorkspaceStore::load(data_dir.join("workspaces.json"))));
+    }
+    if app_state.reviews.is_none() {
+        app_state.reviews = Some(Arc::new(ReviewStore::new(data_dir.join("reviews"))));
+    }
+    let code_runner = Arc::new(CodeRunner {
+        config: config.sandbox.clone().unwrap_or_default(),
+        output_dir: data_dir.join("sandbox"),
+    });
+    app_state.code_runner = Some(code_runner.clone());
+    // User tools stay registered across config loads; built-ins are replaced to pick up new settings
+    let tools = app_state
+        .agent_tools
+        .get_or_insert_with(|| Arc::new(ToolRegistry::new(ToolLimits::default())))
+        .clone();
+    let agent_config = config.agent();
+    tools.set_limits(agent_config.tool_limits());
+    let journal = app_state
+        .agent_files
+        .get_or_insert_with(|| Arc::new(FileJournal::new(data_dir.join("agent-files"))))
+        .clone();
+    let scope = Arc::new(FileScope::new(&agent_config.allowed_roots, agent_config.max_file_bytes));
+    let files = (!scope.roots().is_empty()).then(|| (scope.clone(), journal));
+    let tables = app_state
+        .agent_tables
+        .get_or_insert_with(|| Arc::new(DataTables::default()))
+        .clone();
+    let mut databases: Vec<Arc<Database>> = agent_config
+        .databases
+        .iter()
+        .map(|(name, path)| {
+            Arc::new(Database {
+                name: name.clone(),
+                path: PathBuf::from(path),
+            })
+        })
+        .collect();
+    databases.sort_by(|a, b| a.name.cmp(&b.name));
+    let sql = (!databases.is_empty()).then_some(SqlTools {
+        databases,
+        tables: tables.clone(),
+    });
+    let data = files.is_some().then_some(DataTools {
+        scope,
+        tables,
+        max_csv_bytes: agent_config.max_csv_bytes,
+    });
+    let documents = app_state.documents.clone().filter(|d| d.enabled());
+    register_builtin_tools(&tools, code_runner, files, sql, data, documents, app_state.library.clone());
+    if app_state.extractions.is_none() {
+        app_state.extractions = Some(Arc::new(TableStore::new(data_dir.join("extractions"))));
+    }
+    if app_state.agent_runs.is_none() {
+        app_state.agent_runs = Some(Arc::new(AgentRunStore::new(data_dir.join("agent-runs"))));
     }
 
 
@@ -122,6 +667,62 @@ pub async fn load_config(
     })
 }
 
+fn create_mock_bridge(ai_service: &AIService) -> Result<MockBridge, String> {
+    let mock = ai_service.mock.as_ref().ok_or("Provider mock is not configured")?;
+    MockBridge::from_script_file(Path::new(&mock.script))
+        .map_err(|e| format!("Failed to load mock script: {}", e))
+}
+
+// Builds the bridge for a configured provider, optionally with a different model
+fn create_bridge(
+    ai_service: &AIService,
+    provider: &str,
+    model: Option<&str>,
+) -> Result<Arc<dyn LLMBridge>, String> {
+    let not_configured = || format!("Provider {} is not configured", provider);
+    let bridge: Arc<dyn LLMBridge> = match provider {
+        "ollama" => {
+            let ollama = ai_service.ollama.as_ref().ok_or_else(not_configured)?;
+            Arc::new(
+                OllamaBridge::new(&ollama.base_url, model.unwrap_or(&ollama.model))
+                    .with_embedding_model(ollama.embedding_model.as_deref()),
+            )
+        }
+        "azure" => {
+            let azure = ai_service.azure.as_ref().ok_or_else(not_configured)?;
+            Arc::new(
+                AzureBridge::new(&azure.base_url, model.unwrap_or(&azure.model), &azure.api_key)
+                    .with_embedding_model(azure.embedding_model.as_deref()),
+            )
+        }
+        "openai" => {
+            let openai = ai_service.openai.as_ref().ok_or_else(not_configured)?;
+            Arc::new(
+                OpenAIBridge::new(&openai.base_url, &openai.api_key, model.unwrap_or(&openai.model))
+                    .with_embedding_model(openai.embedding_model.as_deref()),
+            )
+        }
+        "deepseek" => {
+            let deepseek = ai_service.deepseek.as_ref().ok_or_else(not_configured)?;
+            Arc::new(DeepSeekBridge::new(
+                &deepseek.api_key,
+                model.unwrap_or(&deepseek.model),
+            ))
+        }
+        "azure-deepseek" => {
+            let azure_deepseek = ai_service.azure_deepseek.as_ref().ok_or_else(not_configured)?;
+            Arc::new(AzureDeepSeekBridge::new(
+                &azure_deepseek.base_url,
+                model.unwrap_or("DeepSeek-R1"),
+                &azure_deepseek.api_key,
+            ))
+        }
+        "mock" => Arc::new(create_mock_bridge(ai_service)?),
+        _ => return Err(format!("Unsupported AI service provider: {}", provider)),
+    };
+    Ok(bridge)
+}
+
 #[tauri::command]
 pub async fn save_config(config: Config, config_path: String) {
     let config_str = toml::to_string(&config).expect("Failed to serialize config to TOML");
@@ -0,0 +1,173 @@
+use crate::chat::{new_id, save_file};
+use crate::extraction::{
+    export_table, validate_fields, Cell, ExtractionTable, Extractor, TableField, TableFormat, TableStatus, TableStore,
+    TableSummary,
+};
+use crate::structured::SchemaValidator;
+use crate::AppState;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn table_store(holder: &AppState) -> Result<Arc<TableStore>, String> {
+    holder
+        .extractions
+        .clone()
+        .ok_or_else(|| "Extraction tables are not loaded yet".to_string())
+}
+
+fn extractor(app: tauri::AppHandle, holder: &AppState) -> Result<Extractor, String> {
+    let config = holder.config.as_ref().ok_or("The configuration is not loaded yet")?;
+    Ok(Extractor {
+        app,
+        provider: config.provider().to_string(),
+        llm_bridge: holder.llm_bridge.clone().ok_or("No LLM provider is configured")?,
+        budget: holder.budget.clone(),
+        library: holder.library.clone(),
+        store: table_store(holder)?,
+    })
+}
+
+// Creates a table and fills it in the background; progress comes as extraction-progress events.
+// Without `documents` every ingested document gets a row
+#[tauri::command]
+pub async fn start_extraction(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    name: String,
+    fields: Vec<TableField>,
+    documents: Option<Vec<String>>,
+) -> Result<ExtractionTable, String> {
+    validate_fields(&fields)?;
+    let fields: Vec<TableField> = fields
+        .into_iter()
+        .map(|f| TableField {
+            name: f.name.trim().to_string(),
+            ..f
+        })
+        .collect();
+    let holder = app_state.lock().await;
+    let documents = match documents {
+        Some(documents) => documents,
+        None => match holder.documents.as_ref() {
+            Some(index) => index.documents().await.into_iter().map(|d| d.path).collect(),
+            None => Vec::new(),
+        },
+    };
+    if documents.is_empty() {
+        return Err("There are no documents to extract from".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut table = ExtractionTable {
+        id: new_id(),
+        name: name.trim().to_string(),
+        fields,
+        documents,
+        status: TableStatus::Extracting,
+        rows: Vec::new(),
+        error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    let extractor = extractor(app, &holder)?;
+    extractor.store.save(&mut table)?;
+    tauri::async_runtime::spawn(extractor.run(table.clone()));
+    Ok(table)
+}
+
+// Extracts rows for documents that are missing or failed, keeping everything else
+#[tauri::command]
+pub async fn resume_extraction(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    table_id: String,
+) -> Result<ExtractionTable, String> {
+    let holder = app_state.lock().await;
+    let extractor = extractor(app, &holder)?;
+    let table = extractor.store.load(&table_id)?;
+    tauri::async_runtime::spawn(extractor.run(table.clone()));
+    Ok(table)
+}
+
+#[tauri::command]
+pub async fn list_extraction_tables(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<TableSummary>, String> {
+    let holder = app_state.lock().await;
+    Ok(table_store(&holder)?.list())
+}
+
+#[tauri::command]
+pub async fn get_extraction_table(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    table_id: String,
+) -> Result<ExtractionTable, String> {
+    let holder = app_state.lock().await;
+    table_store(&holder)?.load(&table_id)
+}
+
+#[tauri::command]
+pub async fn delete_extraction_table(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    table_id: String,
+) -> Result<(), String> {
+    let holder = app_state.lock().await;
+    let store = table_store(&holder)?;
+    if store.is_running(&table_id).await {
+        return Err("The table is still being extracted".to_string());
+    }
+    store.delete(&table_id)
+}
+
+// Sets a cell by hand; the value must fit the field's type, and null clears it
+#[tauri::command]
+pub async fn update_extraction_cell(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    table_id: String,
+    document: String,
+    field: String,
+    value: Value,
+) -> Result<Cell, String> {
+    let holder = app_state.lock().await;
+    let store = table_store(&holder)?;
+    if store.is_running(&table_id).await {
+        return Err("The table is still being extracted".to_string());
+    }
+    let mut table = store.load(&table_id)?;
+    let schema = table
+        .field(&field)
+        .ok_or_else(|| format!("The table has no field named {}", field))?
+        .value_schema();
+    if let Some(problem) = SchemaValidator::new(&schema)?.errors(&value).into_iter().next() {
+        return Err(format!("Invalid value for {}: {}", field, problem));
+    }
+    let row = table.row_mut(&document).ok_or("The table has no row for that document")?;
+    let cell = Cell::corrected(value);
+    row.cells.insert(field, cell.clone());
+    store.save(&mut table)?;
+    Ok(cell)
+}
+
+// Writes the table wherever the user picks; returns None if cancelled
+#[tauri::command]
+pub async fn export_extraction_table(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    table_id: String,
+    format: TableFormat,
+) -> Result<Option<String>, String> {
+    let table = {
+        let holder = app_state.lock().await;
+        table_store(&holder)?.load(&table_id)?
+    };
+    let contents = export_table(&table, format)?;
+    save_file(
+        &app,
+        &format!("ccru-table-{}", table.id),
+        format.label(),
+        format.extension(),
+        contents,
+    )
+    .await
+}
@@ -0,0 +1,75 @@
+use super::ExtractionTable;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Csv,
+    Json,
+}
+
+impl TableFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Json => "json",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "CSV",
+            TableFormat::Json => "JSON",
+        }
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// One row per document; each field gets its value plus confidence and page columns
+pub fn table_csv(table: &ExtractionTable) -> String {
+    let mut header = vec!["document".to_string(), "title".to_string()];
+    for field in &table.fields {
+        header.push(field.name.clone());
+        header.push(format!("{} confidence", field.name));
+        header.push(format!("{} page", field.name));
+    }
+    let mut lines = vec![header.iter().map(|h| csv_field(h)).collect::<Vec<_>>().join(",")];
+    for row in &table.rows {
+        let mut columns = vec![row.document.clone(), row.title.clone().unwrap_or_default()];
+        for field in &table.fields {
+            match row.cells.get(&field.name) {
+                Some(cell) => {
+                    columns.push(cell_text(&cell.value));
+                    columns.push(format!("{:.2}", cell.confidence));
+                    columns.push(cell.page.map(|p| p.to_string()).unwrap_or_default());
+                }
+                None => columns.extend([String::new(), String::new(), String::new()]),
+            }
+        }
+        lines.push(columns.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(","));
+    }
+    lines.join("\n") + "\n"
+}
+
+pub fn export_table(table: &ExtractionTable, format: TableFormat) -> Result<String, String> {
+    match format {
+        TableFormat::Csv => Ok(table_csv(table)),
+        TableFormat::Json => serde_json::to_string_pretty(table).map_err(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,207 @@
+use super::{row_schema, Cell, ExtractionTable, TableField, TableRow, TableStatus, TableStore};
+use crate::bibliography::Library;
+use crate::budget::{complete_with_budget, BudgetTracker, UsageContext};
+use crate::llm_bridge::{LLMBridge, LLMRequest, Prompt};
+use crate::paper::{paper_text, parse_structure};
+use crate::rag::extract_text;
+use crate::structured::{complete_json, DEFAULT_MAX_REPAIRS};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Emitter;
+
+pub const EXTRACTION_PROGRESS_EVENT: &str = "extraction-progress";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractionProgress {
+    pub table_id: String,
+    pub status: TableStatus,
+    // Documents with a row out of the total
+    pub completed: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExtractedCell {
+    value: Value,
+    confidence: f32,
+    page: Option<u32>,
+    quote: Option<String>,
+}
+
+pub fn extraction_prompt(fields: &[TableField], title: &str, text: &str) -> Vec<Prompt> {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|f| match f.description.trim() {
+            "" => format!("- {}", f.name),
+            description => format!("- {}: {}", f.name, description),
+        })
+        .collect();
+    vec![
+        Prompt {
+            role: "system".to_string(),
+            content: format!(
+                "You are filling in a table for a systematic review. Find these fields in the paper given by the user:\n{}\n\
+                 For each field give the value as the paper reports it, or null if it doesn't; your confidence from 0 to 1; \
+                 the [page n] marker it comes from; and a short verbatim quote from the paper that supports it. \
+                 Never guess a value the paper doesn't state.",
+                fields.join("\n")
+            ),
+        },
+        Prompt {
+            role: "user".to_string(),
+            content: format!("# {}{}", title, text),
+        },
+    ]
+}
+
+// Everything an extraction task needs, detached from the app state so it can outlive the command
+pub struct Extractor {
+    pub app: tauri::AppHandle,
+    pub llm_bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub budget: Option<Arc<BudgetTracker>>,
+    pub library: Option<Arc<Library>>,
+    pub store: Arc<TableStore>,
+}
+
+impl Extractor {
+    fn emit(&self, table: &ExtractionTable, document: Option<String>) {
+        let progress = ExtractionProgress {
+            table_id: table.id.clone(),
+            status: table.status,
+            completed: table.rows.iter().filter(|r| r.error.is_none()).count(),
+            total: table.documents.len(),
+            document,
+            error: table.error.clone(),
+        };
+        if let Err(e) = self.app.emit(EXTRACTION_PROGRESS_EVENT, progress) {
+            log::warn!("Failed to emit extraction progress: {}", e);
+        }
+    }
+
+    async fn extract_row(&self, table: &ExtractionTable, document: &str) -> Result<TableRow, String> {
+        let path = document.to_string();
+        let (pages, structure) = tauri::async_runtime::spawn_blocking(move || {
+            extract_text(Path::new(&path)).map(|pages| {
+                let structure = parse_structure(&path, &pages);
+                (pages, structure)
+            })
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+        let name = Path::new(document)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| document.to_string());
+        let entry = match &self.library {
+            Some(library) => library.entry_for_document(document).await,
+            None => None,
+        };
+        let title = entry.and_then(|e| e.title).or_else(|| structure.title.clone());
+
+        let request = LLMRequest {
+            messages: extraction_prompt(&table.fields, title.as_deref().unwrap_or(&name), &paper_text(&structure)),
+            ..Default::default()
+        };
+        let ctx = UsageContext {
+            provider: self.provider.clone(),
+            model: self.llm_bridge.model().to_string(),
+            session_id: None,
+            agent_run_id: None,
+        };
+        let output = complete_json(request, &row_schema(&table.fields), DEFAULT_MAX_REPAIRS, |request| {
+            complete_with_budget(&self.app, self.llm_bridge.as_ref(), self.budget.as_deref(), ctx.clone(), request)
+        })
+        .await?;
+
+        let mut cells = BTreeMap::new();
+        for field in &table.fields {
+            let Some(raw) = output.value.get(&field.name) else {
+                continue;
+            };
+            let extracted: ExtractedCell = serde_json::from_value(raw.clone()).map_err(|e| e.to_string())?;
+            let mut cell = Cell {
+                value: extracted.value,
+                confidence: extracted.confidence.clamp(0.0, 1.0),
+                page: extracted.page.filter(|&p| p >= 1 && p as usize <= structure.page_count),
+                quote: extracted.quote.filter(|q| !q.trim().is_empty()),
+                verified: false,
+                corrected: false,
+            };
+            cell.verify(&pages);
+            cells.insert(field.name.clone(), cell);
+        }
+        Ok(TableRow {
+            document: document.to_string(),
+            title,
+            cells,
+            error: None,
+        })
+    }
+
+    // Fills in every document without a row, retrying failed ones; a failing document doesn't stop the rest
+    async fn advance(&self, table: &mut ExtractionTable) -> Result<(), String> {
+        table.status = TableStatus::Extracting;
+        table.error = None;
+        for document in table.documents.clone() {
+            if table.rows.iter().any(|r| r.document == document && r.error.is_none()) {
+                continue;
+            }
+            self.emit(table, Some(document.clone()));
+            let mut row = match self.extract_row(table, &document).await {
+                Ok(row) => row,
+                Err(e) => {
+                    log::warn!("Extraction from {} failed: {}", document, e);
+                    TableRow {
+                        document: document.clone(),
+                        title: None,
+                        cells: BTreeMap::new(),
+                        error: Some(e),
+                    }
+                }
+            };
+            if let Some(previous) = table.row_mut(&document) {
+                // Corrections made by hand win over anything extracted
+                for (field, cell) in &previous.cells {
+                    if cell.corrected {
+                        row.cells.insert(field.clone(), cell.clone());
+                    }
+                }
+                *previous = row;
+            } else {
+                table.rows.push(row);
+            }
+            table
+                .rows
+                .sort_by_key(|r| table.documents.iter().position(|d| d == &r.document));
+            self.store.save(table)?;
+        }
+        table.status = TableStatus::Completed;
+        self.store.save(table)
+    }
+
+    pub async fn run(self, mut table: ExtractionTable) {
+        if !self.store.claim(&table.id).await {
+            log::warn!("Table {} is already being extracted", table.id);
+            return;
+        }
+        if let Err(e) = self.advance(&mut table).await {
+            log::warn!("Extraction into {} failed: {}", table.id, e);
+            table.status = TableStatus::Failed;
+            table.error = Some(e);
+            if let Err(e) = self.store.save(&mut table) {
+                log::warn!("Failed to save table {}: {}", table.id, e);
+            }
+        }
+        self.emit(&table, None);
+        self.store.release(&table.id).await;
+    }
+}
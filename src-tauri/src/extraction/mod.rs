@@ -0,0 +1,13 @@
+mod table;
+pub use table::*;
+mod store;
+pub use store::*;
+mod export;
+pub use export::*;
+mod extract;
+pub use extract::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
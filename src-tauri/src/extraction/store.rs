@@ -0,0 +1,22 @@
+use super::{ExtractionTable, TableSummary};
+use crate::jobs::{JobRecord, JobStore};
+
+impl JobRecord for ExtractionTable {
+    const NOUN: &'static str = "Table";
+    type Summary = TableSummary;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    fn set_updated_at(&mut self, timestamp: i64) {
+        self.updated_at = timestamp;
+    }
+}
+
+// One JSON file per table under the extractions directory
+pub type TableStore = JobStore<ExtractionTable>;
@@ -0,0 +1,207 @@
+use crate::rag::PageText;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Text,
+    Number,
+    Integer,
+    Boolean,
+}
+
+// A column of the table, described well enough for the model to find it in a paper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableField {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    // Allowed values, for categorical fields
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+impl TableField {
+    // Schema of the value alone; null means the paper doesn't report it
+    pub fn value_schema(&self) -> Value {
+        let kind = match self.field_type {
+            FieldType::Text => "string",
+            FieldType::Number => "number",
+            FieldType::Integer => "integer",
+            FieldType::Boolean => "boolean",
+        };
+        let mut schema = json!({ "type": [kind, "null"] });
+        if !self.options.is_empty() {
+            let mut options: Vec<Value> = self.options.iter().map(|o| json!(o)).collect();
+            options.push(Value::Null);
+            schema["enum"] = Value::Array(options);
+        }
+        schema
+    }
+}
+
+pub fn validate_fields(fields: &[TableField]) -> Result<(), String> {
+    if fields.is_empty() {
+        return Err("The table needs at least one field".to_string());
+    }
+    let mut names = HashSet::new();
+    for field in fields {
+        let name = field.name.trim();
+        if name.is_empty() {
+            return Err("Field names cannot be empty".to_string());
+        }
+        if !names.insert(name.to_lowercase()) {
+            return Err(format!("There are two fields named {}", name));
+        }
+    }
+    Ok(())
+}
+
+// The reply for one document: an object with one entry per field
+pub fn row_schema(fields: &[TableField]) -> Value {
+    let properties: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|field| {
+            let cell = json!({
+                "type": "object",
+                "properties": {
+                    "value": field.value_schema(),
+                    "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                    "page": { "type": ["integer", "null"] },
+                    "quote": { "type": ["string", "null"] }
+                },
+                "required": ["value", "confidence"]
+            });
+            (field.name.clone(), cell)
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>()
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub value: Value,
+    pub confidence: f32,
+    // Where the value comes from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    // The quote was found in the document's text
+    #[serde(default)]
+    pub verified: bool,
+    // Set by hand; kept when the table is extracted again
+    #[serde(default)]
+    pub corrected: bool,
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+impl Cell {
+    // Checks the quote against the pages, preferring the one the model named, and moves the page
+    // to wherever the quote actually is
+    pub fn verify(&mut self, pages: &[PageText]) {
+        let Some(quote) = self.quote.as_deref().map(normalize).filter(|q| !q.is_empty()) else {
+            return;
+        };
+        let stated = pages.iter().filter(|p| p.page.is_some() && p.page == self.page);
+        let found = stated
+            .chain(pages.iter())
+            .find(|p| normalize(&p.text).contains(&quote));
+        if let Some(page) = found {
+            self.verified = true;
+            self.page = page.page;
+        }
+    }
+
+    pub fn corrected(value: Value) -> Self {
+        Self {
+            value,
+            confidence: 1.0,
+            page: None,
+            quote: None,
+            verified: false,
+            corrected: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableRow {
+    pub document: String,
+    pub title: Option<String>,
+    // By field name
+    #[serde(default)]
+    pub cells: BTreeMap<String, Cell>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStatus {
+    Extracting,
+    Completed,
+    Failed,
+}
+
+// A table of fields across documents, saved after every row so extraction can resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionTable {
+    pub id: String,
+    pub name: String,
+    pub fields: Vec<TableField>,
+    pub documents: Vec<String>,
+    pub status: TableStatus,
+    #[serde(default)]
+    pub rows: Vec<TableRow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableSummary {
+    pub id: String,
+    pub name: String,
+    pub status: TableStatus,
+    pub field_count: usize,
+    pub document_count: usize,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&ExtractionTable> for TableSummary {
+    fn from(table: &ExtractionTable) -> Self {
+        Self {
+            id: table.id.clone(),
+            name: table.name.clone(),
+            status: table.status,
+            field_count: table.fields.len(),
+            document_count: table.documents.len(),
+            created_at: table.created_at,
+            updated_at: table.updated_at,
+        }
+    }
+}
+
+impl ExtractionTable {
+    pub fn field(&self, name: &str) -> Option<&TableField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn row_mut(&mut self, document: &str) -> Option<&mut TableRow> {
+        self.rows.iter_mut().find(|r| r.document == document)
+    }
+}
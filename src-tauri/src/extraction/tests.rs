@@ -0,0 +1,116 @@
+use super::*;
+use crate::rag::PageText;
+use crate::structured::SchemaValidator;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn fields() -> Vec<TableField> {
+    vec![
+        TableField {
+            name: "sample size".to_string(),
+            description: "Number of participants".to_string(),
+            field_type: FieldType::Integer,
+            options: Vec::new(),
+        },
+        TableField {
+            name: "design".to_string(),
+            description: String::new(),
+            field_type: FieldType::Text,
+            options: vec!["RCT".to_string(), "cohort".to_string()],
+        },
+    ]
+}
+
+#[test]
+fn row_schema_follows_the_fields() {
+    let validator = SchemaValidator::new(&row_schema(&fields())).unwrap();
+    let reply = json!({
+        "sample size": { "value": 120, "confidence": 0.9, "page": 3, "quote": "120 participants" },
+        "design": { "value": null, "confidence": 0.2 }
+    });
+    assert!(validator.errors(&reply).is_empty());
+
+    let wrong = json!({
+        "sample size": { "value": "about 120", "confidence": 0.9 },
+        "design": { "value": "case study", "confidence": 1.5 }
+    });
+    assert_eq!(validator.errors(&wrong).len(), 3);
+    assert!(validator.errors(&json!({ "design": { "value": "RCT", "confidence": 1 } }))[0].contains("sample size"));
+
+    let mut duplicate = fields();
+    duplicate[1].name = " Sample Size".to_string();
+    assert!(validate_fields(&duplicate).is_err());
+    assert!(validate_fields(&fields()).is_ok());
+}
+
+#[test]
+fn quotes_are_checked_against_the_pages() {
+    let pages: Vec<PageText> = ["We recruited\n120  participants.", "Results follow."]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| PageText {
+            page: Some(i as u32 + 1),
+            text: text.to_string(),
+        })
+        .collect();
+    let mut cell = Cell {
+        value: json!(120),
+        confidence: 0.9,
+        page: Some(2),
+        quote: Some("recruited 120 Participants".to_string()),
+        verified: false,
+        corrected: false,
+    };
+    cell.verify(&pages);
+    assert!(cell.verified);
+    assert_eq!(cell.page, Some(1));
+
+    let mut invented = Cell {
+        quote: Some("300 participants".to_string()),
+        verified: false,
+        ..cell
+    };
+    invented.verify(&pages);
+    assert!(!invented.verified);
+}
+
+#[test]
+fn csv_has_value_confidence_and_page_columns() {
+    let mut cells = BTreeMap::new();
+    cells.insert(
+        "sample size".to_string(),
+        Cell {
+            value: json!(120),
+            confidence: 0.9,
+            page: Some(3),
+            quote: None,
+            verified: false,
+            corrected: false,
+        },
+    );
+    cells.insert("design".to_string(), Cell::corrected(json!("RCT, blinded")));
+    let table = ExtractionTable {
+        id: "t1".to_string(),
+        name: "Trials".to_string(),
+        fields: fields(),
+        documents: vec!["/papers/a.pdf".to_string()],
+        status: TableStatus::Completed,
+        rows: vec![TableRow {
+            document: "/papers/a.pdf".to_string(),
+            title: Some("A \"large\" trial".to_string()),
+            cells,
+            error: None,
+        }],
+        error: None,
+        created_at: 0,
+        updated_at: 0,
+    };
+
+    assert_eq!(
+        table_csv(&table),
+        "document,title,sample size,sample size confidence,sample size page,design,design confidence,design page\n\
+         /papers/a.pdf,\"A \"\"large\"\" trial\",120,0.90,3,\"RCT, blinded\",1.00,\n"
+    );
+    let json: ExtractionTable = serde_json::from_str(&export_table(&table, TableFormat::Json).unwrap()).unwrap();
+    assert!(json.rows[0].cells["design"].corrected);
+}
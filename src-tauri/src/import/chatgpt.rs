@@ -0,0 +1,195 @@
+use super::{ParsedConversation, SkippedConversation};
+use crate::chat::{new_id, ChatMessage, ChatSession};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// Shape of ChatGPT's `conversations.json` data export
+#[derive(Deserialize)]
+struct Conversation {
+    title: Option<String>,
+    create_time: Option<f64>,
+    update_time: Option<f64>,
+    #[serde(default)]
+    mapping: HashMap<String, Node>,
+    current_node: Option<String>,
+    conversation_id: Option<String>,
+    id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    message: Option<Message>,
+    parent: Option<String>,
+    #[serde(default)]
+    children: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    author: Author,
+    create_time: Option<f64>,
+    content: Option<Content>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    role: String,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    content_type: String,
+    #[serde(default)]
+    parts: Vec<Value>,
+    text: Option<String>,
+}
+
+pub fn parse(content: &str) -> Result<Vec<ParsedConversation>, String> {
+    let raw: Vec<Value> = serde_json::from_str(content)
+        .map_err(|e| format!("Not a ChatGPT conversations.json export: {}", e))?;
+
+    Ok(raw
+        .into_iter()
+        .map(|value| {
+            let title = value.get("title").and_then(|t| t.as_str()).map(str::to_string);
+            match serde_json::from_value::<Conversation>(value) {
+                Ok(conversation) => convert(conversation),
+                Err(e) => Err(SkippedConversation {
+                    title,
+                    reason: format!("Unrecognized conversation layout: {}", e),
+                }),
+            }
+        })
+        .collect())
+}
+
+fn message_text(content: &Content) -> Option<String> {
+    let text = match content.content_type.as_str() {
+        "text" | "multimodal_text" => content
+            .parts
+            .iter()
+            .map(|part| match part {
+                Value::String(s) => s.clone(),
+                _ => "[attachment]".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "code" | "execution_output" => content.text.clone().unwrap_or_default(),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn to_message(id: &str, message: &Message, fallback_time: i64) -> Option<ChatMessage> {
+    let role = match message.author.role.as_str() {
+        "user" => "user",
+        "assistant" | "tool" => "assistant",
+        "system" => "system",
+        _ => return None,
+    };
+    let content = message_text(message.content.as_ref()?)?;
+    let model = message
+        .metadata
+        .get("model_slug")
+        .and_then(|m| m.as_str())
+        .map(str::to_string);
+
+    Some(ChatMessage {
+        id: id.to_string(),
+        parent_id: None,
+        role: role.to_string(),
+        content,
+        timestamp: message.create_time.map(|t| t as i64).unwrap_or(fallback_time),
+        provider: model.as_ref().map(|_| "openai".to_string()),
+        model,
+        ..Default::default()
+    })
+}
+
+// A node and its ancestors, nearest first; stops at a repeat so a parent cycle can't hang the import
+fn ancestors<'a>(mapping: &'a HashMap<String, Node>, start: Option<&'a String>) -> impl Iterator<Item = &'a String> {
+    let mut seen = HashSet::new();
+    std::iter::successors(start, move |id: &&String| mapping.get(*id).and_then(|n| n.parent.as_ref()))
+        .take_while(move |id| seen.insert(id.as_str()))
+}
+
+fn visible_ancestor(
+    mapping: &HashMap<String, Node>,
+    visible: &HashMap<&str, ChatMessage>,
+    id: Option<&String>,
+) -> Option<String> {
+    ancestors(mapping, id)
+        .find(|id| visible.contains_key(id.as_str()))
+        .cloned()
+}
+
+fn convert(conversation: Conversation) -> ParsedConversation {
+    let Conversation {
+        title,
+        create_time,
+        update_time,
+        mapping,
+        current_node,
+        conversation_id,
+        id,
+    } = conversation;
+    let created_at = create_time.map(|t| t as i64).unwrap_or(0);
+    let updated_at = update_time.map(|t| t as i64).unwrap_or(created_at);
+    let mapping = &mapping;
+
+    // Hidden and non-text nodes are dropped; their children hang off the nearest visible ancestor
+    let visible: HashMap<&str, ChatMessage> = mapping
+        .iter()
+        .filter_map(|(id, node)| {
+            let message = to_message(id, node.message.as_ref()?, created_at)?;
+            Some((id.as_str(), message))
+        })
+        .collect();
+
+    let current = current_node.or_else(|| {
+        // Older exports lack current_node; follow the last child from the root instead.
+        // With several roots the lowest id is taken, so the same export always imports the same branch
+        let mut id = mapping.iter().filter(|(_, n)| n.parent.is_none()).map(|(id, _)| id).min()?;
+        let mut seen = HashSet::from([id]);
+        while let Some(next) = mapping.get(id).and_then(|n| n.children.last()) {
+            if !seen.insert(next) {
+                break;
+            }
+            id = next;
+        }
+        Some(id.clone())
+    });
+
+    let mut active_path: Vec<String> = ancestors(mapping, current.as_ref())
+        .filter(|id| visible.contains_key(id.as_str()))
+        .cloned()
+        .collect();
+    active_path.reverse();
+
+    let with_parent = |id: &str| -> ChatMessage {
+        let mut message = visible[id].clone();
+        message.parent_id = visible_ancestor(mapping, &visible, mapping[id].parent.as_ref());
+        message
+    };
+    let messages: Vec<ChatMessage> = active_path.iter().map(|id| with_parent(id)).collect();
+    let mut alternate_messages: Vec<ChatMessage> = visible
+        .keys()
+        .filter(|id| !active_path.iter().any(|a| a == *id))
+        .map(|id| with_parent(id))
+        .collect();
+    alternate_messages.sort_by_key(|m| m.timestamp);
+
+    Ok(ChatSession {
+        id: new_id(),
+        title,
+        messages,
+        alternate_messages,
+        source_id: conversation_id.or(id).map(|id| format!("chatgpt:{}", id)),
+        created_at,
+        updated_at,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,42 @@
+use super::{import_into, parse_file, ImportFormat, ImportReport};
+use crate::chat::ChatHistory;
+use crate::AppState;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
+
+// Imports conversations from `path`, or from a file the user picks when no path is given
+#[tauri::command]
+pub async fn import_conversations(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    path: Option<String>,
+    format: Option<ImportFormat>,
+) -> Result<Option<ImportReport>, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let (tx, rx) = oneshot::channel();
+            app.dialog()
+                .file()
+                .add_filter("Conversations", &["json", "md", "markdown", "txt"])
+                .pick_file(move |path| {
+                    let _ = tx.send(path);
+                });
+            let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+
+    let parsed = parse_file(&path, format.unwrap_or(ImportFormat::Auto))?;
+
+    let mut holder = app_state.lock().await;
+    if holder.chat_history.is_none() {
+        holder.chat_history = Some(Arc::new(ChatHistory::new()));
+    }
+    let chat_history = holder.chat_history.as_ref().unwrap();
+    Ok(Some(import_into(chat_history, parsed).await))
+}
@@ -0,0 +1,255 @@
+use super::ParsedConversation;
+use crate::chat::{new_id, ChatMessage, ChatSession};
+use regex::Regex;
+
+const ROLES: &str = "user|assistant|system|you|me|human|chatgpt|claude|ai|model";
+
+struct RoleMatchers {
+    heading: Regex,
+    bold: Regex,
+    plain: Regex,
+    meta: Regex,
+    created: Regex,
+    updated: Regex,
+    untitled: Regex,
+}
+
+impl RoleMatchers {
+    fn new() -> Self {
+        Self {
+            heading: Regex::new(&format!(r"(?i)^#{{2,6}}\s+({})\s*:?\s*$", ROLES)).unwrap(),
+            bold: Regex::new(&format!(r"(?i)^\*\*({})\s*:?\*\*\s*:?\s*(.*)$", ROLES)).unwrap(),
+            plain: Regex::new(&format!(r"(?i)^({})\s*:\s*(.*)$", ROLES)).unwrap(),
+            meta: Regex::new(r"^_(.+)_$").unwrap(),
+            created: Regex::new(r"^- Created: (\S+)$").unwrap(),
+            updated: Regex::new(r"^- Updated: (\S+)$").unwrap(),
+            untitled: Regex::new(r"^Session [0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap(),
+        }
+    }
+
+    fn is_marked(&self, line: &str) -> bool {
+        self.heading.is_match(line) || self.bold.is_match(line)
+    }
+
+    // Returns the normalized role and any text following the marker on the same line. Bare
+    // `User:` lines only count in transcripts without heading or bold markers, where nothing else
+    // could mark a turn
+    fn role_line<'a>(&self, line: &'a str, marked: bool) -> Option<(&'static str, &'a str)> {
+        let (role, rest) = if let Some(c) = self.heading.captures(line) {
+            (c.get(1)?.as_str(), "")
+        } else if let Some(c) = self.bold.captures(line) {
+            (c.get(1)?.as_str(), c.get(2).map_or("", |m| m.as_str()))
+        } else if let Some(c) = self.plain.captures(line).filter(|_| !marked) {
+            (c.get(1)?.as_str(), c.get(2).map_or("", |m| m.as_str()))
+        } else {
+            return None;
+        };
+        let role = match role.to_lowercase().as_str() {
+            "user" | "you" | "me" | "human" => "user",
+            "system" => "system",
+            _ => "assistant",
+        };
+        Some((role, rest))
+    }
+}
+
+fn parse_time(text: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(text.trim())
+        .ok()
+        .map(|t| t.timestamp())
+}
+
+// Turns the folded reasoning block of a ccru Markdown export back into a <think> prefix
+fn unfold_reasoning(content: &str) -> String {
+    let trimmed = content.trim();
+    if let Some(rest) = trimmed.strip_prefix("<details>\n<summary>Reasoning</summary>") {
+        if let Some(end) = rest.find("</details>") {
+            return format!(
+                "<think>\n{}\n</think>\n\n{}",
+                rest[..end].trim(),
+                rest[end + "</details>".len()..].trim()
+            );
+        }
+    }
+    trimmed.to_string()
+}
+
+// A line of the transcript, and whether it is part of a fenced code block
+#[derive(Clone, Copy)]
+struct Line<'a> {
+    text: &'a str,
+    fenced: bool,
+}
+
+fn fence_marker(text: &str) -> Option<(char, usize)> {
+    let text = text.trim_start();
+    let marker = text.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = text.chars().take_while(|c| *c == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+fn lines_with_fences(content: &str) -> Vec<Line<'_>> {
+    let mut open: Option<(char, usize)> = None;
+    content
+        .lines()
+        .map(|text| {
+            match (open, fence_marker(text)) {
+                (None, Some(marker)) => open = Some(marker),
+                // A closing fence has nothing after its marker
+                (Some((c, n)), Some((marker, length)))
+                    if marker == c && length >= n && text.trim().chars().all(|ch| ch == c) =>
+                {
+                    open = None;
+                    return Line { text, fenced: true };
+                }
+                _ => {}
+            }
+            Line {
+                text,
+                fenced: open.is_some(),
+            }
+        })
+        .collect()
+}
+
+// Drops the `---` rule ccru writes between exported sessions, and the blank lines around it
+fn trim_separator(lines: &mut Vec<Line>) {
+    let trim_blank = |lines: &mut Vec<Line>| {
+        while lines.last().is_some_and(|l| !l.fenced && l.text.trim().is_empty()) {
+            lines.pop();
+        }
+    };
+    trim_blank(lines);
+    if lines.last().is_some_and(|l| !l.fenced && l.text.trim() == "---") {
+        lines.pop();
+        trim_blank(lines);
+    }
+}
+
+pub fn parse(content: &str, fallback_time: i64) -> Vec<ParsedConversation> {
+    let matchers = RoleMatchers::new();
+    let lines = lines_with_fences(content);
+    let marked = lines.iter().any(|l| !l.fenced && matchers.is_marked(l.text));
+
+    // A level-one heading starts a new conversation when a turn or the ccru header follows it;
+    // otherwise it belongs to the message it is in
+    let starts_conversation = |rest: &[Line]| {
+        rest.iter()
+            .find(|l| !l.text.trim().is_empty())
+            .is_some_and(|l| {
+                !l.fenced
+                    && (matchers.role_line(l.text, marked).is_some() || matchers.created.is_match(l.text))
+            })
+    };
+    let mut blocks: Vec<(Option<String>, Vec<Line>)> = vec![(None, Vec::new())];
+    for (i, line) in lines.iter().enumerate() {
+        match line.text.strip_prefix("# ") {
+            Some(title) if !line.fenced && starts_conversation(&lines[i + 1..]) => {
+                trim_separator(&mut blocks.last_mut().unwrap().1);
+                blocks.push((Some(title.trim().to_string()), Vec::new()));
+            }
+            _ => blocks.last_mut().unwrap().1.push(*line),
+        }
+    }
+
+    blocks
+        .into_iter()
+        .filter(|(title, lines)| title.is_some() || lines.iter().any(|l| !l.text.trim().is_empty()))
+        .map(|(title, lines)| Ok(parse_block(&matchers, marked, title, &lines, fallback_time)))
+        .collect()
+}
+
+fn parse_block(
+    matchers: &RoleMatchers,
+    marked: bool,
+    title: Option<String>,
+    lines: &[Line],
+    fallback_time: i64,
+) -> ChatSession {
+    let is_turn = |l: &Line| !l.fenced && matchers.role_line(l.text, marked).is_some();
+    // The ccru header sits between the title and the first turn
+    let header = &lines[..lines.iter().position(is_turn).unwrap_or(lines.len())];
+    let created_at = header
+        .iter()
+        .find_map(|l| matchers.created.captures(l.text))
+        .and_then(|c| parse_time(&c[1]))
+        .unwrap_or(fallback_time);
+    let updated_at = header
+        .iter()
+        .find_map(|l| matchers.updated.captures(l.text))
+        .and_then(|c| parse_time(&c[1]))
+        .unwrap_or(created_at);
+
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    let mut body: Vec<&str> = Vec::new();
+    let mut expecting_meta = false;
+
+    let flush = |messages: &mut Vec<ChatMessage>, body: &mut Vec<&str>| {
+        if let Some(message) = messages.last_mut() {
+            message.content = unfold_reasoning(&body.join("\n"));
+        }
+        body.clear();
+    };
+
+    for line in lines {
+        let Line { text: line, fenced } = *line;
+        if let Some((role, rest)) = matchers.role_line(line, marked).filter(|_| !fenced) {
+            flush(&mut messages, &mut body);
+            messages.push(ChatMessage {
+                id: new_id(),
+                parent_id: None,
+                role: role.to_string(),
+                content: String::new(),
+                timestamp: messages.last().map_or(created_at, |m| m.timestamp),
+                ..Default::default()
+            });
+            if !rest.is_empty() {
+                body.push(rest);
+            }
+            expecting_meta = true;
+            continue;
+        }
+
+        if expecting_meta && !line.trim().is_empty() {
+            expecting_meta = false;
+            if let Some(meta) = matchers.meta.captures(line.trim()).filter(|_| !fenced) {
+                let mut parts = meta[1].split(" · ");
+                if let Some(timestamp) = parts.next().and_then(parse_time) {
+                    let message = messages.last_mut().unwrap();
+                    message.timestamp = timestamp;
+                    if let Some(source) = parts.next() {
+                        match source.split_once(" / ") {
+                            Some((provider, model)) => {
+                                message.provider = Some(provider.to_string());
+                                message.model = Some(model.to_string());
+                            }
+                            None => message.model = Some(source.to_string()),
+                        }
+                    }
+                    continue;
+                }
+            }
+        }
+
+        if !messages.is_empty() {
+            body.push(line);
+        }
+    }
+    flush(&mut messages, &mut body);
+    messages.retain(|m| !m.content.is_empty());
+    let ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+    for (i, message) in messages.iter_mut().enumerate() {
+        message.parent_id = i.checked_sub(1).map(|p| ids[p].clone());
+    }
+
+    // Titles of untitled ccru exports are just the old session id
+    let title = title.filter(|t| !matchers.untitled.is_match(t));
+    ChatSession {
+        id: new_id(),
+        title,
+        messages,
+        created_at,
+        updated_at,
+        ..Default::default()
+    }
+}
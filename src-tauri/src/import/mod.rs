@@ -0,0 +1,168 @@
+mod chatgpt;
+mod markdown;
+
+use crate::chat::{ChatExport, ChatHistory, ChatSession, EXPORT_FORMAT_NAME};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportFormat {
+    Auto,
+    Chatgpt,
+    Markdown,
+    Ccru,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedSession {
+    pub session_id: String,
+    pub title: Option<String>,
+    pub messages: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedConversation {
+    pub title: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ImportReport {
+    pub imported: Vec<ImportedSession>,
+    pub skipped: Vec<SkippedConversation>,
+}
+
+// A parsed conversation, or the reason it could not be turned into a session
+pub type ParsedConversation = Result<ChatSession, SkippedConversation>;
+
+pub fn parse_file(path: &Path, format: ImportFormat) -> Result<Vec<ParsedConversation>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    match resolve_format(path, &content, format) {
+        ImportFormat::Chatgpt => chatgpt::parse(&content),
+        ImportFormat::Markdown => {
+            let modified = std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_else(|| chrono::Utc::now().timestamp());
+            Ok(markdown::parse(&content, modified))
+        }
+        ImportFormat::Ccru => parse_ccru(&content),
+        ImportFormat::Auto => unreachable!("resolve_format never returns Auto"),
+    }
+}
+
+fn resolve_format(path: &Path, content: &str, format: ImportFormat) -> ImportFormat {
+    if !matches!(format, ImportFormat::Auto) {
+        return format;
+    }
+    let is_json = path.extension().is_some_and(|ext| ext == "json")
+        || content.trim_start().starts_with(['[', '{']);
+    if !is_json {
+        return ImportFormat::Markdown;
+    }
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(value) if value.get("format").and_then(|f| f.as_str()) == Some(EXPORT_FORMAT_NAME) => {
+            ImportFormat::Ccru
+        }
+        _ => ImportFormat::Chatgpt,
+    }
+}
+
+fn parse_ccru(content: &str) -> Result<Vec<ParsedConversation>, String> {
+    let export: ChatExport =
+        serde_json::from_str(content).map_err(|e| format!("Invalid ccru export: {}", e))?;
+    Ok(export
+        .sessions
+        .into_iter()
+        .map(|mut session| {
+            if session.source_id.is_none() {
+                session.source_id = Some(format!("ccru:{}", session.id));
+            }
+            Ok(session)
+        })
+        .collect())
+}
+
+fn fingerprint(session: &ChatSession) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for message in &session.messages {
+        message.role.hash(&mut hasher);
+        message.content.trim().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// Adds parsed conversations to the history, skipping ones that are already there
+pub async fn import_into(history: &ChatHistory, parsed: Vec<ParsedConversation>) -> ImportReport {
+    let existing = history.list_sessions().await;
+    let mut known_ids: Vec<String> = existing.iter().map(|s| s.id.clone()).collect();
+    let mut known_sources: Vec<String> = existing.iter().filter_map(|s| s.source_id.clone()).collect();
+    let mut known_fingerprints: Vec<(u64, String)> = existing
+        .iter()
+        .filter(|s| !s.messages.is_empty())
+        .map(|s| (fingerprint(s), s.id.clone()))
+        .collect();
+
+    let mut report = ImportReport::default();
+    for conversation in parsed {
+        let mut session = match conversation {
+            Ok(session) => session,
+            Err(skipped) => {
+                report.skipped.push(skipped);
+                continue;
+            }
+        };
+
+        if session.messages.is_empty() {
+            report.skipped.push(SkippedConversation {
+                title: session.title,
+                reason: "No messages".to_string(),
+            });
+            continue;
+        }
+        if let Some(source) = session.source_id.as_ref().filter(|s| known_sources.contains(s)) {
+            report.skipped.push(SkippedConversation {
+                reason: format!("Already imported from {}", source),
+                title: session.title,
+            });
+            continue;
+        }
+        let print = fingerprint(&session);
+        if let Some((_, id)) = known_fingerprints.iter().find(|(p, _)| *p == print) {
+            report.skipped.push(SkippedConversation {
+                reason: format!("Same messages as session {}", id),
+                title: session.title,
+            });
+            continue;
+        }
+
+        if known_ids.contains(&session.id) {
+            session.id = crate::chat::new_id();
+        }
+        known_ids.push(session.id.clone());
+        known_sources.extend(session.source_id.clone());
+        known_fingerprints.push((print, session.id.clone()));
+
+        report.imported.push(ImportedSession {
+            session_id: session.id.clone(),
+            title: session.title.clone(),
+            messages: session.messages.len(),
+        });
+        history.insert_session(session).await;
+    }
+
+    report
+}
+
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,158 @@
+use super::*;
+use crate::chat::{new_id, render_sessions, ChatMessage, ExportFormat};
+
+fn message(role: &str, content: &str, timestamp: i64) -> ChatMessage {
+    ChatMessage {
+        id: new_id(),
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp,
+        ..Default::default()
+    }
+}
+
+fn session(title: Option<&str>, messages: Vec<ChatMessage>) -> ChatSession {
+    ChatSession {
+        id: new_id(),
+        title: title.map(str::to_string),
+        messages,
+        created_at: 1_700_000_000,
+        updated_at: 1_700_000_100,
+        ..Default::default()
+    }
+}
+
+fn contents(session: &ChatSession) -> Vec<(&str, &str)> {
+    session.messages.iter().map(|m| (m.role.as_str(), m.content.as_str())).collect()
+}
+
+#[test]
+fn markdown_keeps_code_headings_and_rules_inside_messages() {
+    let code = "Like this:\n\n```python\n# Load the data\n\n## User\nprint(1)\n```\n\n# Not a new chat\n\nUser: quoted, not a turn\n\n---";
+    let sessions = vec![
+        session(
+            Some("Session notes on X"),
+            vec![
+                message("user", "How do I comment in Python?", 1_700_000_010),
+                message("assistant", code, 1_700_000_020),
+            ],
+        ),
+        session(None, vec![message("user", "Second chat", 1_700_000_030)]),
+    ];
+    let exported = render_sessions(&sessions, ExportFormat::Markdown);
+
+    let parsed: Vec<ChatSession> = markdown::parse(&exported, 0).into_iter().map(Result::unwrap).collect();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].title.as_deref(), Some("Session notes on X"));
+    assert_eq!(contents(&parsed[0]), [("user", "How do I comment in Python?"), ("assistant", code)]);
+    assert_eq!(parsed[0].messages[1].timestamp, 1_700_000_020);
+    assert_eq!(parsed[0].created_at, 1_700_000_000);
+    assert_eq!(parsed[1].title, None);
+    assert_eq!(contents(&parsed[1]), [("user", "Second chat")]);
+
+    // Bare role lines mark turns only in transcripts without heading or bold markers
+    let plain = markdown::parse("User: hi\nAssistant: hello\n\n# heading in a reply\n\nmore text\nuser: again", 5);
+    let plain = plain[0].as_ref().unwrap();
+    assert_eq!(
+        contents(plain),
+        [("user", "hi"), ("assistant", "hello\n\n# heading in a reply\n\nmore text"), ("user", "again")]
+    );
+}
+
+fn node(id: &str, parent: Option<&str>, children: &[&str], role: &str, text: &str, time: f64) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "parent": parent,
+        "children": children,
+        "message": (!role.is_empty()).then(|| serde_json::json!({
+            "author": { "role": role },
+            "create_time": time,
+            "content": { "content_type": "text", "parts": [text] },
+        })),
+    })
+}
+
+#[test]
+fn chatgpt_branches_become_alternate_messages() {
+    let export = serde_json::json!([{
+        "title": "Branches",
+        "create_time": 1_700_000_000.0,
+        "conversation_id": "c1",
+        "current_node": "u2",
+        "mapping": {
+            "root": node("root", None, &["u1"], "", "", 0.0),
+            "u1": node("u1", Some("root"), &["a1", "a2"], "user", "Question", 1.0),
+            "a1": node("a1", Some("u1"), &[], "assistant", "First answer", 2.0),
+            "a2": node("a2", Some("u1"), &["u2"], "assistant", "Regenerated answer", 3.0),
+            "u2": node("u2", Some("a2"), &[], "user", "Follow-up", 4.0),
+        },
+    }]);
+
+    let parsed = chatgpt::parse(&export.to_string()).unwrap();
+    let session = parsed[0].as_ref().unwrap();
+    assert_eq!(session.source_id.as_deref(), Some("chatgpt:c1"));
+    assert_eq!(
+        contents(session),
+        [("user", "Question"), ("assistant", "Regenerated answer"), ("user", "Follow-up")]
+    );
+    assert_eq!(session.messages[1].parent_id.as_deref(), Some("u1"));
+    assert_eq!(session.alternate_messages.len(), 1);
+    assert_eq!(session.alternate_messages[0].content, "First answer");
+    assert_eq!(session.alternate_messages[0].parent_id.as_deref(), Some("u1"));
+}
+
+#[test]
+fn chatgpt_cycles_and_extra_roots_are_handled() {
+    // A parent cycle must not hang the import
+    let export = serde_json::json!([{
+        "title": "Cycle",
+        "current_node": "a1",
+        "mapping": {
+            "u1": node("u1", Some("a1"), &["a1"], "user", "Question", 1.0),
+            "a1": node("a1", Some("u1"), &["u1"], "assistant", "Answer", 2.0),
+        },
+    }]);
+    let parsed = chatgpt::parse(&export.to_string()).unwrap();
+    assert_eq!(contents(parsed[0].as_ref().unwrap()), [("user", "Question"), ("assistant", "Answer")]);
+
+    // Without current_node, the branch below the lowest root id is followed, every time
+    let export = serde_json::json!([{
+        "title": "Roots",
+        "mapping": {
+            "b-root": node("b-root", None, &["b1"], "", "", 0.0),
+            "b1": node("b1", Some("b-root"), &[], "user", "Second", 2.0),
+            "a-root": node("a-root", None, &["a1"], "", "", 0.0),
+            "a1": node("a1", Some("a-root"), &["a2"], "user", "First", 1.0),
+            "a2": node("a2", Some("a1"), &["a1"], "assistant", "Reply", 1.5),
+        },
+    }]);
+    let parsed = chatgpt::parse(&export.to_string()).unwrap();
+    let session = parsed[0].as_ref().unwrap();
+    assert_eq!(contents(session), [("user", "First"), ("assistant", "Reply")]);
+    assert_eq!(session.alternate_messages[0].content, "Second");
+}
+
+#[tokio::test]
+async fn imports_skip_known_sources_and_same_messages() {
+    let history = ChatHistory::new();
+    let sourced = || {
+        let mut session = session(Some("From ChatGPT"), vec![message("user", "Hello", 1)]);
+        session.source_id = Some("chatgpt:c1".to_string());
+        Ok(session)
+    };
+    let report = import_into(&history, vec![sourced()]).await;
+    assert_eq!(report.imported.len(), 1);
+    let report = import_into(&history, vec![sourced()]).await;
+    assert!(report.imported.is_empty());
+    assert!(report.skipped[0].reason.starts_with("Already imported"));
+
+    let transcript = "## User\n\nWhat is RAG?\n\n## Assistant\n\nRetrieval-augmented generation.";
+    let report = import_into(&history, markdown::parse(transcript, 0)).await;
+    assert_eq!(report.imported.len(), 1);
+    // Same turns with different spacing and a title still count as the same conversation
+    let retitled = format!("# Copy\n\n{}\n", transcript.replace("\n\n", "\n\n\n"));
+    let report = import_into(&history, markdown::parse(&retitled, 0)).await;
+    assert!(report.imported.is_empty());
+    assert!(report.skipped[0].reason.starts_with("Same messages"));
+    assert_eq!(history.list_sessions().await.len(), 2);
+}
@@ -0,0 +1,89 @@
+use crate::chat::check_id;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+// A long-running job saved as one JSON file, so it can be listed, inspected and resumed
+pub trait JobRecord: Serialize + DeserializeOwned {
+    // What the job is called in messages, e.g. "Review"
+    const NOUN: &'static str;
+    type Summary: for<'a> From<&'a Self>;
+
+    fn id(&self) -> &str;
+    fn created_at(&self) -> i64;
+    fn set_updated_at(&mut self, timestamp: i64);
+}
+
+// One JSON file per job in a directory, plus the jobs a task is currently working on
+pub struct JobStore<T> {
+    dir: PathBuf,
+    running: Mutex<HashSet<String>>,
+    _records: PhantomData<fn() -> T>,
+}
+
+impl<T: JobRecord> JobStore<T> {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            running: Mutex::new(HashSet::new()),
+            _records: PhantomData,
+        }
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, String> {
+        check_id(id)?;
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    pub fn save(&self, record: &mut T) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        record.set_updated_at(chrono::Utc::now().timestamp());
+        let content = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+        fs::write(self.path(record.id())?, content)
+            .map_err(|e| format!("Failed to save {}: {}", T::NOUN.to_lowercase(), e))
+    }
+
+    pub fn load(&self, id: &str) -> Result<T, String> {
+        let content = fs::read_to_string(self.path(id)?).map_err(|_| format!("{} not found", T::NOUN))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to read {} {}: {}", T::NOUN.to_lowercase(), id, e))
+    }
+
+    // Every readable job, newest first
+    pub fn records(&self) -> Vec<T> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut records: Vec<T> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| fs::read_to_string(e.path()).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.created_at()));
+        records
+    }
+
+    pub fn list(&self) -> Vec<T::Summary> {
+        self.records().iter().map(T::Summary::from).collect()
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        fs::remove_file(self.path(id)?).map_err(|_| format!("{} not found", T::NOUN))
+    }
+
+    pub async fn is_running(&self, id: &str) -> bool {
+        self.running.lock().await.contains(id)
+    }
+
+    // False if a task is already working on the job
+    pub async fn claim(&self, id: &str) -> bool {
+        self.running.lock().await.insert(id.to_string())
+    }
+
+    pub async fn release(&self, id: &str) {
+        self.running.lock().await.remove(id);
+    }
+}
@@ -1,11 +1,35 @@
 mod chat;
 mod config;
+mod import;
+mod jobs;
 mod llm_bridge;
 mod agent;
+mod bibliography;
+mod budget;
+mod extraction;
+mod paper;
+mod rag;
+mod review;
+mod sandbox;
+mod search;
+mod structured;
+mod summarize;
+mod vector;
+mod workspace;
 
-use crate::chat::ChatHistory;
+use crate::agent::{AgentRunStore, ApprovalGate, DataTables, FileJournal, ToolRegistry};
+use crate::bibliography::Library;
+use crate::budget::{BudgetTracker, BudgetedEmbedder};
+use crate::chat::{ChatHistory, SessionTitler};
 use crate::config::Config;
-use crate::llm_bridge::LLMBridge;
+use crate::extraction::TableStore;
+use crate::llm_bridge::{LLMBridge, MockBridge};
+use crate::rag::{DocumentIndex, FolderWatcher};
+use crate::review::ReviewStore;
+use crate::sandbox::CodeRunner;
+use crate::search::SemanticMessageIndex;
+use crate::summarize::Summarizer;
+use crate::workspace::WorkspaceStore;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tauri::Manager;
@@ -14,12 +38,31 @@ use tauri::Manager;
 pub struct AppState {
     config: Option<Config>,
     llm_bridge: Option<Arc<dyn LLMBridge>>,
+    mock_bridge: Option<Arc<MockBridge>>,
+    embedder: Option<Arc<BudgetedEmbedder>>,
     chat_history: Option<Arc<ChatHistory>>,
+    session_titler: Option<Arc<SessionTitler>>,
+    budget: Option<Arc<BudgetTracker>>,
+    workspaces: Option<Arc<WorkspaceStore>>,
+    documents: Option<Arc<DocumentIndex>>,
+    folder_watcher: Option<FolderWatcher>,
+    message_vectors: Option<Arc<SemanticMessageIndex>>,
+    library: Option<Arc<Library>>,
+    reviews: Option<Arc<ReviewStore>>,
+    extractions: Option<Arc<TableStore>>,
+    code_runner: Option<Arc<CodeRunner>>,
+    approvals: Option<Arc<ApprovalGate>>,
+    agent_runs: Option<Arc<AgentRunStore>>,
+    agent_tools: Option<Arc<ToolRegistry>>,
+    agent_files: Option<Arc<FileJournal>>,
+    agent_tables: Option<Arc<DataTables>>,
+    summarizer: Option<Arc<Summarizer>>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             let app_state = AppState::default();
             app.manage(Mutex::new(app_state));
@@ -36,12 +79,75 @@ pub fn run() {
             config::load_config,
             config::save_config,
             llm_bridge::get_completion,
+            llm_bridge::stream_completion,
+            llm_bridge::get_mock_requests,
+            llm_bridge::embed_texts,
             chat::create_session,
             chat::delete_session,
             chat::get_session,
             chat::list_sessions,
+            chat::update_session,
+            chat::open_citation,
             chat::add_message,
             chat::ask_question,
+            chat::export_sessions,
+            import::import_conversations,
+            budget::get_budget_status,
+            budget::override_budget,
+            budget::clear_budget_override,
+            search::search_messages,
+            search::semantic_search_messages,
+            search::index_message_vectors,
+            rag::ingest_documents,
+            rag::list_indexed_documents,
+            rag::remove_indexed_document,
+            rag::search_documents,
+            rag::reindex_documents,
+            rag::rescan_watched_folders,
+            paper::create_paper_session,
+            paper::get_paper_structure,
+            paper::ask_paper_question,
+            paper::summarize_paper,
+            summarize::summarize_text,
+            structured::complete_structured,
+            sandbox::run_code,
+            sandbox::run_code_block,
+            agent::start_agent_run,
+            agent::resolve_agent_action,
+            agent::list_pending_agent_actions,
+            agent::resume_agent_run,
+            agent::list_agent_runs,
+            agent::get_agent_run,
+            agent::list_agent_tools,
+            agent::register_agent_tool,
+            agent::remove_agent_tool,
+            agent::list_agent_file_writes,
+            agent::get_agent_file_changes,
+            agent::undo_agent_file_writes,
+            review::start_literature_review,
+            review::resume_literature_review,
+            review::list_literature_reviews,
+            review::get_literature_review,
+            review::export_literature_review,
+            extraction::start_extraction,
+            extraction::resume_extraction,
+            extraction::list_extraction_tables,
+            extraction::get_extraction_table,
+            extraction::delete_extraction_table,
+            extraction::update_extraction_cell,
+            extraction::export_extraction_table,
+            bibliography::import_bibtex,
+            bibliography::search_library,
+            bibliography::get_library_entry,
+            bibliography::export_session_bibliography,
+            workspace::create_workspace,
+            workspace::update_workspace,
+            workspace::delete_workspace,
+            workspace::list_workspaces,
+            workspace::get_workspace,
+            workspace::attach_workspace_document,
+            workspace::remove_workspace_document,
+            workspace::export_workspace,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
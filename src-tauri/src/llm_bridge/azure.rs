@@ -1,5 +1,5 @@
 use super::LLMServiceError;
-use super::{LLMBridge, LLMRequest, LLMResponse, Prompt};
+use super::{LLMBridge, LLMRequest, LLMResponse, Prompt, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, EMBED_BATCH_SIZE};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 pub struct AzureBridge {
     base_url: String,
     model: String,
+    embedding_model: Option<String>,
     api_key: String,
     client: Client,
 }
@@ -23,6 +24,23 @@ struct Parameters {
     temperature: f32,
 }
 
+#[derive(Serialize)]
+struct AzureEmbeddingRequest<'a> {
+    input: &'a [String],
+    model: &'a str,
+}
+
+#[derive(Deserialize)]
+struct AzureEmbeddingResponse {
+    data: Vec<AzureEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct AzureEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
 #[derive(Deserialize)]
 struct AzureResponse {
     outputs: Vec<Output>,
@@ -38,11 +56,17 @@ impl AzureBridge {
         Self {
             base_url: base_url.to_string(),
             model: model.to_string(),
+            embedding_model: None,
             api_key: api_key.to_string(),
             client: Client::new(),
         }
     }
 
+    pub fn with_embedding_model(mut self, model: Option<&str>) -> Self {
+        self.embedding_model = model.map(str::to_string);
+        self
+    }
+
     fn format_prompts(prompts: Vec<Prompt>) -> String {
         prompts
             .into_iter()
@@ -66,8 +90,8 @@ impl LLMBridge for AzureBridge {
         let azure_req = AzureRequest {
             input: Self::format_prompts(request.messages),
             parameters: Parameters {
-                max_new_tokens: 800,
-                temperature: 0.7,
+                max_new_tokens: request.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+                temperature: request.params.temperature.unwrap_or(DEFAULT_TEMPERATURE),
             },
         };
 
@@ -108,7 +132,62 @@ impl LLMBridge for AzureBridge {
             .text
             .clone();
 
-        Ok(LLMResponse { content })
+        // The text completions endpoint does not report token counts.
+        Ok(LLMResponse {
+            content,
+            usage: None,
+            tool_calls: Vec::new(),
+        })
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model.as_deref()
+    }
+
+    // Uses the model inference embeddings route of the same Azure endpoint
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        let model = self.embedding_model.as_deref().ok_or_else(|| LLMServiceError {
+            error: "No embedding model configured for Azure".to_string(),
+        })?;
+
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let response = self
+                .client
+                .post(format!(
+                    "{}/embeddings?api-version=2024-05-01-preview",
+                    self.base_url
+                ))
+                .header("api-key", &self.api_key)
+                .json(&AzureEmbeddingRequest { input: batch, model })
+                .send()
+                .await
+                .map_err(|e| LLMServiceError {
+                    error: format!("Azure embedding request failed: {}", e),
+                })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.map_err(|e| LLMServiceError {
+                    error: format!("Failed to read error response: {}", e),
+                })?;
+                return Err(LLMServiceError {
+                    error: format!("Azure API error: {} - {}", status, error_text),
+                });
+            }
+
+            let mut body: AzureEmbeddingResponse = response.json().await.map_err(|e| LLMServiceError {
+                error: format!("Failed to parse Azure embeddings: {}", e),
+            })?;
+            if body.data.len() != batch.len() {
+                return Err(LLMServiceError {
+                    error: format!("Azure returned {} embeddings for {} inputs", body.data.len(), batch.len()),
+                });
+            }
+            body.data.sort_by_key(|e| e.index);
+            embeddings.extend(body.data.into_iter().map(|e| e.embedding));
+        }
+        Ok(embeddings)
     }
 
     async fn health_check(&self) -> Result<bool, LLMServiceError> {
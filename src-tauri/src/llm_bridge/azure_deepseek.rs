@@ -1,5 +1,5 @@
 use super::LLMServiceError;
-use super::{LLMBridge, LLMRequest, LLMResponse, Prompt};
+use super::{LLMBridge, LLMRequest, LLMResponse, Prompt, Usage, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -17,6 +17,9 @@ struct DeepSeekRequest {
     temperature: f32,
     max_tokens: u32,
     stream: bool,
+    // Only {"type": "json_object"}; DeepSeek has no schema-constrained mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,6 +31,13 @@ struct ChatMessage {
 #[derive(Deserialize)]
 struct DeepSeekResponse {
     choices: Vec<Choice>,
+    usage: Option<DeepSeekUsage>,
+}
+
+#[derive(Deserialize)]
+struct DeepSeekUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -70,9 +80,12 @@ impl LLMBridge for AzureDeepSeekBridge {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse, LLMServiceError> {
         let deepseek_req = DeepSeekRequest {
             messages: Self::convert_prompts(request.messages),
-            temperature: 0.7,
-            max_tokens: 800,
+            temperature: request.params.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: request.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
             stream: false,
+            response_format: request
+                .format
+                .map(|_| serde_json::json!({ "type": "json_object" })),
         };
 
         let response = self
@@ -114,7 +127,16 @@ impl LLMBridge for AzureDeepSeekBridge {
             .content
             .clone();
 
-        Ok(LLMResponse { content })
+        let usage = deepseek_resp.usage.map(|u| Usage {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+        });
+
+        Ok(LLMResponse {
+            content,
+            usage,
+            tool_calls: Vec::new(),
+        })
     }
 
     async fn health_check(&self) -> Result<bool, LLMServiceError> {
@@ -126,6 +148,7 @@ impl LLMBridge for AzureDeepSeekBridge {
             temperature: 0.7,
             max_tokens: 1,
             stream: false,
+            response_format: None,
         };
 
         let response = self
@@ -1,17 +1,110 @@
+use crate::budget::{complete_with_budget, stream_with_budget, UsageContext};
 use crate::llm_bridge::{LLMRequest, LLMServiceError, Prompt};
 use crate::AppState;
+use serde::Serialize;
+use tauri::Emitter;
 use tokio::sync::Mutex;
 
+pub const COMPLETION_CHUNK_EVENT: &str = "completion-chunk";
+
+#[derive(Serialize, Clone)]
+pub struct CompletionChunk<'a> {
+    stream_id: &'a str,
+    chunk: &'a str,
+}
+
 #[tauri::command]
 pub async fn get_completion(
+    app: tauri::AppHandle,
     app_state: tauri::State<'_, Mutex<AppState>>,
     prompts: Vec<Prompt>,
 ) -> Result<String, LLMServiceError> {
     let holder = app_state.lock().await;
-    let llm_bridge = holder.llm_bridge.as_ref().unwrap();
-    let r = llm_bridge.complete(LLMRequest { messages: prompts }).await;
+    let llm_bridge = holder.llm_bridge.as_ref().ok_or_else(|| LLMServiceError {
+        error: "No LLM provider is configured".to_string(),
+    })?;
+    let ctx = UsageContext {
+        provider: holder.config.as_ref().unwrap().provider().to_string(),
+        model: llm_bridge.model().to_string(),
+        ..Default::default()
+    };
+    let r = complete_with_budget(
+        &app,
+        llm_bridge.as_ref(),
+        holder.budget.as_deref(),
+        ctx,
+        LLMRequest {
+            messages: prompts,
+            ..Default::default()
+        },
+    )
+    .await;
     match r {
         Ok(response) => Ok(response.content),
         Err(e) => Err(e),
     }
 }
+
+#[tauri::command]
+pub async fn stream_completion(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    stream_id: String,
+    prompts: Vec<Prompt>,
+) -> Result<String, LLMServiceError> {
+    let holder = app_state.lock().await;
+    let llm_bridge = holder.llm_bridge.as_ref().ok_or_else(|| LLMServiceError {
+        error: "No LLM provider is configured".to_string(),
+    })?;
+    let ctx = UsageContext {
+        provider: holder.config.as_ref().unwrap().provider().to_string(),
+        model: llm_bridge.model().to_string(),
+        ..Default::default()
+    };
+    let emitter = app.clone();
+    let on_chunk = move |chunk: &str| {
+        let payload = CompletionChunk {
+            stream_id: &stream_id,
+            chunk,
+        };
+        if let Err(e) = emitter.emit(COMPLETION_CHUNK_EVENT, payload) {
+            log::warn!("Failed to emit completion chunk: {}", e);
+        }
+    };
+    let response = stream_with_budget(
+        &app,
+        llm_bridge.as_ref(),
+        holder.budget.as_deref(),
+        ctx,
+        LLMRequest {
+            messages: prompts,
+            ..Default::default()
+        },
+        &on_chunk,
+    )
+    .await?;
+    Ok(response.content)
+}
+
+#[tauri::command]
+pub async fn get_mock_requests(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<LLMRequest>, String> {
+    let holder = app_state.lock().await;
+    let mock_bridge = holder
+        .mock_bridge
+        .as_ref()
+        .ok_or_else(|| "The mock provider is not active".to_string())?;
+    Ok(mock_bridge.recorded_requests())
+}
+
+#[tauri::command]
+pub async fn embed_texts(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+    let embedder = app_state.lock().await.embedder.clone().ok_or_else(|| LLMServiceError {
+        error: "No embedding provider is configured".to_string(),
+    })?;
+    embedder.embed(&texts).await
+}
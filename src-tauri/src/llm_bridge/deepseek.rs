@@ -1,10 +1,14 @@
 use super::LLMServiceError;
-use super::{LLMBridge, LLMRequest, LLMResponse, Prompt};
+use super::{chat_tools, ReplyMessage};
+use super::{LLMBridge, LLMRequest, LLMResponse, Prompt, Usage, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
+
 pub struct DeepSeekBridge {
+    base_url: String,
     api_key: String,
     model: String,
     client: Client,
@@ -17,6 +21,11 @@ struct DeepSeekRequest {
     temperature: f32,
     max_tokens: u32,
     stream: bool,
+    // Only {"type": "json_object"}; DeepSeek has no schema-constrained mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,16 +37,28 @@ struct ChatMessage {
 #[derive(Deserialize)]
 struct DeepSeekResponse {
     choices: Vec<Choice>,
+    usage: Option<DeepSeekUsage>,
+}
+
+#[derive(Deserialize)]
+struct DeepSeekUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
 struct Choice {
-    message: ChatMessage,
+    message: ReplyMessage,
 }
 
 impl DeepSeekBridge {
     pub fn new(api_key: &str, model: &str) -> Self {
+        Self::with_base_url(DEEPSEEK_BASE_URL, api_key, model)
+    }
+
+    pub fn with_base_url(base_url: &str, api_key: &str, model: &str) -> Self {
         Self {
+            base_url: base_url.to_string(),
             api_key: api_key.to_string(),
             model: model.to_string(),
             client: Client::new(),
@@ -69,14 +90,18 @@ impl LLMBridge for DeepSeekBridge {
         let deepseek_req = DeepSeekRequest {
             model: self.model.clone(),
             messages: Self::convert_prompts(request.messages),
-            temperature: 0.7,
-            max_tokens: 800,
+            temperature: request.params.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: request.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
             stream: false,
+            tools: chat_tools(&request.tools),
+            response_format: request
+                .format
+                .map(|_| serde_json::json!({ "type": "json_object" })),
         };
 
         let response = self
             .client
-            .post("https://api.deepseek.com/v1/chat/completions")
+            .post(format!("{}/v1/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&deepseek_req)
             .send()
@@ -100,17 +125,25 @@ impl LLMBridge for DeepSeekBridge {
                 error: format!("Failed to parse DeepSeek response: {}", e),
             })?;
 
-        let content = deepseek_resp
+        let message = deepseek_resp
             .choices
-            .first()
+            .into_iter()
+            .next()
             .ok_or_else(|| LLMServiceError {
                 error: "No completion choices returned".to_string(),
             })?
-            .message
-            .content
-            .clone();
-
-        Ok(LLMResponse { content })
+            .message;
+
+        let usage = deepseek_resp.usage.map(|u| Usage {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+        });
+
+        Ok(LLMResponse {
+            content: message.content.unwrap_or_default(),
+            usage,
+            tool_calls: message.tool_calls.into_iter().map(Into::into).collect(),
+        })
     }
 
     async fn health_check(&self) -> Result<bool, LLMServiceError> {
@@ -123,11 +156,13 @@ impl LLMBridge for DeepSeekBridge {
             temperature: 0.7,
             max_tokens: 1,
             stream: false,
+            response_format: None,
+            tools: None,
         };
 
         let response = self
             .client
-            .post("https://api.deepseek.com/v1/chat/completions")
+            .post(format!("{}/v1/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&deepseek_req)
             .send()
@@ -0,0 +1,55 @@
+use super::{ToolCall, ToolDefinition};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+// Function calling in the chat-completions format shared by OpenAI-compatible servers and DeepSeek
+
+pub(crate) fn chat_tools(tools: &[ToolDefinition]) -> Option<Vec<Value>> {
+    (!tools.is_empty()).then(|| {
+        tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                    },
+                })
+            })
+            .collect()
+    })
+}
+
+// A reply message, which has no content when the model only calls tools
+#[derive(Deserialize)]
+pub(crate) struct ReplyMessage {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ChatToolCall>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ChatToolCall {
+    function: ChatFunctionCall,
+}
+
+#[derive(Deserialize)]
+struct ChatFunctionCall {
+    name: String,
+    // JSON encoded as a string
+    arguments: String,
+}
+
+impl From<ChatToolCall> for ToolCall {
+    fn from(call: ChatToolCall) -> Self {
+        let arguments = serde_json::from_str(&call.function.arguments)
+            .unwrap_or(Value::String(call.function.arguments));
+        ToolCall {
+            name: call.function.name,
+            arguments,
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::LLMServiceError;
+use super::{ChunkHandler, LLMBridge, LLMRequest, LLMResponse, ToolCall, Usage};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const MOCK_EMBEDDING_MODEL: &str = "mock-embedding";
+const DEFAULT_EMBEDDING_DIMENSIONS: usize = 64;
+
+// Answers from a script of canned responses instead of a real provider
+pub struct MockBridge {
+    model: String,
+    embedding_dimensions: usize,
+    latency_ms: u64,
+    chunk_delay_ms: u64,
+    rules: Vec<MockRule>,
+    fallback: Option<MockReply>,
+    record_to: Option<PathBuf>,
+    requests: Mutex<Vec<LLMRequest>>,
+}
+
+#[derive(Deserialize)]
+pub struct MockScript {
+    model: Option<String>,
+    #[serde(rename = "latencyMs", default)]
+    latency_ms: u64,
+    #[serde(rename = "chunkDelayMs", default)]
+    chunk_delay_ms: u64,
+    #[serde(rename = "embeddingDimensions")]
+    embedding_dimensions: Option<usize>,
+    #[serde(rename = "recordTo")]
+    record_to: Option<PathBuf>,
+    #[serde(default)]
+    responses: Vec<MockRuleSpec>,
+    fallback: Option<MockReply>,
+}
+
+#[derive(Deserialize)]
+struct MockRuleSpec {
+    #[serde(rename = "match")]
+    pattern: Option<String>,
+    turn: Option<usize>,
+    #[serde(flatten)]
+    reply: MockReply,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MockReply {
+    #[serde(default)]
+    content: String,
+    think: Option<String>,
+    #[serde(rename = "latencyMs")]
+    latency_ms: Option<u64>,
+    chunks: Option<Vec<String>>,
+    #[serde(rename = "toolCalls", default)]
+    tool_calls: Vec<ToolCall>,
+    error: Option<String>,
+}
+
+struct MockRule {
+    pattern: Option<Regex>,
+    turn: Option<usize>,
+    reply: MockReply,
+}
+
+impl MockRule {
+    fn matches(&self, turn: usize, message: &str) -> bool {
+        self.turn.map_or(true, |t| t == turn)
+            && self.pattern.as_ref().map_or(true, |p| p.is_match(message))
+    }
+}
+
+impl MockReply {
+    // Explicit chunks are the whole streamed text, so they also make up the final content
+    fn full_content(&self) -> String {
+        if let Some(chunks) = &self.chunks {
+            return chunks.concat();
+        }
+        match &self.think {
+            Some(think) => format!("<think>\n{}\n</think>\n\n{}", think, self.content),
+            None => self.content.clone(),
+        }
+    }
+
+    fn chunks(&self) -> Vec<String> {
+        match &self.chunks {
+            Some(chunks) => chunks.clone(),
+            None => self
+                .full_content()
+                .split_inclusive(' ')
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl MockBridge {
+    pub fn new(script: MockScript) -> Result<Self, LLMServiceError> {
+        let rules = script
+            .responses
+            .into_iter()
+            .map(|spec| {
+                let pattern = spec
+                    .pattern
+                    .map(|p| Regex::new(&p))
+                    .transpose()
+                    .map_err(|e| LLMServiceError {
+                        error: format!("Invalid mock script pattern: {}", e),
+                    })?;
+                Ok(MockRule {
+                    pattern,
+                    turn: spec.turn,
+                    reply: spec.reply,
+                })
+            })
+            .collect::<Result<_, LLMServiceError>>()?;
+
+        Ok(Self {
+            model: script.model.unwrap_or_else(|| "mock".to_string()),
+            embedding_dimensions: script
+                .embedding_dimensions
+                .unwrap_or(DEFAULT_EMBEDDING_DIMENSIONS)
+                .max(1),
+            latency_ms: script.latency_ms,
+            chunk_delay_ms: script.chunk_delay_ms,
+            rules,
+            fallback: script.fallback,
+            record_to: script.record_to,
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn from_script_file(path: &Path) -> Result<Self, LLMServiceError> {
+        let content = fs::read_to_string(path).map_err(|e| LLMServiceError {
+            error: format!("Failed to read mock script {}: {}", path.display(), e),
+        })?;
+        let script = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        }
+        .map_err(|e| LLMServiceError {
+            error: format!("Failed to parse mock script {}: {}", path.display(), e),
+        })?;
+        Self::new(script)
+    }
+
+    pub fn recorded_requests(&self) -> Vec<LLMRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // Records the request and picks the scripted reply for it
+    async fn respond(&self, request: &LLMRequest) -> Result<MockReply, LLMServiceError> {
+        let turn = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request.clone());
+            requests.len() - 1
+        };
+        if let Some(path) = &self.record_to {
+            if let Err(e) = Self::append_record(path, request) {
+                log::warn!("Failed to record mock request: {}", e);
+            }
+        }
+
+        let message = request
+            .messages
+            .iter()
+            .rev()
+            .find(|p| p.role == "user")
+            .map(|p| p.content.as_str())
+            .unwrap_or_default();
+        let reply = self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(turn, message))
+            .map(|rule| rule.reply.clone())
+            .or_else(|| self.fallback.clone())
+            .ok_or_else(|| LLMServiceError {
+                error: format!("No scripted mock response for turn {}", turn),
+            })?;
+
+        let latency = reply.latency_ms.unwrap_or(self.latency_ms);
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+
+        match &reply.error {
+            Some(error) => Err(LLMServiceError {
+                error: error.clone(),
+            }),
+            None => Ok(reply),
+        }
+    }
+
+    fn append_record(path: &Path, request: &LLMRequest) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(request).map_err(std::io::Error::other)?;
+        writeln!(file, "{}", line)
+    }
+
+    fn response(request: &LLMRequest, reply: &MockReply) -> LLMResponse {
+        let content = reply.full_content();
+        let prompt_chars: usize = request.messages.iter().map(|p| p.content.len()).sum();
+        LLMResponse {
+            usage: Some(Usage {
+                prompt_tokens: prompt_chars.div_ceil(4) as u64,
+                completion_tokens: content.len().div_ceil(4) as u64,
+            }),
+            content,
+            tool_calls: reply.tool_calls.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl LLMBridge for MockBridge {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse, LLMServiceError> {
+        let reply = self.respond(&request).await?;
+        Ok(Self::response(&request, &reply))
+    }
+
+    async fn health_check(&self) -> Result<bool, LLMServiceError> {
+        Ok(true)
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        Some(MOCK_EMBEDDING_MODEL)
+    }
+
+    // Hashed bag of words: deterministic, and texts sharing words come out similar
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let mut vector = vec![0.0; self.embedding_dimensions];
+                for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+                    let mut hasher = DefaultHasher::new();
+                    word.to_lowercase().hash(&mut hasher);
+                    vector[hasher.finish() as usize % self.embedding_dimensions] += 1.0;
+                }
+                vector
+            })
+            .collect())
+    }
+
+    async fn complete_stream(
+        &self,
+        request: LLMRequest,
+        on_chunk: &ChunkHandler<'_>,
+    ) -> Result<LLMResponse, LLMServiceError> {
+        let reply = self.respond(&request).await?;
+        for chunk in reply.chunks() {
+            on_chunk(&chunk);
+            if self.chunk_delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(self.chunk_delay_ms)).await;
+            }
+        }
+        Ok(Self::response(&request, &reply))
+    }
+}
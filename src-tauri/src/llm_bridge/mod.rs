@@ -1,7 +1,10 @@
 mod azure;
 mod azure_deepseek;
 mod deepseek;
+mod functions;
+mod mock;
 mod ollama;
+mod openai;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -10,21 +13,90 @@ pub use azure::*;
 pub use deepseek::*;
 pub use ollama::*;
 pub use azure_deepseek::*;
+pub use mock::*;
+pub use openai::*;
+use functions::*;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LLMResponse {
     pub content: String,
+    pub usage: Option<Usage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+// A function the model may call, described by a JSON schema for its arguments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl Usage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Prompt {
     pub role: String,
     pub content: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Inputs per embedding request; longer lists are split into several requests
+pub const EMBED_BATCH_SIZE: usize = 32;
+
+pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+pub const DEFAULT_MAX_TOKENS: u32 = 800;
+
+// Sampling overrides; bridges fall back to their defaults for anything unset
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationParams {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Asks for a JSON reply. Bridges that can constrain output to a schema pass it on, the others
+// switch on their plain JSON mode or ignore it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Json,
+    JsonSchema { schema: serde_json::Value },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct LLMRequest {
     pub messages: Vec<Prompt>,
+    #[serde(default)]
+    pub params: GenerationParams,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ResponseFormat>,
+    // Offered for native function calling; bridges without it ignore them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -40,13 +112,41 @@ impl std::fmt::Display for LLMServiceError {
     }
 }
 
+// Receives streamed completion text as it arrives
+pub type ChunkHandler<'a> = dyn Fn(&str) + Send + Sync + 'a;
+
 #[async_trait]
 pub trait LLMBridge: Send + Sync {
     fn name(&self) -> &str;
     fn model(&self) -> &str;
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse, LLMServiceError>;
     async fn health_check(&self) -> Result<bool, LLMServiceError>;
+
+    // Configured separately from the chat model; None when the bridge can't embed
+    fn embedding_model(&self) -> Option<&str> {
+        None
+    }
+
+    async fn embed(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        Err(LLMServiceError {
+            error: format!("{} does not support embeddings", self.name()),
+        })
+    }
+
+    // Bridges without native streaming deliver the whole completion as a single chunk
+    async fn complete_stream(
+        &self,
+        request: LLMRequest,
+        on_chunk: &ChunkHandler<'_>,
+    ) -> Result<LLMResponse, LLMServiceError> {
+        let response = self.complete(request).await?;
+        on_chunk(&response.content);
+        Ok(response)
+    }
 }
 
 mod commands;
 pub use commands::*;
+
+#[cfg(test)]
+mod tests;
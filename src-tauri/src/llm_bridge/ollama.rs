@@ -1,5 +1,5 @@
 use super::LLMServiceError;
-use super::{LLMBridge, LLMRequest, LLMResponse, Prompt};
+use super::{LLMBridge, LLMRequest, LLMResponse, Prompt, ResponseFormat, Usage, EMBED_BATCH_SIZE};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 pub struct OllamaBridge {
     base_url: String,
     model: String,
+    embedding_model: Option<String>,
     client: Client,
 }
 
@@ -15,11 +16,37 @@ struct OllamaRequest {
     model: String,
     prompt: String,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaOptions>,
+    // "json", or a JSON schema the reply must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
 }
 
 #[derive(Deserialize)]
 struct OllamaResponse {
     response: String,
+    prompt_eval_count: Option<u64>,
+    eval_count: Option<u64>,
 }
 
 impl OllamaBridge {
@@ -27,10 +54,16 @@ impl OllamaBridge {
         Self {
             base_url: base_url.to_string(),
             model: model.to_string(),
+            embedding_model: None,
             client: Client::new(),
         }
     }
 
+    pub fn with_embedding_model(mut self, model: Option<&str>) -> Self {
+        self.embedding_model = model.map(str::to_string);
+        self
+    }
+
     fn format_prompts(prompts: Vec<Prompt>) -> String {
         prompts
             .into_iter()
@@ -55,6 +88,15 @@ impl LLMBridge for OllamaBridge {
             model: self.model.clone(),
             prompt: Self::format_prompts(request.messages),
             stream: false,
+            // Ollama keeps the model's own defaults unless options are given
+            options: (!request.params.is_default()).then_some(OllamaOptions {
+                temperature: request.params.temperature,
+                num_predict: request.params.max_tokens,
+            }),
+            format: request.format.map(|format| match format {
+                ResponseFormat::Json => serde_json::json!("json"),
+                ResponseFormat::JsonSchema { schema } => schema,
+            }),
         };
 
         let response = self
@@ -81,11 +123,69 @@ impl LLMBridge for OllamaBridge {
             error: format!("Failed to parse Ollama response: {}", e),
         })?;
 
+        let usage = match (ollama_resp.prompt_eval_count, ollama_resp.eval_count) {
+            (None, None) => None,
+            (prompt, completion) => Some(Usage {
+                prompt_tokens: prompt.unwrap_or(0),
+                completion_tokens: completion.unwrap_or(0),
+            }),
+        };
+
         Ok(LLMResponse {
             content: ollama_resp.response,
+            usage,
+            tool_calls: Vec::new(),
         })
     }
 
+    fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model.as_deref()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        let model = self.embedding_model.as_deref().ok_or_else(|| LLMServiceError {
+            error: "No embedding model configured for Ollama".to_string(),
+        })?;
+
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let response = self
+                .client
+                .post(format!("{}/api/embed", self.base_url))
+                .json(&OllamaEmbedRequest { model, input: batch })
+                .send()
+                .await
+                .map_err(|e| LLMServiceError {
+                    error: format!("Ollama embedding request failed: {}", e),
+                })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.map_err(|e| LLMServiceError {
+                    error: format!("Failed to read error response: {}", e),
+                })?;
+                return Err(LLMServiceError {
+                    error: format!("Ollama API error: {} - {}", status, error_text),
+                });
+            }
+
+            let body: OllamaEmbedResponse = response.json().await.map_err(|e| LLMServiceError {
+                error: format!("Failed to parse Ollama embeddings: {}", e),
+            })?;
+            if body.embeddings.len() != batch.len() {
+                return Err(LLMServiceError {
+                    error: format!(
+                        "Ollama returned {} embeddings for {} inputs",
+                        body.embeddings.len(),
+                        batch.len()
+                    ),
+                });
+            }
+            embeddings.extend(body.embeddings);
+        }
+        Ok(embeddings)
+    }
+
     async fn health_check(&self) -> Result<bool, LLMServiceError> {
         let response = self
             .client
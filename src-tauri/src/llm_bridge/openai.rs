@@ -0,0 +1,234 @@
+use super::LLMServiceError;
+use super::{chat_tools, ReplyMessage};
+use super::{LLMBridge, LLMRequest, LLMResponse, Prompt, ResponseFormat, Usage, DEFAULT_MAX_TOKENS, DEFAULT_TEMPERATURE, EMBED_BATCH_SIZE};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+// Any server speaking the OpenAI REST API: OpenAI itself, vLLM, LM Studio, llama.cpp and the like.
+// `base_url` includes the version prefix, e.g. "https://api.openai.com/v1".
+pub struct OpenAIBridge {
+    base_url: String,
+    api_key: String,
+    model: String,
+    embedding_model: Option<String>,
+    client: Client,
+}
+
+#[derive(Serialize)]
+struct OpenAIRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    temperature: f32,
+    max_tokens: u32,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAIResponse {
+    choices: Vec<Choice>,
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: ReplyMessage,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<Embedding>,
+}
+
+#[derive(Deserialize)]
+struct Embedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAIBridge {
+    pub fn new(base_url: &str, api_key: &str, model: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            embedding_model: None,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_embedding_model(mut self, model: Option<&str>) -> Self {
+        self.embedding_model = model.map(str::to_string);
+        self
+    }
+
+    fn convert_prompts(prompts: Vec<Prompt>) -> Vec<ChatMessage> {
+        prompts
+            .into_iter()
+            .map(|p| ChatMessage {
+                role: p.role,
+                content: p.content,
+            })
+            .collect()
+    }
+
+    // Local servers often run without a key
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        }
+    }
+}
+
+#[async_trait]
+impl LLMBridge for OpenAIBridge {
+    fn name(&self) -> &str {
+        "OpenAI"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse, LLMServiceError> {
+        let openai_req = OpenAIRequest {
+            model: self.model.clone(),
+            messages: Self::convert_prompts(request.messages),
+            temperature: request.params.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: request.params.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            stream: false,
+            tools: chat_tools(&request.tools),
+            response_format: request.format.map(|format| match format {
+                ResponseFormat::Json => serde_json::json!({ "type": "json_object" }),
+                ResponseFormat::JsonSchema { schema } => serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "response", "schema": schema },
+                }),
+            }),
+        };
+
+        let response = self
+            .authorized(self.client.post(format!("{}/chat/completions", self.base_url)))
+            .json(&openai_req)
+            .send()
+            .await
+            .map_err(|e| LLMServiceError {
+                error: format!("OpenAI request failed: {}", e),
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.map_err(|e| LLMServiceError {
+                error: format!("Failed to read error response: {}", e),
+            })?;
+            return Err(LLMServiceError {
+                error: format!("OpenAI API error: {} - {}", status, error_text),
+            });
+        }
+
+        let openai_resp: OpenAIResponse = response.json().await.map_err(|e| LLMServiceError {
+            error: format!("Failed to parse OpenAI response: {}", e),
+        })?;
+
+        let message = openai_resp
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| LLMServiceError {
+                error: "No completion choices returned".to_string(),
+            })?
+            .message;
+
+        let usage = openai_resp.usage.map(|u| Usage {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+        });
+
+        Ok(LLMResponse {
+            content: message.content.unwrap_or_default(),
+            usage,
+            tool_calls: message.tool_calls.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model.as_deref()
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, LLMServiceError> {
+        let model = self.embedding_model.as_deref().ok_or_else(|| LLMServiceError {
+            error: "No embedding model configured for OpenAI".to_string(),
+        })?;
+
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let response = self
+                .authorized(self.client.post(format!("{}/embeddings", self.base_url)))
+                .json(&EmbeddingRequest { model, input: batch })
+                .send()
+                .await
+                .map_err(|e| LLMServiceError {
+                    error: format!("OpenAI embedding request failed: {}", e),
+                })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.map_err(|e| LLMServiceError {
+                    error: format!("Failed to read error response: {}", e),
+                })?;
+                return Err(LLMServiceError {
+                    error: format!("OpenAI API error: {} - {}", status, error_text),
+                });
+            }
+
+            let mut body: EmbeddingResponse = response.json().await.map_err(|e| LLMServiceError {
+                error: format!("Failed to parse OpenAI embeddings: {}", e),
+            })?;
+            if body.data.len() != batch.len() {
+                return Err(LLMServiceError {
+                    error: format!("OpenAI returned {} embeddings for {} inputs", body.data.len(), batch.len()),
+                });
+            }
+            // The API doesn't promise to keep input order
+            body.data.sort_by_key(|e| e.index);
+            embeddings.extend(body.data.into_iter().map(|e| e.embedding));
+        }
+        Ok(embeddings)
+    }
+
+    async fn health_check(&self) -> Result<bool, LLMServiceError> {
+        let response = self
+            .authorized(self.client.get(format!("{}/models", self.base_url)))
+            .send()
+            .await
+            .map_err(|e| LLMServiceError {
+                error: format!("Health check failed: {}", e),
+            })?;
+
+        Ok(response.status().is_success())
+    }
+}
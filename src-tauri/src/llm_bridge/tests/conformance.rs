@@ -0,0 +1,161 @@
+use super::harness::{unreachable_url, MockServer};
+use crate::llm_bridge::{LLMBridge, LLMRequest, Prompt};
+
+// Every success fixture must answer with this text
+pub const FIXTURE_ANSWER: &str = "Hello from the fixture.";
+
+pub struct BridgeCase {
+    pub fixtures: &'static str,
+    pub method: &'static str,
+    pub completion_path: &'static str,
+    pub auth_header: Option<(&'static str, &'static str)>,
+    pub reports_usage: bool,
+    pub make: fn(&str) -> Box<dyn LLMBridge>,
+}
+
+fn request() -> LLMRequest {
+    LLMRequest {
+        messages: vec![
+            Prompt {
+                role: "system".to_string(),
+                content: "You are a research assistant.".to_string(),
+            },
+            Prompt {
+                role: "user".to_string(),
+                content: "ping".to_string(),
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+pub async fn completes_successfully(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "success").await;
+    let bridge = (case.make)(&server.url());
+
+    let response = bridge.complete(request()).await.expect("completion failed");
+    assert_eq!(response.content, FIXTURE_ANSWER);
+    assert_eq!(response.usage.is_some(), case.reports_usage);
+    if let Some(usage) = response.usage {
+        assert!(usage.total_tokens() > 0);
+    }
+
+    let requests = server.requests().await;
+    assert_eq!(requests.len(), 1);
+    let sent = &requests[0];
+    assert_eq!(sent.method, case.method);
+    assert_eq!(sent.path, case.completion_path);
+    if let Some((name, value)) = case.auth_header {
+        assert_eq!(sent.header(name), Some(value));
+    }
+    let body = sent.json().to_string();
+    assert!(body.contains("ping"), "prompt missing from request: {}", body);
+    assert!(body.contains("You are a research assistant."));
+}
+
+pub async fn handles_streamed_body(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "streamed").await;
+    let bridge = (case.make)(&server.url());
+
+    let response = bridge.complete(request()).await.expect("completion failed");
+    assert_eq!(response.content, FIXTURE_ANSWER);
+}
+
+pub async fn reports_server_errors(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "server_error").await;
+    let bridge = (case.make)(&server.url());
+
+    let error = bridge.complete(request()).await.unwrap_err();
+    assert!(error.error.contains("500"), "unexpected error: {}", error);
+}
+
+pub async fn reports_rate_limits(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "rate_limited").await;
+    let bridge = (case.make)(&server.url());
+
+    let error = bridge.complete(request()).await.unwrap_err();
+    assert!(error.error.contains("429"), "unexpected error: {}", error);
+}
+
+pub async fn rejects_malformed_json(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "malformed").await;
+    let bridge = (case.make)(&server.url());
+
+    let error = bridge.complete(request()).await.unwrap_err();
+    assert!(error.error.contains("parse"), "unexpected error: {}", error);
+}
+
+pub async fn rejects_empty_completion(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "empty").await;
+    let bridge = (case.make)(&server.url());
+
+    assert!(bridge.complete(request()).await.is_err());
+}
+
+pub async fn reports_unreachable_server(case: &BridgeCase) {
+    let bridge = (case.make)(&unreachable_url().await);
+
+    let error = bridge.complete(request()).await.unwrap_err();
+    assert!(error.error.contains("failed"), "unexpected error: {}", error);
+}
+
+pub async fn health_check_reflects_status(case: &BridgeCase) {
+    let server = MockServer::replay(case.fixtures, "success").await;
+    let bridge = (case.make)(&server.url());
+    assert!(bridge.health_check().await.unwrap());
+
+    let server = MockServer::replay(case.fixtures, "server_error").await;
+    let bridge = (case.make)(&server.url());
+    assert!(!bridge.health_check().await.unwrap());
+}
+
+// Instantiates the whole suite for one bridge; every LLMBridge implementation gets one
+macro_rules! conformance_suite {
+    ($name:ident, $case:expr) => {
+        mod $name {
+            use super::*;
+
+            #[tokio::test]
+            async fn completes_successfully() {
+                conformance::completes_successfully(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn handles_streamed_body() {
+                conformance::handles_streamed_body(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn reports_server_errors() {
+                conformance::reports_server_errors(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn reports_rate_limits() {
+                conformance::reports_rate_limits(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn rejects_malformed_json() {
+                conformance::rejects_malformed_json(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn rejects_empty_completion() {
+                conformance::rejects_empty_completion(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn reports_unreachable_server() {
+                conformance::reports_unreachable_server(&$case).await;
+            }
+
+            #[tokio::test]
+            async fn health_check_reflects_status() {
+                conformance::health_check_reflects_status(&$case).await;
+            }
+        }
+    };
+}
+
+pub(crate) use conformance_suite;
@@ -0,0 +1,308 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+// When set, fixtures are re-recorded by proxying the first request to this upstream
+const RECORD_UPSTREAM_ENV: &str = "CCRU_RECORD_UPSTREAM";
+// Replaces the tests' dummy credentials while recording, e.g. "Bearer sk-..."
+const RECORD_AUTH_ENV: &str = "CCRU_RECORD_AUTH";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    // Sent verbatim instead of `body`, for payloads that are not valid JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_body: Option<String>,
+    // Sent with chunked transfer encoding, one write per chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<String>>,
+    #[serde(default)]
+    pub chunk_delay_ms: u64,
+}
+
+impl FixtureResponse {
+    fn payload(&self) -> Vec<u8> {
+        match (&self.raw_body, &self.body) {
+            (Some(raw), _) => raw.clone().into_bytes(),
+            (None, Some(body)) => serde_json::to_vec(body).unwrap(),
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|v| v.as_str())
+    }
+
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).expect("request body is not JSON")
+    }
+}
+
+pub fn fixture_path(provider: &str, case: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(provider)
+        .join(format!("{}.json", case))
+}
+
+pub fn load_fixture(provider: &str, case: &str) -> FixtureResponse {
+    let path = fixture_path(provider, case);
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path.display(), e));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|e| panic!("Failed to parse fixture {}: {}", path.display(), e))
+}
+
+// A local HTTP/1.1 server answering each connection with the next queued response
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(responses: Vec<FixtureResponse>) -> Self {
+        Self::start_with(responses, None).await
+    }
+
+    // Replays a recorded fixture, or records it first when an upstream is configured
+    pub async fn replay(provider: &str, case: &str) -> Self {
+        match std::env::var(RECORD_UPSTREAM_ENV) {
+            Ok(upstream) => {
+                Self::start_with(Vec::new(), Some((upstream, fixture_path(provider, case)))).await
+            }
+            Err(_) => Self::start(vec![load_fixture(provider, case)]).await,
+        }
+    }
+
+    async fn start_with(responses: Vec<FixtureResponse>, record: Option<(String, PathBuf)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let queue = Arc::new(Mutex::new(responses));
+
+        let recorded = requests.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let queue = queue.clone();
+                let record = record.clone();
+                tokio::spawn(async move {
+                    serve(stream, recorded, queue, record).await;
+                });
+            }
+        });
+
+        Self {
+            addr,
+            requests,
+            handle,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub async fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().await.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+// Returns a URL nothing is listening on
+pub async fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    recorded: Arc<Mutex<Vec<RecordedRequest>>>,
+    queue: Arc<Mutex<Vec<FixtureResponse>>>,
+    record: Option<(String, PathBuf)>,
+) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    recorded.lock().await.push(request.clone());
+
+    let response = match record {
+        Some((upstream, path)) => record_upstream(&upstream, &path, &request).await,
+        None => {
+            let mut queue = queue.lock().await;
+            if queue.is_empty() {
+                FixtureResponse {
+                    status: 599,
+                    headers: HashMap::new(),
+                    body: None,
+                    raw_body: Some("mock server has no response queued".to_string()),
+                    chunks: None,
+                    chunk_delay_ms: 0,
+                }
+            } else {
+                queue.remove(0)
+            }
+        }
+    };
+
+    let _ = write_response(&mut stream, &response).await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buf[head_end..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+async fn write_response(stream: &mut TcpStream, response: &FixtureResponse) -> std::io::Result<()> {
+    let reason = StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("connection: close\r\n");
+
+    match &response.chunks {
+        Some(chunks) => {
+            head.push_str("transfer-encoding: chunked\r\n\r\n");
+            stream.write_all(head.as_bytes()).await?;
+            for chunk in chunks {
+                stream
+                    .write_all(format!("{:x}\r\n{}\r\n", chunk.len(), chunk).as_bytes())
+                    .await?;
+                stream.flush().await?;
+                tokio::time::sleep(Duration::from_millis(response.chunk_delay_ms)).await;
+            }
+            stream.write_all(b"0\r\n\r\n").await?;
+        }
+        None => {
+            let payload = response.payload();
+            head.push_str(&format!("content-length: {}\r\n\r\n", payload.len()));
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(&payload).await?;
+        }
+    }
+
+    stream.flush().await
+}
+
+async fn record_upstream(upstream: &str, path: &PathBuf, request: &RecordedRequest) -> FixtureResponse {
+    let client = reqwest::Client::new();
+    let method = reqwest::Method::from_bytes(request.method.as_bytes()).unwrap();
+    let mut builder = client.request(method, format!("{}{}", upstream, request.path));
+    let auth = std::env::var(RECORD_AUTH_ENV).ok();
+    for (name, value) in &request.headers {
+        match (name.as_str(), &auth) {
+            ("host" | "content-length", _) => {}
+            ("authorization" | "api-key", Some(auth)) => builder = builder.header(name, auth),
+            _ => builder = builder.header(name, value),
+        }
+    }
+    let upstream_response = builder
+        .body(request.body.clone())
+        .send()
+        .await
+        .expect("Failed to reach upstream while recording");
+
+    let status = upstream_response.status().as_u16();
+    let headers = upstream_response
+        .headers()
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            name == "content-type" || name == "retry-after"
+        })
+        .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect();
+    let text = upstream_response.text().await.unwrap_or_default();
+    let (body, raw_body) = match serde_json::from_str::<Value>(&text) {
+        Ok(body) => (Some(body), None),
+        Err(_) => (None, Some(text)),
+    };
+
+    let fixture = FixtureResponse {
+        status,
+        headers,
+        body,
+        raw_body,
+        chunks: None,
+        chunk_delay_ms: 0,
+    };
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, serde_json::to_string_pretty(&fixture).unwrap()).unwrap();
+    fixture
+}
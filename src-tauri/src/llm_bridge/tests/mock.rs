@@ -0,0 +1,151 @@
+use crate::llm_bridge::{LLMBridge, LLMRequest, MockBridge, MockScript, Prompt};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+fn bridge(script: &str) -> MockBridge {
+    let script: MockScript = toml::from_str(script).unwrap();
+    MockBridge::new(script).unwrap()
+}
+
+fn ask(content: &str) -> LLMRequest {
+    LLMRequest {
+        messages: vec![Prompt {
+            role: "user".to_string(),
+            content: content.to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+const SCRIPT: &str = r#"
+model = "scripted"
+
+[[responses]]
+turn = 0
+content = "First turn."
+
+[[responses]]
+match = "(?i)variational"
+content = "ELBO."
+think = "The user asks about VI."
+
+[[responses]]
+match = "outage"
+error = "simulated outage"
+
+[[responses]]
+match = "search"
+content = ""
+toolCalls = [{ name = "search", arguments = { query = "hnsw" } }]
+
+[fallback]
+content = "I don't know."
+"#;
+
+#[tokio::test]
+async fn answers_by_turn_then_pattern_then_fallback() {
+    let mock = bridge(SCRIPT);
+    assert_eq!(mock.model(), "scripted");
+
+    let first = mock.complete(ask("Tell me about variational inference")).await;
+    assert_eq!(first.unwrap().content, "First turn.");
+
+    let second = mock.complete(ask("And Variational Bayes?")).await.unwrap();
+    assert_eq!(
+        second.content,
+        "<think>\nThe user asks about VI.\n</think>\n\nELBO."
+    );
+
+    let third = mock.complete(ask("Something else")).await.unwrap();
+    assert_eq!(third.content, "I don't know.");
+}
+
+#[tokio::test]
+async fn injects_errors_and_tool_calls() {
+    let mock = bridge(SCRIPT);
+    mock.complete(ask("warm up")).await.unwrap();
+
+    let error = mock.complete(ask("simulate an outage")).await.unwrap_err();
+    assert_eq!(error.error, "simulated outage");
+
+    let response = mock.complete(ask("search the index")).await.unwrap();
+    assert_eq!(response.tool_calls.len(), 1);
+    assert_eq!(response.tool_calls[0].name, "search");
+    assert_eq!(response.tool_calls[0].arguments["query"], "hnsw");
+}
+
+#[tokio::test]
+async fn records_every_request() {
+    let mock = bridge(SCRIPT);
+    mock.complete(ask("one")).await.unwrap();
+    let _ = mock.complete(ask("outage")).await;
+
+    let recorded = mock.recorded_requests();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[0].messages[0].content, "one");
+    assert_eq!(recorded[1].messages[0].content, "outage");
+}
+
+#[tokio::test]
+async fn streams_scripted_chunks() {
+    let mock = bridge(
+        r#"
+chunkDelayMs = 1
+
+[fallback]
+chunks = ["<think>hm</think>", "Hello", ", world"]
+"#,
+    );
+    let received = Mutex::new(Vec::new());
+    let on_chunk = |chunk: &str| received.lock().unwrap().push(chunk.to_string());
+
+    let response = mock.complete_stream(ask("hi"), &on_chunk).await.unwrap();
+    assert_eq!(response.content, "<think>hm</think>Hello, world");
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec!["<think>hm</think>", "Hello", ", world"]
+    );
+}
+
+#[tokio::test]
+async fn simulates_latency() {
+    let mock = bridge(
+        r#"
+[fallback]
+content = "slow"
+latencyMs = 50
+"#,
+    );
+    let start = Instant::now();
+    mock.complete(ask("hi")).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn fails_without_matching_script_entry() {
+    let mock = bridge(
+        r#"
+[[responses]]
+match = "^exact$"
+content = "matched"
+"#,
+    );
+    assert!(mock.complete(ask("not exact")).await.is_err());
+}
+
+#[tokio::test]
+async fn embeds_words_deterministically() {
+    let mock = bridge(
+        r#"
+embeddingDimensions = 16
+
+[fallback]
+content = "ok"
+"#,
+    );
+    let texts = vec!["Variational inference".to_string(), "variational INFERENCE".to_string()];
+    let embeddings = mock.embed(&texts).await.unwrap();
+    assert_eq!(embeddings[0].len(), 16);
+    assert_eq!(embeddings[0], embeddings[1]);
+    assert_eq!(embeddings[0].iter().sum::<f32>(), 2.0);
+}
@@ -0,0 +1,227 @@
+mod conformance;
+mod harness;
+mod mock;
+
+use super::{
+    AzureBridge, AzureDeepSeekBridge, DeepSeekBridge, LLMBridge, LLMRequest, OllamaBridge, OpenAIBridge,
+    Prompt, ToolDefinition,
+};
+use conformance::{conformance_suite, BridgeCase};
+use harness::MockServer;
+use serde_json::json;
+
+const OLLAMA: BridgeCase = BridgeCase {
+    fixtures: "ollama",
+    method: "POST",
+    completion_path: "/api/generate",
+    auth_header: None,
+    reports_usage: true,
+    make: |url| Box::new(OllamaBridge::new(url, "phi4")),
+};
+
+const AZURE: BridgeCase = BridgeCase {
+    fixtures: "azure",
+    method: "POST",
+    completion_path: "/text/completions?api-version=2023-05-01",
+    auth_header: Some(("api-key", "test-key")),
+    reports_usage: false,
+    make: |url| Box::new(AzureBridge::new(url, "gpt-4o", "test-key")),
+};
+
+const DEEPSEEK: BridgeCase = BridgeCase {
+    fixtures: "deepseek",
+    method: "POST",
+    completion_path: "/v1/chat/completions",
+    auth_header: Some(("authorization", "Bearer test-key")),
+    reports_usage: true,
+    make: |url| Box::new(DeepSeekBridge::with_base_url(url, "test-key", "deepseek-chat")),
+};
+
+const AZURE_DEEPSEEK: BridgeCase = BridgeCase {
+    fixtures: "azure_deepseek",
+    method: "POST",
+    completion_path: "/v1/chat/completions",
+    auth_header: Some(("authorization", "test-key")),
+    reports_usage: true,
+    make: |url| Box::new(AzureDeepSeekBridge::new(url, "DeepSeek-R1", "test-key")),
+};
+
+const OPENAI: BridgeCase = BridgeCase {
+    fixtures: "openai",
+    method: "POST",
+    completion_path: "/chat/completions",
+    auth_header: Some(("authorization", "Bearer test-key")),
+    reports_usage: true,
+    make: |url| Box::new(OpenAIBridge::new(url, "test-key", "gpt-4o-mini")),
+};
+
+conformance_suite!(ollama, OLLAMA);
+conformance_suite!(azure, AZURE);
+conformance_suite!(deepseek, DEEPSEEK);
+conformance_suite!(azure_deepseek, AZURE_DEEPSEEK);
+conformance_suite!(openai, OPENAI);
+
+fn question() -> LLMRequest {
+    LLMRequest {
+        messages: vec![Prompt {
+            role: "user".to_string(),
+            content: "What is variational inference?".to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+async fn sent_body(bridge: &dyn LLMBridge, server: &MockServer) -> serde_json::Value {
+    bridge.complete(question()).await.unwrap();
+    server.requests().await.remove(0).json()
+}
+
+#[tokio::test]
+async fn ollama_sends_flattened_prompt() {
+    let server = MockServer::replay("ollama", "success").await;
+    let bridge = OllamaBridge::new(&server.url(), "phi4");
+
+    let body = sent_body(&bridge, &server).await;
+    assert_eq!(
+        body,
+        json!({
+            "model": "phi4",
+            "prompt": "user: What is variational inference?",
+            "stream": false,
+        })
+    );
+}
+
+#[tokio::test]
+async fn azure_sends_flattened_input() {
+    let server = MockServer::replay("azure", "success").await;
+    let bridge = AzureBridge::new(&server.url(), "gpt-4o", "test-key");
+
+    let body = sent_body(&bridge, &server).await;
+    assert_eq!(body["input"], "user: What is variational inference?");
+    assert_eq!(body["parameters"]["max_new_tokens"], 800);
+}
+
+#[tokio::test]
+async fn deepseek_sends_chat_messages() {
+    let server = MockServer::replay("deepseek", "success").await;
+    let bridge = DeepSeekBridge::with_base_url(&server.url(), "test-key", "deepseek-chat");
+
+    let body = sent_body(&bridge, &server).await;
+    assert_eq!(body["model"], "deepseek-chat");
+    assert_eq!(body["stream"], false);
+    assert_eq!(
+        body["messages"],
+        json!([{ "role": "user", "content": "What is variational inference?" }])
+    );
+}
+
+#[tokio::test]
+async fn azure_deepseek_sends_chat_messages() {
+    let server = MockServer::replay("azure_deepseek", "success").await;
+    let bridge = AzureDeepSeekBridge::new(&server.url(), "DeepSeek-R1", "test-key");
+
+    let body = sent_body(&bridge, &server).await;
+    assert!(body.get("model").is_none());
+    assert_eq!(
+        body["messages"],
+        json!([{ "role": "user", "content": "What is variational inference?" }])
+    );
+}
+
+#[tokio::test]
+async fn deepseek_usage_is_parsed() {
+    let server = MockServer::replay("deepseek", "success").await;
+    let bridge = DeepSeekBridge::with_base_url(&server.url(), "test-key", "deepseek-chat");
+
+    let usage = bridge.complete(question()).await.unwrap().usage.unwrap();
+    assert_eq!(usage.prompt_tokens, 17);
+    assert_eq!(usage.completion_tokens, 6);
+}
+
+#[tokio::test]
+async fn openai_offers_tools_and_reads_calls() {
+    let server = MockServer::replay("openai", "tool_call").await;
+    let bridge = OpenAIBridge::new(&server.url(), "test-key", "gpt-4o-mini");
+    let parameters = json!({ "type": "object", "properties": { "code": { "type": "string" } } });
+    let request = LLMRequest {
+        tools: vec![ToolDefinition {
+            name: "run_python".to_string(),
+            description: "Run Python".to_string(),
+            parameters: parameters.clone(),
+        }],
+        ..question()
+    };
+
+    let response = bridge.complete(request).await.unwrap();
+    assert_eq!(response.content, "");
+    assert_eq!(response.tool_calls.len(), 1);
+    assert_eq!(response.tool_calls[0].name, "run_python");
+    assert_eq!(response.tool_calls[0].arguments, json!({ "code": "print(2 + 2)" }));
+
+    let body = server.requests().await.remove(0).json();
+    assert_eq!(
+        body["tools"],
+        json!([{
+            "type": "function",
+            "function": { "name": "run_python", "description": "Run Python", "parameters": parameters },
+        }])
+    );
+}
+
+fn passages() -> Vec<String> {
+    vec!["first passage".to_string(), "second passage".to_string()]
+}
+
+#[tokio::test]
+async fn ollama_embeds_with_embedding_model() {
+    let server = MockServer::replay("ollama", "embeddings").await;
+    let bridge = OllamaBridge::new(&server.url(), "phi4").with_embedding_model(Some("nomic-embed-text"));
+
+    let embeddings = bridge.embed(&passages()).await.unwrap();
+    assert_eq!(embeddings, vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
+
+    let sent = server.requests().await.remove(0);
+    assert_eq!(sent.path, "/api/embed");
+    assert_eq!(
+        sent.json(),
+        json!({ "model": "nomic-embed-text", "input": ["first passage", "second passage"] })
+    );
+}
+
+#[tokio::test]
+async fn openai_embeddings_follow_input_order() {
+    let server = MockServer::replay("openai", "embeddings").await;
+    let bridge = OpenAIBridge::new(&server.url(), "test-key", "gpt-4o-mini")
+        .with_embedding_model(Some("text-embedding-3-small"));
+
+    let embeddings = bridge.embed(&passages()).await.unwrap();
+    assert_eq!(embeddings, vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
+
+    let sent = server.requests().await.remove(0);
+    assert_eq!(sent.path, "/embeddings");
+    assert_eq!(sent.header("authorization"), Some("Bearer test-key"));
+    assert_eq!(sent.json()["model"], "text-embedding-3-small");
+}
+
+#[tokio::test]
+async fn azure_embeddings_follow_input_order() {
+    let server = MockServer::replay("azure", "embeddings").await;
+    let bridge = AzureBridge::new(&server.url(), "gpt-4o", "test-key")
+        .with_embedding_model(Some("text-embedding-3-small"));
+
+    let embeddings = bridge.embed(&passages()).await.unwrap();
+    assert_eq!(embeddings[0], vec![0.1, 0.2, 0.3]);
+    assert_eq!(server.requests().await[0].header("api-key"), Some("test-key"));
+}
+
+#[tokio::test]
+async fn embedding_requires_embedding_model() {
+    let bridge = OllamaBridge::new("http://localhost:1", "phi4");
+    assert!(bridge.embedding_model().is_none());
+    assert!(bridge.embed(&passages()).await.is_err());
+
+    let bridge = DeepSeekBridge::new("test-key", "deepseek-chat");
+    let error = bridge.embed(&passages()).await.unwrap_err();
+    assert!(error.error.contains("does not support embeddings"));
+}
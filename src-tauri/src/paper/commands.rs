@@ -0,0 +1,208 @@
+use crate::budget::{complete_with_budget, UsageContext};
+use crate::chat::{new_id, session_bridge, ChatHistory, ChatSession};
+use crate::llm_bridge::{LLMRequest, LLMServiceError};
+use crate::paper::{parse_structure, settle_summary, summary_prompt, PaperSession, PaperStructure, PaperSummary};
+use crate::rag::{citations, extract_text, ground_messages, parse_citations, GroundedResponse};
+use crate::structured::{complete_typed, DEFAULT_MAX_REPAIRS};
+use crate::AppState;
+use std::path::Path;
+use std::sync::Arc;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
+
+// Opens a PDF as its own session; shows a file picker when no path is given and returns None if cancelled
+#[tauri::command]
+pub async fn create_paper_session(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let (tx, rx) = oneshot::channel();
+            app.dialog()
+                .file()
+                .add_filter("PDF", &["pdf"])
+                .pick_file(move |path| {
+                    let _ = tx.send(path);
+                });
+            let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?.display().to_string()
+        }
+    };
+
+    let document = path.clone();
+    let structure = tauri::async_runtime::spawn_blocking(move || {
+        extract_text(Path::new(&path)).map(|pages| parse_structure(&path, &pages))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if structure.sections.is_empty() {
+        return Err(format!("No text could be extracted from {}", document));
+    }
+
+    let paper = PaperSession {
+        structure,
+        summary: None,
+    };
+    let now = chrono::Utc::now().timestamp();
+    let session = ChatSession {
+        id: new_id(),
+        title: paper.structure.title.clone(),
+        system_prompt: Some(paper.system_prompt()),
+        paper: Some(paper),
+        created_at: now,
+        updated_at: now,
+        ..Default::default()
+    };
+    let session_id = session.id.clone();
+
+    let mut holder = app_state.lock().await;
+    let chat_history = holder.chat_history.get_or_insert_with(|| Arc::new(ChatHistory::new()));
+    chat_history.insert_session(session).await;
+    Ok(Some(session_id))
+}
+
+async fn paper_session(holder: &AppState, session_id: &str) -> Result<(ChatSession, PaperSession), String> {
+    let session = holder
+        .chat_history
+        .as_ref()
+        .unwrap()
+        .get_session(session_id)
+        .await
+        .ok_or_else(|| "Session not found".to_string())?;
+    let paper = session
+        .paper
+        .clone()
+        .ok_or_else(|| "Not a paper-reading session".to_string())?;
+    Ok((session, paper))
+}
+
+#[tauri::command]
+pub async fn get_paper_structure(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+) -> Result<PaperStructure, String> {
+    let holder = app_state.lock().await;
+    let (_, paper) = paper_session(&holder, &session_id).await?;
+    Ok(paper.structure)
+}
+
+// Answers from one section when `section` (an index into the structure's sections) is given, otherwise from the best-matching pages
+#[tauri::command]
+pub async fn ask_paper_question(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+    question: String,
+    section: Option<usize>,
+) -> Result<GroundedResponse, LLMServiceError> {
+    // The lock is only held to read the session; the completion below can take a while
+    let (chat_history, session, excerpts, provider, llm_bridge, budget) = {
+        let holder = app_state.lock().await;
+        let (_, paper) = paper_session(&holder, &session_id)
+            .await
+            .map_err(|e| LLMServiceError { error: e })?;
+        let excerpts = paper
+            .excerpts(&question, section)
+            .map_err(|e| LLMServiceError { error: e })?;
+
+        let chat_history = holder.chat_history.clone().unwrap();
+        chat_history
+            .add_message(&session_id, "user", &question)
+            .await
+            .map_err(|e| LLMServiceError { error: e })?;
+        let session = chat_history
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| LLMServiceError {
+                error: "Session not found".to_string(),
+            })?;
+        let (provider, llm_bridge) =
+            session_bridge(&holder, &session).map_err(|e| LLMServiceError { error: e })?;
+        (chat_history, session, excerpts, provider, llm_bridge, holder.budget.clone())
+    };
+    let llm_request = LLMRequest {
+        params: session.params,
+        messages: ground_messages(session.into(), &excerpts),
+        format: None,
+        tools: Vec::new(),
+    };
+    let ctx = UsageContext {
+        provider: provider.clone(),
+        model: llm_bridge.model().to_string(),
+        session_id: Some(session_id.clone()),
+        agent_run_id: None,
+    };
+    let response = complete_with_budget(&app, llm_bridge.as_ref(), budget.as_deref(), ctx, llm_request).await?;
+
+    let cited = parse_citations(&response.content, &excerpts);
+    chat_history
+        .add_assistant_message(
+            &session_id,
+            &response.content,
+            &provider,
+            llm_bridge.model(),
+            cited.clone(),
+            Vec::new(),
+        )
+        .await
+        .map_err(|e| LLMServiceError { error: e })?;
+    Ok(GroundedResponse {
+        response,
+        sources: citations(&excerpts),
+        citations: cited,
+        references: Vec::new(),
+    })
+}
+
+// Contributions, method, datasets and limitations, each point with the pages it comes from
+#[tauri::command]
+pub async fn summarize_paper(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+) -> Result<PaperSummary, LLMServiceError> {
+    let (chat_history, session, paper, provider, llm_bridge, budget) = {
+        let holder = app_state.lock().await;
+        let (session, paper) = paper_session(&holder, &session_id)
+            .await
+            .map_err(|e| LLMServiceError { error: e })?;
+        let (provider, llm_bridge) =
+            session_bridge(&holder, &session).map_err(|e| LLMServiceError { error: e })?;
+        (
+            holder.chat_history.clone().unwrap(),
+            session,
+            paper,
+            provider,
+            llm_bridge,
+            holder.budget.clone(),
+        )
+    };
+    let ctx = UsageContext {
+        provider,
+        model: llm_bridge.model().to_string(),
+        session_id: Some(session_id.clone()),
+        agent_run_id: None,
+    };
+    let llm_request = LLMRequest {
+        params: session.params,
+        messages: summary_prompt(&paper.structure),
+        format: None,
+        tools: Vec::new(),
+    };
+    let summary = complete_typed(llm_request, DEFAULT_MAX_REPAIRS, |request| {
+        complete_with_budget(&app, llm_bridge.as_ref(), budget.as_deref(), ctx.clone(), request)
+    })
+    .await
+    .map_err(|e| LLMServiceError { error: e })?;
+    let summary = settle_summary(summary, paper.structure.page_count);
+    chat_history
+        .set_paper_summary(&session_id, summary.clone())
+        .await
+        .map_err(|e| LLMServiceError { error: e })?;
+    Ok(summary)
+}
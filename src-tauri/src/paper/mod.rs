@@ -0,0 +1,11 @@
+mod structure;
+pub use structure::*;
+mod summary;
+pub use summary::*;
+mod session;
+pub use session::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
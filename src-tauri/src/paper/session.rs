@@ -0,0 +1,102 @@
+use super::{PaperStructure, PaperSummary};
+use crate::rag::RetrievedChunk;
+use crate::search::terms;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// Excerpts sent with a question are capped at this many characters
+const MAX_CONTEXT_CHARS: usize = 24_000;
+
+// What makes a chat session a paper-reading session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperSession {
+    pub structure: PaperStructure,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<PaperSummary>,
+}
+
+impl PaperSession {
+    // Standing instructions naming the paper and its outline
+    pub fn system_prompt(&self) -> String {
+        let structure = &self.structure;
+        let name = Path::new(&structure.document)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| structure.document.clone());
+        let mut prompt = format!(
+            "You are helping the user read the paper \"{}\" ({}). Answer from the paper's text where you can.",
+            structure.title.as_deref().unwrap_or("Untitled"),
+            name
+        );
+        if let Some(abstract_text) = &structure.abstract_text {
+            prompt.push_str(&format!("\n\nAbstract: {}", abstract_text));
+        }
+        prompt.push_str("\n\nSections:");
+        for section in structure.body() {
+            prompt.push_str(&format!("\n- {}", section.title()));
+        }
+        prompt
+    }
+
+    // Page-sized excerpts for a question: the chosen section, or the parts sharing most terms with the question
+    pub fn excerpts(&self, question: &str, section: Option<usize>) -> Result<Vec<RetrievedChunk>, String> {
+        let structure = &self.structure;
+        let chunk = |index: usize, part_index: usize, score: f64| {
+            let part = &structure.sections[index].parts[part_index];
+            RetrievedChunk {
+                chunk_id: format!("{}:{}", index, part_index),
+                document: structure.document.clone(),
+                page: part.page,
+                start: part.start,
+                end: part.end,
+                text: part.text.clone(),
+                score,
+                cite_key: None,
+            }
+        };
+
+        let mut ranked: Vec<RetrievedChunk> = match section {
+            Some(index) => {
+                let section = structure
+                    .sections
+                    .get(index)
+                    .ok_or_else(|| format!("The paper has no section {}", index))?;
+                (0..section.parts.len()).map(|i| chunk(index, i, 1.0)).collect()
+            }
+            None => {
+                let wanted = terms(question);
+                let mut scored = Vec::new();
+                for (index, section) in structure.sections.iter().enumerate() {
+                    if section.is_references() {
+                        continue;
+                    }
+                    let heading = terms(&section.heading);
+                    for (i, part) in section.parts.iter().enumerate() {
+                        let have = terms(&part.text);
+                        let overlap = wanted.iter().filter(|t| have.contains(t) || heading.contains(t)).count();
+                        if overlap > 0 {
+                            scored.push(chunk(index, i, overlap as f64 / wanted.len() as f64));
+                        }
+                    }
+                }
+                scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+                if scored.is_empty() {
+                    // Nothing matches, e.g. "what is this about?"; read from the start
+                    for (index, section) in structure.sections.iter().enumerate() {
+                        if !section.is_references() {
+                            scored.extend((0..section.parts.len()).map(|i| chunk(index, i, 0.0)));
+                        }
+                    }
+                }
+                scored
+            }
+        };
+
+        let mut total = 0;
+        ranked.retain(|c| {
+            total += c.text.len();
+            total <= MAX_CONTEXT_CHARS
+        });
+        Ok(ranked)
+    }
+}
@@ -0,0 +1,285 @@
+use crate::rag::PageText;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// Headings recognised without a section number
+const NAMED_SECTIONS: &[&str] = &[
+    "abstract",
+    "introduction",
+    "related work",
+    "background",
+    "preliminaries",
+    "method",
+    "methods",
+    "methodology",
+    "approach",
+    "experiments",
+    "experimental setup",
+    "evaluation",
+    "results",
+    "discussion",
+    "limitations",
+    "conclusion",
+    "conclusions",
+    "acknowledgments",
+    "acknowledgements",
+    "references",
+    "bibliography",
+    "appendix",
+];
+
+const FRONT_MATTER: &str = "Front matter";
+
+// The run of a section on one page; offsets are into that page's extracted text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionPart {
+    pub page: Option<u32>,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    pub heading: String,
+    pub parts: Vec<SectionPart>,
+}
+
+impl Section {
+    pub fn title(&self) -> String {
+        match &self.number {
+            Some(number) => format!("{} {}", number, self.heading),
+            None => self.heading.clone(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let parts: Vec<&str> = self.parts.iter().map(|p| p.text.as_str()).collect();
+        parts.join("\n")
+    }
+
+    // Text before the first heading, e.g. title and authors
+    pub fn is_front_matter(&self) -> bool {
+        self.number.is_none() && self.heading == FRONT_MATTER
+    }
+
+    pub fn is_references(&self) -> bool {
+        matches!(self.heading.to_lowercase().as_str(), "references" | "bibliography")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FigureCaption {
+    // "Figure 3" or "Table 1"
+    pub label: String,
+    pub caption: String,
+    pub page: Option<u32>,
+}
+
+// What could be recovered of a paper's layout from its extracted text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperStructure {
+    pub document: String,
+    pub title: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub sections: Vec<Section>,
+    pub figures: Vec<FigureCaption>,
+    pub references: Vec<String>,
+    pub page_count: usize,
+}
+
+impl PaperStructure {
+    // Sections worth sending to the model, i.e. everything but the reference list
+    pub fn body(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|s| !s.is_references())
+    }
+}
+
+struct Heading {
+    number: Option<String>,
+    heading: String,
+    // Byte offset within the line where the section's own text starts, for "Abstract—We study..."
+    rest: Option<usize>,
+}
+
+struct Patterns {
+    numbered: Regex,
+    roman: Regex,
+    markdown: Regex,
+    inline_abstract: Regex,
+    caption: Regex,
+    reference_marker: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        Self {
+            numbered: Regex::new(r"^(\d{1,2}(?:\.\d{1,2}){0,3})\.?\s+([A-Z][A-Za-z][^.]{0,78})$").unwrap(),
+            roman: Regex::new(r"^([IVX]{1,5})\.\s+([A-Z][A-Za-z][^.]{0,78})$").unwrap(),
+            markdown: Regex::new(r"^#{1,6}\s+(.+)$").unwrap(),
+            inline_abstract: Regex::new(r"^(?i:abstract)\s*[—–:.-]\s*").unwrap(),
+            caption: Regex::new(r"^(Figure|Fig\.|Table)\s+(\d+)\s*[:.|]\s*(.+)$").unwrap(),
+            reference_marker: Regex::new(r"(?m)^\s*(?:\[\d+\]|\d{1,3}\.)\s+").unwrap(),
+        }
+    }
+
+    fn heading(&self, line: &str) -> Option<Heading> {
+        if NAMED_SECTIONS.contains(&line.to_lowercase().as_str()) {
+            return Some(Heading {
+                number: None,
+                heading: line.to_string(),
+                rest: None,
+            });
+        }
+        if let Some(captures) = self.markdown.captures(line) {
+            return Some(Heading {
+                number: None,
+                heading: captures[1].trim().to_string(),
+                rest: None,
+            });
+        }
+        if let Some(found) = self.inline_abstract.find(line) {
+            return Some(Heading {
+                number: None,
+                heading: "Abstract".to_string(),
+                rest: Some(found.end()),
+            });
+        }
+        let captures = self.numbered.captures(line).or_else(|| self.roman.captures(line))?;
+        let heading = captures[2].trim();
+        // Table rows and numbered list items look like headings too; real ones are short and wordy
+        let words = heading.split_whitespace().count();
+        let letters = heading.chars().filter(|c| c.is_alphabetic()).count();
+        if words > 10 || letters * 2 < heading.len() || heading.ends_with(',') {
+            return None;
+        }
+        Some(Heading {
+            number: Some(captures[1].to_string()),
+            heading: heading.to_string(),
+            rest: None,
+        })
+    }
+}
+
+fn push_text(section: &mut Section, page: &PageText, start: usize, end: usize) {
+    if page.text[start..end].trim().is_empty() {
+        return;
+    }
+    match section.parts.last_mut() {
+        Some(part) if part.page == page.page => part.end = end,
+        _ => section.parts.push(SectionPart {
+            page: page.page,
+            start,
+            end,
+            text: String::new(),
+        }),
+    }
+}
+
+fn split_references(text: &str, patterns: &Patterns) -> Vec<String> {
+    let clean = |entry: &str| entry.split_whitespace().collect::<Vec<_>>().join(" ");
+    let markers: Vec<usize> = patterns.reference_marker.find_iter(text).map(|m| m.start()).collect();
+    if markers.len() >= 2 {
+        let mut bounds = markers.clone();
+        bounds.push(text.len());
+        return bounds
+            .windows(2)
+            .map(|w| clean(&text[w[0]..w[1]]))
+            .filter(|e| !e.is_empty())
+            .collect();
+    }
+    // Author-year styles: an entry ends with a line ending in a full stop
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        current.push(' ');
+        current.push_str(line.trim());
+        if line.trim_end().ends_with('.') {
+            entries.push(clean(&current));
+            current.clear();
+        }
+    }
+    entries.push(clean(&current));
+    entries.into_iter().filter(|e| !e.is_empty()).collect()
+}
+
+pub fn parse_structure(document: &str, pages: &[PageText]) -> PaperStructure {
+    let patterns = Patterns::new();
+    let mut sections = vec![Section {
+        number: None,
+        heading: FRONT_MATTER.to_string(),
+        parts: Vec::new(),
+    }];
+    let mut figures = Vec::new();
+
+    for page in pages {
+        let mut offset = 0;
+        for raw_line in page.text.split_inclusive('\n') {
+            let start = offset;
+            offset += raw_line.len();
+            let line = raw_line.trim();
+            let leading = raw_line.len() - raw_line.trim_start().len();
+
+            if let Some(heading) = patterns.heading(line) {
+                sections.push(Section {
+                    number: heading.number,
+                    heading: heading.heading,
+                    parts: Vec::new(),
+                });
+                if let Some(rest) = heading.rest {
+                    push_text(sections.last_mut().unwrap(), page, start + leading + rest, offset);
+                }
+                continue;
+            }
+            if let Some(captures) = patterns.caption.captures(line) {
+                let kind = if &captures[1] == "Table" { "Table" } else { "Figure" };
+                figures.push(FigureCaption {
+                    label: format!("{} {}", kind, &captures[2]),
+                    caption: captures[3].trim().to_string(),
+                    page: page.page,
+                });
+            }
+            push_text(sections.last_mut().unwrap(), page, start, offset);
+        }
+    }
+
+    for section in &mut sections {
+        let page_text = |page: Option<u32>| pages.iter().find(|p| p.page == page).map(|p| p.text.as_str());
+        for part in &mut section.parts {
+            let text = page_text(part.page).unwrap_or_default();
+            let trimmed = text[part.start..part.end].trim_end();
+            part.end = part.start + trimmed.len();
+            part.text = trimmed.to_string();
+        }
+    }
+    sections.retain(|s| !s.parts.is_empty() || !s.is_front_matter());
+
+    let title = sections
+        .iter()
+        .find(|s| s.is_front_matter())
+        .and_then(|s| s.parts.first())
+        .and_then(|p| p.text.lines().map(str::trim).find(|l| l.split_whitespace().count() >= 2))
+        .map(str::to_string);
+    let abstract_text = sections
+        .iter()
+        .find(|s| s.heading.eq_ignore_ascii_case("abstract"))
+        .map(|s| s.text().split_whitespace().collect::<Vec<_>>().join(" "));
+    let references = sections
+        .iter()
+        .find(|s| s.is_references())
+        .map(|s| split_references(&s.text(), &patterns))
+        .unwrap_or_default();
+
+    PaperStructure {
+        document: document.to_string(),
+        title,
+        abstract_text,
+        sections,
+        figures,
+        references,
+        page_count: pages.len(),
+    }
+}
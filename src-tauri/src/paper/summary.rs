@@ -0,0 +1,84 @@
+use super::PaperStructure;
+use crate::llm_bridge::Prompt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Papers longer than this are cut off when sent for summarization
+const MAX_PAPER_CHARS: usize = 60_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SummaryPoint {
+    pub text: String,
+    // Pages the point is drawn from
+    #[serde(default)]
+    pub pages: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PaperSummary {
+    #[serde(default)]
+    pub contributions: Vec<SummaryPoint>,
+    #[serde(default)]
+    pub method: Vec<SummaryPoint>,
+    #[serde(default)]
+    pub datasets: Vec<SummaryPoint>,
+    #[serde(default)]
+    pub limitations: Vec<SummaryPoint>,
+}
+
+// The paper's body with page markers, so the model can say where each point comes from
+pub fn paper_text(structure: &PaperStructure) -> String {
+    let mut text = String::new();
+    let mut page = None;
+    for section in structure.body() {
+        text.push_str(&format!("\n\n## {}", section.title()));
+        for part in &section.parts {
+            if let Some(number) = part.page.filter(|_| part.page != page) {
+                page = part.page;
+                text.push_str(&format!("\n[page {}]", number));
+            }
+            text.push('\n');
+            text.push_str(&part.text);
+        }
+    }
+    match text.char_indices().nth(MAX_PAPER_CHARS) {
+        Some((end, _)) => format!("{}\n[truncated]", &text[..end]),
+        None => text,
+    }
+}
+
+pub fn summary_prompt(structure: &PaperStructure) -> Vec<Prompt> {
+    let instructions = "Summarize the research paper given by the user. Reply with only a JSON object with the keys \
+        \"contributions\", \"method\", \"datasets\" and \"limitations\". Each is a list of points of the form \
+        {\"text\": \"...\", \"pages\": [3, 4]}, where pages are the [page n] markers the point is drawn from. \
+        Use an empty list when the paper says nothing on a topic, and don't invent limitations the paper doesn't support.";
+    let title = structure.title.as_deref().unwrap_or("Untitled paper");
+    vec![
+        Prompt {
+            role: "system".to_string(),
+            content: instructions.to_string(),
+        },
+        Prompt {
+            role: "user".to_string(),
+            content: format!("# {}{}", title, paper_text(structure)),
+        },
+    ]
+}
+
+// Drops empty points and pages the paper doesn't have from the model's summary
+pub fn settle_summary(mut summary: PaperSummary, page_count: usize) -> PaperSummary {
+    for points in [
+        &mut summary.contributions,
+        &mut summary.method,
+        &mut summary.datasets,
+        &mut summary.limitations,
+    ] {
+        points.retain(|p| !p.text.trim().is_empty());
+        for point in points.iter_mut() {
+            point.pages.retain(|&p| p >= 1 && p as usize <= page_count);
+            point.pages.sort_unstable();
+            point.pages.dedup();
+        }
+    }
+    summary
+}
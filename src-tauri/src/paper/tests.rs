@@ -0,0 +1,79 @@
+use super::*;
+use crate::rag::PageText;
+
+fn pages() -> Vec<PageText> {
+    let first = "Amortized Inference for Topic Models\nJane Doe and John Roe\n\n\
+                 Abstract—We amortize posterior inference for topic models.\n\
+                 It is fast.\n\n\
+                 1 Introduction\nTopic models are slow to fit.\n\
+                 Figure 1: Runtime against corpus size.\n";
+    let second = "2 Method\nAn encoder maps documents to variational parameters.\n\
+                  2.1 Training\nWe maximize the ELBO on 20 Newsgroups.\n\
+                  Table 1: Perplexity on held-out documents.\n\
+                  3 Conclusion\nAmortization works.\n\
+                  References\n[1] D. Blei. Latent Dirichlet allocation. JMLR, 2003.\n\
+                  [2] D. Kingma and M. Welling. Auto-encoding\nvariational Bayes. ICLR, 2014.\n";
+    [first, second]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| PageText {
+            page: Some(i as u32 + 1),
+            text: text.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn extracts_title_sections_captions_and_references() {
+    let pages = pages();
+    let structure = parse_structure("/papers/amortized.pdf", &pages);
+
+    assert_eq!(structure.title.as_deref(), Some("Amortized Inference for Topic Models"));
+    assert_eq!(
+        structure.abstract_text.as_deref(),
+        Some("We amortize posterior inference for topic models. It is fast.")
+    );
+    let titles: Vec<String> = structure.sections.iter().map(|s| s.title()).collect();
+    assert_eq!(
+        titles,
+        ["Front matter", "Abstract", "1 Introduction", "2 Method", "2.1 Training", "3 Conclusion", "References"]
+    );
+    let labels: Vec<&str> = structure.figures.iter().map(|f| f.label.as_str()).collect();
+    assert_eq!(labels, ["Figure 1", "Table 1"]);
+    assert_eq!(structure.figures[1].page, Some(2));
+    assert_eq!(structure.references.len(), 2);
+    assert!(structure.references[1].ends_with("Auto-encoding variational Bayes. ICLR, 2014."));
+
+    // Part offsets point back into the page text
+    let training = &structure.sections[4].parts[0];
+    assert_eq!(&pages[1].text[training.start..training.end], training.text);
+    assert_eq!(training.text, "We maximize the ELBO on 20 Newsgroups.\nTable 1: Perplexity on held-out documents.");
+}
+
+#[test]
+fn questions_are_scoped_to_sections() {
+    let paper = PaperSession {
+        structure: parse_structure("/papers/amortized.pdf", &pages()),
+        summary: None,
+    };
+    let scoped = paper.excerpts("anything", Some(3)).unwrap();
+    assert_eq!(scoped.len(), 1);
+    assert!(scoped[0].text.starts_with("An encoder"));
+    assert!(paper.excerpts("anything", Some(42)).is_err());
+
+    // Without a section, matching parts are used and the reference list is left out
+    let matched = paper.excerpts("Which corpus is the ELBO maximized on?", None).unwrap();
+    assert_eq!(matched[0].page, Some(2));
+    assert!(matched[0].text.contains("20 Newsgroups"));
+    assert!(matched.iter().all(|c| !c.text.contains("Kingma")));
+}
+
+#[test]
+fn summaries_keep_only_real_pages() {
+    let reply = "{\"contributions\": [{\"text\": \"Amortized inference\", \"pages\": [1, 9, 1]}], \
+                 \"datasets\": [{\"text\": \"20 Newsgroups\", \"pages\": [2]}], \"limitations\": [{\"text\": \" \"}]}";
+    let summary = settle_summary(serde_json::from_str(reply).unwrap(), 2);
+    assert_eq!(summary.contributions[0].pages, [1]);
+    assert_eq!(summary.datasets[0].text, "20 Newsgroups");
+    assert!(summary.method.is_empty() && summary.limitations.is_empty());
+}
@@ -0,0 +1,91 @@
+use super::PageText;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub text: String,
+    pub page: Option<u32>,
+    // Byte range of the chunk within its page
+    pub start: usize,
+    pub end: usize,
+}
+
+// Preferred break points, strongest first
+const SEPARATORS: &[&str] = &["\n\n", ". ", "\n", " "];
+
+fn floor_boundary(text: &str, mut i: usize) -> usize {
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn ceil_boundary(text: &str, mut i: usize) -> usize {
+    while i < text.len() && !text.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+// Windows of roughly `size` bytes that end on a paragraph, sentence or word break where possible
+// and repeat the last `overlap` bytes of the previous window
+pub fn chunk_ranges(text: &str, size: usize, overlap: usize) -> Vec<(usize, usize)> {
+    let size = size.max(1);
+    let overlap = overlap.min(size / 2);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start < text.len() {
+        let mut end = floor_boundary(text, (start + size).min(text.len()));
+        if end <= start {
+            end = ceil_boundary(text, start + 1);
+        }
+        if end < text.len() {
+            let window = &text[start..end];
+            let break_at = SEPARATORS.iter().find_map(|separator| {
+                window
+                    .rfind(separator)
+                    .filter(|&i| i >= window.len() / 2)
+                    .map(|i| i + separator.len())
+            });
+            if let Some(break_at) = break_at {
+                end = start + break_at;
+            }
+        }
+        ranges.push((start, end));
+        if end >= text.len() {
+            break;
+        }
+
+        let mut next = floor_boundary(text, end - overlap);
+        if overlap > 0 {
+            // Start the overlap on a word boundary
+            if let Some((i, c)) = text[next..end].char_indices().find(|(_, c)| c.is_whitespace()) {
+                next += i + c.len_utf8();
+            }
+        }
+        start = if next > start { next } else { end };
+    }
+
+    ranges
+}
+
+pub fn chunk_pages(pages: &[PageText], size: usize, overlap: usize) -> Vec<TextChunk> {
+    let mut chunks = Vec::new();
+    for page in pages {
+        for (start, end) in chunk_ranges(&page.text, size, overlap) {
+            let raw = &page.text[start..end];
+            let text = raw.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let start = start + (raw.len() - raw.trim_start().len());
+            chunks.push(TextChunk {
+                text: text.to_string(),
+                page: page.page,
+                start,
+                end: start + text.len(),
+            });
+        }
+    }
+    chunks
+}
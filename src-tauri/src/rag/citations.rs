@@ -0,0 +1,189 @@
+use super::{extract_text, RetrievedChunk};
+use crate::chat::split_reasoning;
+use crate::search::terms;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationStatus {
+    Resolved,
+    // The number matches none of the excerpts given to the model
+    NotRetrieved,
+}
+
+// The part of a retrieved chunk that backs a claim; offsets are into the chunk's page text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CitedSource {
+    pub chunk_id: String,
+    pub document: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub start: usize,
+    pub end: usize,
+    pub quote: String,
+}
+
+// One [n] marker in an answer and what it points at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    pub number: usize,
+    // Byte range of the marker within the message content
+    pub marker_start: usize,
+    pub marker_end: usize,
+    pub claim: String,
+    pub status: CitationStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<CitedSource>,
+}
+
+// A source passage with the cited quote located inside it
+#[derive(Debug, Clone, Serialize)]
+pub struct SourcePassage {
+    pub document: String,
+    pub page: Option<u32>,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    // The document has changed or can't be read; text is only the quote stored with the answer
+    pub stale: bool,
+}
+
+fn marker_regex() -> Regex {
+    Regex::new(r"\[(\d+(?:\s*[,;–-]\s*\d+)*)\]").unwrap()
+}
+
+// Expands "1, 3-4" into [1, 3, 4]
+fn marker_numbers(inner: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for part in inner.split([',', ';']) {
+        let bounds: Vec<usize> = part
+            .split(['-', '–'])
+            .filter_map(|n| n.trim().parse().ok())
+            .collect();
+        match bounds.as_slice() {
+            [n] => numbers.push(*n),
+            [from, to] if from <= to && to - from < 20 => numbers.extend(*from..=*to),
+            _ => {}
+        }
+    }
+    numbers
+}
+
+// The sentence a marker closes, without any citation markers of its own
+fn claim_before(answer: &str, marker_start: usize, markers: &Regex) -> String {
+    let before = answer[..marker_start].trim_end();
+    // "evidence. [1]" cites the sentence that just ended
+    let body = before.trim_end_matches(['.', '!', '?', ':']);
+    let start = body
+        .rfind(['.', '!', '?', '\n'])
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let claim = markers.replace_all(&before[start..], "");
+    claim.trim().to_string()
+}
+
+// The sentence of the chunk sharing the most terms with the claim, as a byte range of the chunk
+fn best_quote(chunk: &str, claim: &str) -> (usize, usize) {
+    let claim_terms = terms(claim);
+    let mut best = (0, chunk.len(), 0);
+    let mut start = 0;
+    for (i, c) in chunk.char_indices() {
+        let boundary = matches!(c, '.' | '!' | '?' | '\n');
+        let end = i + c.len_utf8();
+        if boundary || end == chunk.len() {
+            let sentence = &chunk[start..end];
+            let overlap = terms(sentence).iter().filter(|t| claim_terms.contains(t)).count();
+            if overlap > best.2 {
+                best = (start, end, overlap);
+            }
+            start = end;
+        }
+    }
+    let quote = &chunk[best.0..best.1];
+    let leading = quote.len() - quote.trim_start().len();
+    (best.0 + leading, best.0 + quote.trim_end().len())
+}
+
+fn cited_source(chunk: &RetrievedChunk, claim: &str) -> CitedSource {
+    let (start, end) = best_quote(&chunk.text, claim);
+    CitedSource {
+        chunk_id: chunk.chunk_id.clone(),
+        document: chunk.document.clone(),
+        page: chunk.page,
+        start: chunk.start + start,
+        end: chunk.start + end,
+        quote: chunk.text[start..end].to_string(),
+    }
+}
+
+// Finds the [n] markers in the visible answer; `chunks` are in the order they were numbered
+pub fn parse_citations(content: &str, chunks: &[RetrievedChunk]) -> Vec<Citation> {
+    let (_, answer) = split_reasoning(content);
+    let offset = answer.as_ptr() as usize - content.as_ptr() as usize;
+    let markers = marker_regex();
+
+    let mut found = Vec::new();
+    for captures in markers.captures_iter(answer) {
+        let inner = captures.get(1).unwrap();
+        let marker_start = inner.start() - 1;
+        let marker_end = inner.end() + 1;
+        // "[1](...)" is a Markdown link, not a citation
+        if answer[marker_end..].starts_with('(') {
+            continue;
+        }
+        let claim = claim_before(answer, marker_start, &markers);
+        for number in marker_numbers(inner.as_str()) {
+            let chunk = number.checked_sub(1).and_then(|i| chunks.get(i));
+            found.push(Citation {
+                number,
+                marker_start: offset + marker_start,
+                marker_end: offset + marker_end,
+                status: match chunk {
+                    Some(_) => CitationStatus::Resolved,
+                    None => CitationStatus::NotRetrieved,
+                },
+                source: chunk.map(|c| cited_source(c, &claim)),
+                claim: claim.clone(),
+            });
+        }
+    }
+    found
+}
+
+// Re-reads the cited page so the quote can be shown in context
+pub fn locate_source(source: &CitedSource) -> SourcePassage {
+    let stale = || SourcePassage {
+        document: source.document.clone(),
+        page: source.page,
+        text: source.quote.clone(),
+        start: 0,
+        end: source.quote.len(),
+        stale: true,
+    };
+    let Ok(pages) = extract_text(Path::new(&source.document)) else {
+        return stale();
+    };
+    let Some(page) = pages.into_iter().find(|p| p.page == source.page) else {
+        return stale();
+    };
+
+    let (start, end) = if page.text.get(source.start..source.end) == Some(source.quote.as_str()) {
+        (source.start, source.end)
+    } else {
+        // The document was edited since indexing; look for the quote elsewhere on the page
+        match page.text.find(&source.quote) {
+            Some(start) => (start, start + source.quote.len()),
+            None => return stale(),
+        }
+    };
+    SourcePassage {
+        document: source.document.clone(),
+        page: source.page,
+        text: page.text,
+        start,
+        end,
+        stale: false,
+    }
+}
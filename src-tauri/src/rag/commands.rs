@@ -0,0 +1,153 @@
+use super::{collect_documents, DocumentIndex, IndexedDocument, IngestOutcome, RetrievedChunk};
+use crate::bibliography::Library;
+use crate::vector::MetadataFilter;
+use crate::AppState;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::Emitter;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
+
+pub const INGEST_PROGRESS_EVENT: &str = "ingest-progress";
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestProgress {
+    pub path: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedDocument {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct IngestReport {
+    pub indexed: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub failed: Vec<FailedDocument>,
+}
+
+async fn document_index(app_state: &tauri::State<'_, Mutex<AppState>>) -> Result<Arc<DocumentIndex>, String> {
+    app_state
+        .lock()
+        .await
+        .documents
+        .clone()
+        .ok_or_else(|| "Document retrieval is not configured; add a [rag] section to the config".to_string())
+}
+
+// Library entries are matched to documents by file name, so they're re-linked when the index changes
+async fn relink_library(app_state: &tauri::State<'_, Mutex<AppState>>, index: &DocumentIndex) {
+    let library: Option<Arc<Library>> = app_state.lock().await.library.clone();
+    if let Some(library) = library {
+        library.relink(index).await;
+    }
+}
+
+// Indexes a folder or a single file; opens a folder picker when no path is given
+#[tauri::command]
+pub async fn ingest_documents(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    path: Option<String>,
+) -> Result<Option<IngestReport>, String> {
+    let index = document_index(&app_state).await?;
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let (tx, rx) = oneshot::channel();
+            app.dialog().file().pick_folder(move |path| {
+                let _ = tx.send(path);
+            });
+            let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?
+        }
+    };
+
+    let mut files = Vec::new();
+    collect_documents(&path, &mut files);
+
+    let mut report = IngestReport::default();
+    for (i, file) in files.iter().enumerate() {
+        let display = file.display().to_string();
+        let progress = IngestProgress {
+            path: display.clone(),
+            done: i,
+            total: files.len(),
+        };
+        if let Err(e) = app.emit(INGEST_PROGRESS_EVENT, progress) {
+            log::warn!("Failed to emit ingest progress: {}", e);
+        }
+        match index.ingest_file(file).await {
+            Ok(IngestOutcome::Indexed) => report.indexed.push(display),
+            Ok(IngestOutcome::Unchanged) => report.unchanged.push(display),
+            Err(reason) => report.failed.push(FailedDocument {
+                path: display,
+                reason,
+            }),
+        }
+    }
+    if !report.indexed.is_empty() {
+        relink_library(&app_state, &index).await;
+    }
+
+    Ok(Some(report))
+}
+
+#[tauri::command]
+pub async fn list_indexed_documents(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<IndexedDocument>, String> {
+    Ok(document_index(&app_state).await?.documents().await)
+}
+
+#[tauri::command]
+pub async fn remove_indexed_document(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    path: String,
+) -> Result<bool, String> {
+    let index = document_index(&app_state).await?;
+    let removed = index.remove_file(&path).await?;
+    if removed {
+        relink_library(&app_state, &index).await;
+    }
+    Ok(removed)
+}
+
+// Hybrid search over the indexed chunks, optionally narrowed by chunk metadata
+#[tauri::command]
+pub async fn search_documents(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    query: String,
+    filter: Option<MetadataFilter>,
+    limit: Option<usize>,
+) -> Result<Vec<RetrievedChunk>, String> {
+    let index = document_index(&app_state).await?;
+    index
+        .search(&query, &filter.unwrap_or_default(), limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .await
+}
+
+#[tauri::command]
+pub async fn reindex_documents(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<(), String> {
+    document_index(&app_state).await?.reindex().await
+}
+
+// Re-checks the watched folders for changes; returns the folders being scanned
+#[tauri::command]
+pub async fn rescan_watched_folders(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<Vec<String>, String> {
+    let holder = app_state.lock().await;
+    let watcher = holder
+        .folder_watcher
+        .as_ref()
+        .ok_or_else(|| "No watched folders are configured; add watchedFolders under [rag]".to_string())?;
+    watcher.rescan();
+    Ok(watcher.folders().iter().map(|f| f.display().to_string()).collect())
+}
@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct PageText {
+    // 1-based page number for paginated formats
+    pub page: Option<u32>,
+    pub text: String,
+}
+
+const TEXT_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "text"];
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    let extension = extension(path);
+    extension == "pdf" || TEXT_EXTENSIONS.contains(&extension.as_str())
+}
+
+// Supported files under a folder, skipping hidden entries
+pub fn collect_documents(path: &Path, found: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        if is_supported(path) {
+            found.push(path.to_path_buf());
+        }
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if !hidden {
+            collect_documents(&entry, found);
+        }
+    }
+}
+
+// Blocking; PDF parsing in particular can take a while on large papers
+pub fn extract_text(path: &Path) -> Result<Vec<PageText>, String> {
+    let extension = extension(path);
+    if extension == "pdf" {
+        // pdf-extract panics on some malformed files instead of returning an error
+        let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_by_pages(path))
+            .map_err(|_| format!("PDF parser crashed on {}", path.display()))?
+            .map_err(|e| format!("Failed to extract text from {}: {}", path.display(), e))?;
+        return Ok(pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| PageText {
+                page: Some(i as u32 + 1),
+                text,
+            })
+            .collect());
+    }
+    if TEXT_EXTENSIONS.contains(&extension.as_str()) {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return Ok(vec![PageText { page: None, text }]);
+    }
+    Err(format!("Unsupported document type: {}", path.display()))
+}
@@ -0,0 +1,283 @@
+use super::{chunk_pages, extract_text};
+use crate::budget::BudgetedEmbedder;
+use crate::config::RagConfig;
+use crate::chat::new_id;
+use crate::vector::{normalize, HybridHit, HybridQuery, Metadata, MetadataFilter, VectorCollection, VectorRecord};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDocument {
+    pub path: String,
+    pub modified: i64,
+    pub size: u64,
+    // SHA-256 of the file, so a touched but unchanged file isn't embedded again
+    #[serde(default)]
+    pub hash: String,
+    pub chunk_count: usize,
+    pub indexed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievedChunk {
+    pub chunk_id: String,
+    pub document: String,
+    pub page: Option<u32>,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    // Reciprocal-rank fusion of the vector and BM25 rankings
+    pub score: f64,
+    // Cite key of the library entry linked to the document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cite_key: Option<String>,
+}
+
+impl From<HybridHit> for RetrievedChunk {
+    fn from(hit: HybridHit) -> Self {
+        let field = |key: &str| hit.record.metadata.get(key).and_then(|v| v.parse().ok());
+        RetrievedChunk {
+            document: hit.record.metadata.get("document").cloned().unwrap_or_default(),
+            page: field("page").map(|p: usize| p as u32),
+            start: field("start").unwrap_or(0),
+            end: field("end").unwrap_or(0),
+            chunk_id: hit.record.id,
+            text: hit.record.text,
+            score: hit.score,
+            cite_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestOutcome {
+    Indexed,
+    Unchanged,
+}
+
+fn file_stamp(path: &Path) -> Result<(i64, u64), String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    Ok((modified, metadata.len()))
+}
+
+fn file_hash(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+// Ingested files and their chunks; chunk metadata carries document, page, start, end and extension
+pub struct DocumentIndex {
+    config: RagConfig,
+    embedder: Arc<BudgetedEmbedder>,
+    catalog_path: PathBuf,
+    vectors_path: PathBuf,
+    documents: Mutex<Vec<IndexedDocument>>,
+    vectors: Mutex<VectorCollection>,
+}
+
+impl DocumentIndex {
+    pub fn load(config: RagConfig, embedder: Arc<BudgetedEmbedder>, data_dir: &Path) -> Self {
+        let model = embedder.embedding_model().unwrap_or_default().to_string();
+        let catalog_path = data_dir.join("documents.json");
+        let vectors_path = data_dir.join("document-vectors.json");
+        let vectors = VectorCollection::load(&vectors_path, &model);
+        // The catalog is only meaningful alongside the chunks it describes
+        let documents = match vectors.is_empty() {
+            true => Vec::new(),
+            false => fs::read_to_string(&catalog_path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default(),
+        };
+        Self {
+            config,
+            embedder,
+            catalog_path,
+            vectors_path,
+            documents: Mutex::new(documents),
+            vectors: Mutex::new(vectors),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        self.embedder.embed(texts).await.map_err(|e| e.error)
+    }
+
+    fn save(&self, documents: &[IndexedDocument], vectors: &mut VectorCollection) -> Result<(), String> {
+        if vectors.needs_compaction() {
+            vectors.reindex();
+        }
+        vectors.save(&self.vectors_path)?;
+        let content = serde_json::to_string(documents).map_err(|e| e.to_string())?;
+        fs::write(&self.catalog_path, content).map_err(|e| format!("Failed to save document catalog: {}", e))
+    }
+
+    pub async fn documents(&self) -> Vec<IndexedDocument> {
+        self.documents.lock().await.clone()
+    }
+
+    fn document_filter(path: &str) -> MetadataFilter {
+        MetadataFilter([("document".to_string(), vec![path.to_string()])].into())
+    }
+
+    // Extracts, chunks and embeds a file, replacing any earlier version of it in the index
+    pub async fn ingest_file(&self, path: &Path) -> Result<IngestOutcome, String> {
+        let key = path.display().to_string();
+        let (modified, size) = file_stamp(path)?;
+        let unchanged = self
+            .documents
+            .lock()
+            .await
+            .iter()
+            .any(|d| d.path == key && d.modified == modified && d.size == size);
+        if unchanged {
+            return Ok(IngestOutcome::Unchanged);
+        }
+
+        let hash = file_hash(path)?;
+        {
+            let mut documents = self.documents.lock().await;
+            if let Some(document) = documents.iter_mut().find(|d| d.path == key && d.hash == hash) {
+                document.modified = modified;
+                document.size = size;
+                let mut vectors = self.vectors.lock().await;
+                self.save(&documents, &mut vectors)?;
+                return Ok(IngestOutcome::Unchanged);
+            }
+        }
+
+        let owned_path = path.to_path_buf();
+        let pages = tauri::async_runtime::spawn_blocking(move || extract_text(&owned_path))
+            .await
+            .map_err(|e| e.to_string())??;
+        let chunks = chunk_pages(&pages, self.config.chunk_size, self.config.chunk_overlap);
+        let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let embeddings = self.embed(&texts).await?;
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let mut documents = self.documents.lock().await;
+        let mut vectors = self.vectors.lock().await;
+        vectors.remove_where(&Self::document_filter(&key));
+        documents.retain(|d| d.path != key);
+        documents.push(IndexedDocument {
+            path: key.clone(),
+            modified,
+            size,
+            hash,
+            chunk_count: chunks.len(),
+            indexed_at: chrono::Utc::now().timestamp(),
+        });
+        for (chunk, embedding) in chunks.into_iter().zip(embeddings) {
+            let mut metadata = Metadata::new();
+            metadata.insert("document".to_string(), key.clone());
+            metadata.insert("extension".to_string(), extension.clone());
+            metadata.insert("start".to_string(), chunk.start.to_string());
+            metadata.insert("end".to_string(), chunk.end.to_string());
+            if let Some(page) = chunk.page {
+                metadata.insert("page".to_string(), page.to_string());
+            }
+            let record = VectorRecord {
+                id: new_id(),
+                text: chunk.text,
+                metadata,
+            };
+            vectors.upsert(record, embedding)?;
+        }
+        self.save(&documents, &mut vectors)?;
+        Ok(IngestOutcome::Indexed)
+    }
+
+    pub async fn remove_file(&self, path: &str) -> Result<bool, String> {
+        let mut documents = self.documents.lock().await;
+        let mut vectors = self.vectors.lock().await;
+        let before = documents.len();
+        documents.retain(|d| d.path != path);
+        vectors.remove_where(&Self::document_filter(path));
+        let removed = documents.len() != before;
+        if removed {
+            self.save(&documents, &mut vectors)?;
+        }
+        Ok(removed)
+    }
+
+    // Drops documents at or below the path whose files are gone; returns their paths
+    pub async fn prune_missing(&self, path: &Path) -> Result<Vec<String>, String> {
+        let mut documents = self.documents.lock().await;
+        let mut vectors = self.vectors.lock().await;
+        let removed: Vec<String> = documents
+            .iter()
+            .filter(|d| Path::new(&d.path).starts_with(path) && !Path::new(&d.path).exists())
+            .map(|d| d.path.clone())
+            .collect();
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+        documents.retain(|d| !removed.contains(&d.path));
+        for document in &removed {
+            vectors.remove_where(&Self::document_filter(document));
+        }
+        self.save(&documents, &mut vectors)?;
+        Ok(removed)
+    }
+
+    // Rebuilds the ANN graph from the stored chunks without re-embedding anything
+    pub async fn reindex(&self) -> Result<(), String> {
+        let documents = self.documents.lock().await;
+        let mut vectors = self.vectors.lock().await;
+        vectors.reindex();
+        self.save(&documents, &mut vectors)
+    }
+
+    // The configured number of chunks best matching the query by meaning and by keywords
+    pub async fn retrieve(&self, query: &str) -> Result<Vec<RetrievedChunk>, String> {
+        self.search(query, &MetadataFilter::default(), self.config.top_k).await
+    }
+
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: &MetadataFilter,
+        top_k: usize,
+    ) -> Result<Vec<RetrievedChunk>, String> {
+        if self.vectors.lock().await.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedding = self
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| "No embedding returned for the query".to_string())?;
+        let embedding = normalize(embedding);
+
+        let hybrid = HybridQuery {
+            text: query,
+            vector: Some(&embedding),
+            top_k,
+            min_score: self.config.min_score,
+        };
+        let vectors = self.vectors.lock().await;
+        Ok(vectors
+            .search(&hybrid, |record| filter.matches(&record.metadata))
+            .into_iter()
+            .map(RetrievedChunk::from)
+            .collect())
+    }
+}
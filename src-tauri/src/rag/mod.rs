@@ -0,0 +1,17 @@
+mod chunker;
+pub use chunker::*;
+mod extract;
+pub use extract::*;
+mod index;
+pub use index::*;
+mod retrieval;
+pub use retrieval::*;
+mod citations;
+pub use citations::*;
+mod watcher;
+pub use watcher::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,92 @@
+use super::{Citation, RetrievedChunk};
+use crate::llm_bridge::{LLMResponse, Prompt};
+use serde::Serialize;
+use std::path::Path;
+
+const EXCERPT_LENGTH: usize = 300;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceCitation {
+    // The [n] the chunk was numbered with in the prompt
+    pub number: usize,
+    pub chunk_id: String,
+    pub document: String,
+    pub page: Option<u32>,
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+    pub excerpt: String,
+}
+
+// A completion together with the document chunks it was grounded in
+#[derive(Debug, Serialize)]
+pub struct GroundedResponse {
+    #[serde(flatten)]
+    pub response: LLMResponse,
+    pub sources: Vec<SourceCitation>,
+    pub citations: Vec<Citation>,
+    // Cite keys of the library entries the answer refers to
+    pub references: Vec<String>,
+}
+
+fn source_label(chunk: &RetrievedChunk) -> String {
+    let name = Path::new(&chunk.document)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| chunk.document.clone());
+    let name = match &chunk.cite_key {
+        Some(key) => format!("@{}, {}", key, name),
+        None => name,
+    };
+    match chunk.page {
+        Some(page) => format!("{}, page {}", name, page),
+        None => name,
+    }
+}
+
+pub fn grounding_prompt(chunks: &[RetrievedChunk]) -> Prompt {
+    let mut content = String::from(
+        "Use the numbered excerpts from the user's documents below when they are relevant to the question. \
+         Put the number of the excerpt supporting each claim right after it, like [2] or [1, 3], and only cite numbers listed below. \
+         If they don't contain the answer, say so before answering from general knowledge.",
+    );
+    if chunks.iter().any(|c| c.cite_key.is_some()) {
+        content.push_str(" Excerpts from papers in the user's library start with the paper's cite key; refer to the paper itself as [@key].");
+    }
+    for (i, chunk) in chunks.iter().enumerate() {
+        content.push_str(&format!("\n\n[{}] {}\n{}", i + 1, source_label(chunk), chunk.text));
+    }
+    Prompt {
+        role: "system".to_string(),
+        content,
+    }
+}
+
+// Puts the excerpts right before the latest message so they sit next to the question
+pub fn ground_messages(mut messages: Vec<Prompt>, chunks: &[RetrievedChunk]) -> Vec<Prompt> {
+    if !chunks.is_empty() {
+        let position = messages.len().saturating_sub(1);
+        messages.insert(position, grounding_prompt(chunks));
+    }
+    messages
+}
+
+pub fn citations(chunks: &[RetrievedChunk]) -> Vec<SourceCitation> {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| SourceCitation {
+            number: i + 1,
+            chunk_id: chunk.chunk_id.clone(),
+            document: chunk.document.clone(),
+            page: chunk.page,
+            start: chunk.start,
+            end: chunk.end,
+            score: chunk.score,
+            excerpt: match chunk.text.char_indices().nth(EXCERPT_LENGTH) {
+                Some((end, _)) => format!("{}…", &chunk.text[..end]),
+                None => chunk.text.clone(),
+            },
+        })
+        .collect()
+}
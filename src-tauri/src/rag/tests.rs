@@ -0,0 +1,175 @@
+use super::*;
+
+fn page(text: &str) -> PageText {
+    PageText {
+        page: Some(3),
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn short_text_is_a_single_chunk() {
+    let chunks = chunk_pages(&[page("  A short note.  ")], 100, 20);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].text, "A short note.");
+    assert_eq!((chunks[0].start, chunks[0].end), (2, 15));
+    assert_eq!(chunks[0].page, Some(3));
+}
+
+#[test]
+fn chunks_break_at_paragraphs_and_overlap() {
+    let text = format!("{}\n\n{}", "alpha ".repeat(15).trim(), "beta ".repeat(30).trim());
+    let ranges = chunk_ranges(&text, 120, 30);
+    assert!(ranges.len() > 1);
+    // The first window ends at the paragraph break rather than mid-word
+    assert!(text[..ranges[0].1].ends_with("\n\n"));
+    for pair in ranges.windows(2) {
+        assert!(pair[1].0 < pair[0].1, "consecutive chunks should overlap");
+        assert!(pair[1].0 > pair[0].0, "chunking must make progress");
+    }
+    assert_eq!(ranges.last().unwrap().1, text.len());
+}
+
+#[test]
+fn chunking_respects_utf8_boundaries() {
+    let text = "é".repeat(500);
+    for (start, end) in chunk_ranges(&text, 101, 17) {
+        assert!(text.is_char_boundary(start) && text.is_char_boundary(end));
+    }
+}
+
+#[test]
+fn chunk_offsets_point_into_the_page() {
+    let text = "First paragraph about priors.\n\nSecond paragraph about posteriors and evidence.";
+    for chunk in chunk_pages(&[page(text)], 40, 10) {
+        assert_eq!(&text[chunk.start..chunk.end], chunk.text);
+    }
+}
+
+#[test]
+fn grounding_numbers_excerpts_before_the_question() {
+    let retrieved = vec![RetrievedChunk {
+        chunk_id: "c1".to_string(),
+        document: "/papers/vi.pdf".to_string(),
+        page: Some(4),
+        start: 0,
+        end: 10,
+        text: "The ELBO lower-bounds the evidence.".to_string(),
+        score: 0.9,
+        cite_key: None,
+    }];
+    let messages = vec![crate::llm_bridge::Prompt {
+        role: "user".to_string(),
+        content: "What is the ELBO?".to_string(),
+    }];
+
+    let grounded = ground_messages(messages, &retrieved);
+    assert_eq!(grounded.len(), 2);
+    assert_eq!(grounded[0].role, "system");
+    assert!(grounded[0].content.contains("[1] vi.pdf, page 4\nThe ELBO"));
+    assert_eq!(grounded[1].content, "What is the ELBO?");
+    assert_eq!(citations(&retrieved)[0].number, 1);
+}
+
+fn retrieved(id: &str, text: &str, start: usize) -> RetrievedChunk {
+    RetrievedChunk {
+        chunk_id: id.to_string(),
+        document: "/papers/vi.pdf".to_string(),
+        page: Some(2),
+        start,
+        end: start + text.len(),
+        text: text.to_string(),
+        score: 0.5,
+        cite_key: None,
+    }
+}
+
+#[test]
+fn citations_resolve_to_quotes_and_flag_unknown_numbers() {
+    let chunks = vec![
+        retrieved("c1", "Inference is hard. The ELBO lower-bounds the log evidence.", 100),
+        retrieved("c2", "Mean-field families factorize over latent variables.", 400),
+    ];
+    let answer = "<think>Maybe cite [9].</think>\n\nThe ELBO bounds the evidence [1]. \
+                  Mean-field posteriors factorize.[2, 3] See [the paper](https://x.org) and [1](https://y.org).";
+
+    let found = parse_citations(answer, &chunks);
+    let numbers: Vec<usize> = found.iter().map(|c| c.number).collect();
+    assert_eq!(numbers, [1, 2, 3]);
+
+    let first = &found[0];
+    assert_eq!(&answer[first.marker_start..first.marker_end], "[1]");
+    assert_eq!(first.claim, "The ELBO bounds the evidence");
+    let source = first.source.as_ref().unwrap();
+    assert_eq!(source.quote, "The ELBO lower-bounds the log evidence.");
+    assert_eq!(source.start, 100 + "Inference is hard. ".len());
+
+    assert_eq!(found[1].claim, "Mean-field posteriors factorize.");
+    assert_eq!(found[1].status, CitationStatus::Resolved);
+    assert_eq!(found[2].status, CitationStatus::NotRetrieved);
+    assert!(found[2].source.is_none());
+}
+
+#[test]
+fn cited_sources_are_found_again_in_edited_documents() {
+    let dir = std::env::temp_dir().join(format!("ccru-citations-{}", crate::chat::new_id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("notes.md");
+    std::fs::write(&path, "Intro.\nThe ELBO lower-bounds the log evidence.\n").unwrap();
+
+    let document = path.display().to_string();
+    let mut source = CitedSource {
+        chunk_id: "c1".to_string(),
+        document,
+        page: None,
+        start: 7,
+        end: 46,
+        quote: "The ELBO lower-bounds the log evidence.".to_string(),
+    };
+    let passage = locate_source(&source);
+    assert!(!passage.stale);
+    assert_eq!(&passage.text[passage.start..passage.end], source.quote);
+
+    std::fs::write(&path, "A longer introduction.\nThe ELBO lower-bounds the log evidence.\n").unwrap();
+    let passage = locate_source(&source);
+    assert_eq!((passage.start, passage.stale), (23, false));
+
+    source.quote = "Removed sentence.".to_string();
+    assert!(locate_source(&source).stale);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn manifest_skips_unchanged_files_and_prunes_deleted_ones() {
+    let dir = std::env::temp_dir().join(format!("ccru-manifest-{}", crate::chat::new_id()));
+    let papers = dir.join("papers");
+    std::fs::create_dir_all(&papers).unwrap();
+    let note = papers.join("elbo.md");
+    std::fs::write(&note, "The ELBO lower-bounds the log evidence.").unwrap();
+
+    let script: crate::llm_bridge::MockScript = toml::from_str("[fallback]\ncontent = \"ok\"").unwrap();
+    let embedder = std::sync::Arc::new(crate::budget::BudgetedEmbedder {
+        bridge: std::sync::Arc::new(crate::llm_bridge::MockBridge::new(script).unwrap()),
+        provider: "mock".to_string(),
+        budget: None,
+        app: None,
+    });
+    let config: crate::config::RagConfig = toml::from_str("enabled = true").unwrap();
+    let index = DocumentIndex::load(config.clone(), embedder.clone(), &dir);
+    assert_eq!(index.ingest_file(&note).await.unwrap(), IngestOutcome::Indexed);
+
+    // Same content with a new modification time is recognised by its hash
+    let later = std::time::SystemTime::now() + std::time::Duration::from_secs(120);
+    std::fs::File::options().write(true).open(&note).unwrap().set_modified(later).unwrap();
+    let reloaded = DocumentIndex::load(config, embedder, &dir);
+    assert_eq!(reloaded.ingest_file(&note).await.unwrap(), IngestOutcome::Unchanged);
+
+    std::fs::write(&note, "Mean-field families factorize.").unwrap();
+    assert_eq!(reloaded.ingest_file(&note).await.unwrap(), IngestOutcome::Indexed);
+
+    std::fs::remove_file(&note).unwrap();
+    let removed = reloaded.prune_missing(&papers).await.unwrap();
+    assert_eq!(removed, [note.display().to_string()]);
+    assert!(reloaded.documents().await.is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
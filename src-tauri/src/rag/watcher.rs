@@ -0,0 +1,178 @@
+use super::{collect_documents, is_supported, DocumentIndex, IngestOutcome};
+use crate::bibliography::Library;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::mpsc;
+
+pub const INDEX_STATUS_EVENT: &str = "index-status";
+
+// Editors save in bursts of events; changes are handled once the folder has been quiet this long
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexState {
+    Indexing,
+    Indexed,
+    Unchanged,
+    Removed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub path: String,
+    pub state: IndexState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Files still waiting in this batch
+    pub pending: usize,
+}
+
+// Keeps the document index in step with the watched folders until dropped
+pub struct FolderWatcher {
+    folders: Vec<PathBuf>,
+    changes: mpsc::UnboundedSender<Vec<PathBuf>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl FolderWatcher {
+    pub fn start(
+        app: tauri::AppHandle,
+        index: Arc<DocumentIndex>,
+        library: Option<Arc<Library>>,
+        folders: Vec<PathBuf>,
+    ) -> Result<Self, String> {
+        let (changes, receiver) = mpsc::unbounded_channel();
+        let events = changes.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                let _ = events.send(event.paths);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("File watcher error: {}", e),
+        })
+        .map_err(|e| format!("Failed to start the file watcher: {}", e))?;
+        for folder in &folders {
+            if let Err(e) = watcher.watch(folder, RecursiveMode::Recursive) {
+                log::warn!("Not watching {}: {}", folder.display(), e);
+            }
+        }
+
+        tauri::async_runtime::spawn(process_changes(app, index, library, receiver));
+        let watcher = Self {
+            folders,
+            changes,
+            _watcher: watcher,
+        };
+        // Catch up on whatever changed while the app was closed
+        watcher.rescan();
+        Ok(watcher)
+    }
+
+    pub fn folders(&self) -> &[PathBuf] {
+        &self.folders
+    }
+
+    pub fn rescan(&self) {
+        let _ = self.changes.send(self.folders.clone());
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'))
+}
+
+// The files to (re)index for a changed path, and whether indexed files below it may have gone
+fn expand(path: &Path) -> (Vec<PathBuf>, bool) {
+    if path.is_dir() {
+        let mut files = Vec::new();
+        collect_documents(path, &mut files);
+        (files, true)
+    } else if path.is_file() {
+        let wanted = is_supported(path) && !is_hidden(path);
+        (if wanted { vec![path.to_path_buf()] } else { Vec::new() }, false)
+    } else {
+        (Vec::new(), true)
+    }
+}
+
+async fn process_changes(
+    app: tauri::AppHandle,
+    index: Arc<DocumentIndex>,
+    library: Option<Arc<Library>>,
+    mut receiver: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+) {
+    let emit = |status: IndexStatus| {
+        if let Err(e) = app.emit(INDEX_STATUS_EVENT, status) {
+            log::warn!("Failed to emit index status: {}", e);
+        }
+    };
+
+    while let Some(first) = receiver.recv().await {
+        let mut changed: BTreeSet<PathBuf> = first.into_iter().collect();
+        while let Ok(Some(more)) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+            changed.extend(more);
+        }
+
+        let mut files: BTreeSet<PathBuf> = BTreeSet::new();
+        let mut relink = false;
+        for path in &changed {
+            let (found, check_removed) = expand(path);
+            files.extend(found);
+            if !check_removed {
+                continue;
+            }
+            let removed = match index.prune_missing(path).await {
+                Ok(removed) => removed,
+                Err(e) => {
+                    log::warn!("Failed to update the document index: {}", e);
+                    continue;
+                }
+            };
+            relink |= !removed.is_empty();
+            for document in removed {
+                emit(IndexStatus {
+                    path: document,
+                    state: IndexState::Removed,
+                    error: None,
+                    pending: files.len(),
+                });
+            }
+        }
+
+        let total = files.len();
+        for (i, file) in files.into_iter().enumerate() {
+            let path = file.display().to_string();
+            let pending = total - i - 1;
+            emit(IndexStatus {
+                path: path.clone(),
+                state: IndexState::Indexing,
+                error: None,
+                pending,
+            });
+            let (state, error) = match index.ingest_file(&file).await {
+                Ok(IngestOutcome::Indexed) => {
+                    relink = true;
+                    (IndexState::Indexed, None)
+                }
+                Ok(IngestOutcome::Unchanged) => (IndexState::Unchanged, None),
+                Err(e) => (IndexState::Failed, Some(e)),
+            };
+            emit(IndexStatus {
+                path,
+                state,
+                error,
+                pending,
+            });
+        }
+        if let Some(library) = library.as_ref().filter(|_| relink) {
+            library.relink(&index).await;
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::chat::{new_id, save_file, ChatHistory};
+use crate::review::{ReportFormat, ReviewRun, ReviewStage, ReviewStore, ReviewSummary, Reviewer};
+use crate::AppState;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn review_store(holder: &AppState) -> Result<Arc<ReviewStore>, String> {
+    holder
+        .reviews
+        .clone()
+        .ok_or_else(|| "Reviews are not loaded yet".to_string())
+}
+
+fn reviewer(app: tauri::AppHandle, holder: &mut AppState) -> Result<Reviewer, String> {
+    let config = holder.config.as_ref().ok_or("The configuration is not loaded yet")?;
+    Ok(Reviewer {
+        app,
+        provider: config.provider().to_string(),
+        llm_bridge: holder.llm_bridge.clone().ok_or("No LLM provider is configured")?,
+        budget: holder.budget.clone(),
+        library: holder.library.clone(),
+        chat_history: holder
+            .chat_history
+            .get_or_insert_with(|| Arc::new(ChatHistory::new()))
+            .clone(),
+        store: review_store(holder)?,
+    })
+}
+
+// Starts a review in the background and returns it right away; progress comes as review-progress events.
+// Without `documents` every ingested document is reviewed
+#[tauri::command]
+pub async fn start_literature_review(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    question: String,
+    documents: Option<Vec<String>>,
+    format: Option<ReportFormat>,
+) -> Result<ReviewRun, String> {
+    let question = question.trim().to_string();
+    if question.is_empty() {
+        return Err("The research question cannot be empty".to_string());
+    }
+    let mut holder = app_state.lock().await;
+    let documents = match documents {
+        Some(documents) => documents,
+        None => match holder.documents.as_ref() {
+            Some(index) => index.documents().await.into_iter().map(|d| d.path).collect(),
+            None => Vec::new(),
+        },
+    };
+    if documents.is_empty() {
+        return Err("There are no documents to review".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut run = ReviewRun {
+        id: new_id(),
+        question,
+        documents,
+        format: format.unwrap_or(ReportFormat::Markdown),
+        stage: ReviewStage::Extracting,
+        papers: Vec::new(),
+        overview: None,
+        themes: Vec::new(),
+        report: None,
+        session_id: None,
+        error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    let reviewer = reviewer(app, &mut holder)?;
+    reviewer.store.save(&mut run)?;
+    tauri::async_runtime::spawn(reviewer.run(run.clone()));
+    Ok(run)
+}
+
+// Continues a failed or interrupted review from its saved artifacts
+#[tauri::command]
+pub async fn resume_literature_review(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<ReviewRun, String> {
+    let mut holder = app_state.lock().await;
+    let reviewer = reviewer(app, &mut holder)?;
+    let run = reviewer.store.load(&run_id)?;
+    if run.stage == ReviewStage::Completed {
+        return Err("The review has already completed".to_string());
+    }
+    tauri::async_runtime::spawn(reviewer.run(run.clone()));
+    Ok(run)
+}
+
+#[tauri::command]
+pub async fn list_literature_reviews(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<ReviewSummary>, String> {
+    let holder = app_state.lock().await;
+    Ok(review_store(&holder)?.list())
+}
+
+#[tauri::command]
+pub async fn get_literature_review(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<ReviewRun, String> {
+    let holder = app_state.lock().await;
+    review_store(&holder)?.load(&run_id)
+}
+
+// Writes the finished report wherever the user picks; returns None if cancelled
+#[tauri::command]
+pub async fn export_literature_review(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    run_id: String,
+) -> Result<Option<String>, String> {
+    let run = {
+        let holder = app_state.lock().await;
+        review_store(&holder)?.load(&run_id)?
+    };
+    let report = run.report.ok_or("The review hasn't finished yet")?;
+    save_file(
+        &app,
+        &format!("ccru-review-{}", run.id),
+        run.format.label(),
+        run.format.extension(),
+        report,
+    )
+    .await
+}
@@ -0,0 +1,11 @@
+mod run;
+pub use run::*;
+mod report;
+pub use report::*;
+mod pipeline;
+pub use pipeline::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,353 @@
+use super::{render_report, Finding, PaperFindings, ReviewRun, ReviewStage, ReviewStore, Theme};
+use crate::bibliography::Library;
+use crate::budget::{complete_with_budget, BudgetTracker, UsageContext};
+use crate::chat::{new_id, ChatHistory, ChatMessage, ChatSession};
+use crate::llm_bridge::{LLMBridge, LLMRequest, Prompt};
+use crate::paper::{paper_text, parse_structure};
+use crate::rag::extract_text;
+use crate::structured::{complete_typed, DEFAULT_MAX_REPAIRS};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Emitter;
+
+pub const REVIEW_PROGRESS_EVENT: &str = "review-progress";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewProgress {
+    pub run_id: String,
+    pub stage: ReviewStage,
+    // Papers done out of the total while extracting
+    pub completed: usize,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ExtractedFinding {
+    claim: String,
+    evidence: Option<String>,
+    #[serde(default)]
+    pages: Vec<u32>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ExtractionReply {
+    #[serde(default)]
+    findings: Vec<ExtractedFinding>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ClusteringReply {
+    overview: Option<String>,
+    #[serde(default)]
+    themes: Vec<Theme>,
+}
+
+// Everything a review task needs, detached from the app state so it can outlive the command
+pub struct Reviewer {
+    pub app: tauri::AppHandle,
+    pub llm_bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub budget: Option<Arc<BudgetTracker>>,
+    pub library: Option<Arc<Library>>,
+    pub chat_history: Arc<ChatHistory>,
+    pub store: Arc<ReviewStore>,
+}
+
+pub fn extraction_prompt(question: &str, title: &str, text: &str) -> Vec<Prompt> {
+    vec![
+        Prompt {
+            role: "system".to_string(),
+            content: format!(
+                "You are extracting material for a literature review on: {}\n\
+                 List the paper's claims and findings that bear on this question. Reply with only a JSON object \
+                 {{\"findings\": [{{\"claim\": \"...\", \"evidence\": \"...\", \"pages\": [3]}}]}}, where evidence is \
+                 a short quote or result backing the claim and pages are the [page n] markers it comes from. \
+                 Reply with an empty list if the paper has nothing relevant.",
+                question
+            ),
+        },
+        Prompt {
+            role: "user".to_string(),
+            content: format!("# {}{}", title, text),
+        },
+    ]
+}
+
+pub fn clustering_prompt(question: &str, papers: &[PaperFindings]) -> Vec<Prompt> {
+    let mut findings = String::new();
+    for paper in papers {
+        for finding in &paper.findings {
+            findings.push_str(&format!(
+                "[{}] ({}) {}\n",
+                finding.id,
+                paper.title.as_deref().unwrap_or(&paper.document),
+                finding.claim
+            ));
+        }
+    }
+    vec![
+        Prompt {
+            role: "system".to_string(),
+            content: format!(
+                "Group the findings below into the themes of a literature review on: {}\n\
+                 Reply with only a JSON object {{\"overview\": \"...\", \"themes\": [{{\"name\": \"...\", \
+                 \"summary\": \"...\", \"findings\": [\"1.2\", \"3.1\"]}}]}}. The overview is one paragraph answering \
+                 the question from the findings; each theme summary synthesizes its findings, noting where papers \
+                 agree or disagree. Use the ids in brackets and put every finding in exactly one theme.",
+                question
+            ),
+        },
+        Prompt {
+            role: "user".to_string(),
+            content: findings,
+        },
+    ]
+}
+
+// Themes keep only known findings, each once; whatever the model left out is collected at the end
+pub fn settle_themes(papers: &[PaperFindings], mut themes: Vec<Theme>) -> Vec<Theme> {
+    let mut assigned: Vec<String> = Vec::new();
+    for theme in &mut themes {
+        theme.findings.retain(|id| {
+            let known = papers.iter().any(|p| p.findings.iter().any(|f| &f.id == id));
+            let fresh = known && !assigned.contains(id);
+            if fresh {
+                assigned.push(id.clone());
+            }
+            fresh
+        });
+    }
+    themes.retain(|t| !t.findings.is_empty());
+    let unassigned: Vec<String> = papers
+        .iter()
+        .flat_map(|p| &p.findings)
+        .filter(|f| !assigned.contains(&f.id))
+        .map(|f| f.id.clone())
+        .collect();
+    if !unassigned.is_empty() {
+        themes.push(Theme {
+            name: "Other findings".to_string(),
+            summary: "Findings that didn't fit any of the themes above.".to_string(),
+            findings: unassigned,
+        });
+    }
+    themes
+}
+
+impl Reviewer {
+    fn emit(&self, run: &ReviewRun, completed: usize, document: Option<String>) {
+        let progress = ReviewProgress {
+            run_id: run.id.clone(),
+            stage: run.stage,
+            completed,
+            total: run.documents.len(),
+            document,
+            error: run.error.clone(),
+        };
+        if let Err(e) = self.app.emit(REVIEW_PROGRESS_EVENT, progress) {
+            log::warn!("Failed to emit review progress: {}", e);
+        }
+    }
+
+    // Replies are checked against the schema of T and sent back for repair when they don't fit
+    async fn complete<T: JsonSchema + DeserializeOwned>(&self, messages: Vec<Prompt>) -> Result<T, String> {
+        let ctx = UsageContext {
+            provider: self.provider.clone(),
+            model: self.llm_bridge.model().to_string(),
+            session_id: None,
+            agent_run_id: None,
+        };
+        let request = LLMRequest {
+            messages,
+            ..Default::default()
+        };
+        complete_typed(request, DEFAULT_MAX_REPAIRS, |request| {
+            complete_with_budget(&self.app, self.llm_bridge.as_ref(), self.budget.as_deref(), ctx.clone(), request)
+        })
+        .await
+    }
+
+    async fn extract(&self, run: &ReviewRun, number: usize, document: &str) -> Result<PaperFindings, String> {
+        let path = document.to_string();
+        let structure = tauri::async_runtime::spawn_blocking(move || {
+            extract_text(Path::new(&path)).map(|pages| parse_structure(&path, &pages))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+        let name = Path::new(document)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| document.to_string());
+        let entry = match &self.library {
+            Some(library) => library.entry_for_document(document).await,
+            None => None,
+        };
+        let title = entry
+            .as_ref()
+            .and_then(|e| e.title.clone())
+            .or_else(|| structure.title.clone());
+
+        let reply: ExtractionReply = self
+            .complete(extraction_prompt(
+                &run.question,
+                title.as_deref().unwrap_or(&name),
+                &paper_text(&structure),
+            ))
+            .await
+            .map_err(|e| format!("{}: {}", name, e))?;
+        let findings = reply
+            .findings
+            .into_iter()
+            .filter(|f| !f.claim.trim().is_empty())
+            .enumerate()
+            .map(|(i, f)| Finding {
+                id: format!("{}.{}", number, i + 1),
+                claim: f.claim.trim().to_string(),
+                evidence: f.evidence.filter(|e| !e.trim().is_empty()),
+                pages: f
+                    .pages
+                    .into_iter()
+                    .filter(|&p| p >= 1 && p as usize <= structure.page_count)
+                    .collect(),
+            })
+            .collect();
+        Ok(PaperFindings {
+            document: document.to_string(),
+            reference: match &entry {
+                Some(entry) => entry.reference(),
+                None => format!("{} ({})", title.as_deref().unwrap_or(&name), name),
+            },
+            cite_key: entry.map(|e| e.key),
+            title,
+            findings,
+            error: None,
+        })
+    }
+
+    // Picks up wherever the run stopped: papers without findings, then themes, then the report.
+    // A paper that can't be read is recorded with its error and the review goes on without it,
+    // unless no paper could be read at all
+    async fn advance(&self, run: &mut ReviewRun) -> Result<(), String> {
+        run.stage = ReviewStage::Extracting;
+        run.error = None;
+        for (i, document) in run.documents.clone().iter().enumerate() {
+            if run.papers.iter().any(|p| &p.document == document && p.error.is_none()) {
+                continue;
+            }
+            self.emit(run, run.papers.len(), Some(document.clone()));
+            let paper = match self.extract(run, i + 1, document).await {
+                Ok(paper) => {
+                    // Themes grouped before this paper came through would miss its findings
+                    run.themes.clear();
+                    paper
+                }
+                Err(e) => {
+                    log::warn!("Review {} left out {}: {}", run.id, document, e);
+                    PaperFindings::failed(document, e)
+                }
+            };
+            run.papers.retain(|p| &p.document != document);
+            run.papers.push(paper);
+            // Keep papers in the order they were given, so finding ids stay stable across resumes
+            run.papers
+                .sort_by_key(|p| run.documents.iter().position(|d| d == &p.document));
+            self.store.save(run)?;
+        }
+        self.emit(run, run.papers.len(), None);
+        if run.reviewed_papers().next().is_none() {
+            let errors: Vec<String> = run
+                .papers
+                .iter()
+                .filter_map(|p| p.error.as_ref().map(|e| format!("{}: {}", p.reference, e)))
+                .collect();
+            return Err(format!("None of the papers could be read. {}", errors.join("; ")));
+        }
+
+        if run.themes.is_empty() {
+            run.stage = ReviewStage::Clustering;
+            self.store.save(run)?;
+            self.emit(run, run.papers.len(), None);
+            let reviewed: Vec<PaperFindings> = run.reviewed_papers().cloned().collect();
+            let reply: ClusteringReply = if reviewed.iter().all(|p| p.findings.is_empty()) {
+                ClusteringReply {
+                    overview: Some("None of the papers had findings bearing on the question.".to_string()),
+                    themes: Vec::new(),
+                }
+            } else {
+                self.complete(clustering_prompt(&run.question, &reviewed)).await?
+            };
+            run.overview = reply.overview;
+            run.themes = settle_themes(&reviewed, reply.themes);
+            self.store.save(run)?;
+        }
+
+        run.stage = ReviewStage::Writing;
+        self.emit(run, run.papers.len(), None);
+        let report = render_report(run);
+        if run.session_id.is_none() {
+            run.session_id = Some(self.save_session(run, &report).await);
+        }
+        run.report = Some(report);
+        run.stage = ReviewStage::Completed;
+        self.store.save(run)
+    }
+
+    // The finished report also lands in the chat history as a session of its own
+    async fn save_session(&self, run: &ReviewRun, report: &str) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let question = ChatMessage {
+            id: new_id(),
+            role: "user".to_string(),
+            content: run.question.clone(),
+            timestamp: now,
+            ..Default::default()
+        };
+        let answer = ChatMessage {
+            id: new_id(),
+            parent_id: Some(question.id.clone()),
+            role: "assistant".to_string(),
+            content: report.to_string(),
+            timestamp: now,
+            provider: Some(self.provider.clone()),
+            model: Some(self.llm_bridge.model().to_string()),
+            ..Default::default()
+        };
+        let session = ChatSession {
+            id: new_id(),
+            title: Some(format!("Literature review: {}", run.question)),
+            tags: vec!["literature-review".to_string()],
+            messages: vec![question, answer],
+            created_at: now,
+            updated_at: now,
+            ..Default::default()
+        };
+        let session_id = session.id.clone();
+        self.chat_history.insert_session(session).await;
+        session_id
+    }
+
+    pub async fn run(self, mut run: ReviewRun) {
+        if !self.store.claim(&run.id).await {
+            log::warn!("Review {} is already running", run.id);
+            return;
+        }
+        if let Err(e) = self.advance(&mut run).await {
+            log::warn!("Review {} failed: {}", run.id, e);
+            run.stage = ReviewStage::Failed;
+            run.error = Some(e);
+            if let Err(e) = self.store.save(&mut run) {
+                log::warn!("Failed to save review {}: {}", run.id, e);
+            }
+        }
+        self.emit(&run, run.papers.len(), None);
+        self.store.release(&run.id).await;
+    }
+}
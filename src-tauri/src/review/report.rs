@@ -0,0 +1,141 @@
+use super::{Finding, PaperFindings, ReportFormat, ReviewRun};
+
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn pages(finding: &Finding) -> String {
+    match finding.pages.as_slice() {
+        [] => String::new(),
+        [page] => format!(", p. {}", page),
+        pages => {
+            let pages: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+            format!(", pp. {}", pages.join(", "))
+        }
+    }
+}
+
+// The paper a finding came from and its 1-based position in the reference list
+fn source<'a>(run: &'a ReviewRun, finding_id: &str) -> Option<(usize, &'a PaperFindings, &'a Finding)> {
+    run.reviewed_papers().enumerate().find_map(|(i, paper)| {
+        paper
+            .findings
+            .iter()
+            .find(|f| f.id == finding_id)
+            .map(|finding| (i + 1, paper, finding))
+    })
+}
+
+fn latex_key(number: usize, paper: &PaperFindings) -> String {
+    paper.cite_key.clone().unwrap_or_else(|| format!("ref{}", number))
+}
+
+// Papers that couldn't be read, with the reason, so the report says what it doesn't cover
+fn left_out(run: &ReviewRun) -> Vec<(&PaperFindings, &str)> {
+    run.papers
+        .iter()
+        .filter_map(|paper| paper.error.as_deref().map(|error| (paper, error)))
+        .collect()
+}
+
+pub fn render_report(run: &ReviewRun) -> String {
+    match run.format {
+        ReportFormat::Markdown => render_markdown(run),
+        ReportFormat::Latex => render_latex(run),
+    }
+}
+
+fn render_markdown(run: &ReviewRun) -> String {
+    let mut out = format!("# Literature review: {}\n", run.question);
+    if let Some(overview) = &run.overview {
+        out.push_str(&format!("\n{}\n", overview));
+    }
+    for theme in &run.themes {
+        out.push_str(&format!("\n## {}\n\n{}\n\n", theme.name, theme.summary));
+        for id in &theme.findings {
+            if let Some((number, _, finding)) = source(run, id) {
+                out.push_str(&format!("- {} [{}{}]\n", finding.claim, number, pages(finding)));
+            }
+        }
+    }
+    let left_out = left_out(run);
+    if !left_out.is_empty() {
+        out.push_str("\n## Papers left out\n\n");
+        for (paper, error) in left_out {
+            out.push_str(&format!("- {}: {}\n", paper.reference, error));
+        }
+    }
+    out.push_str("\n## References\n\n");
+    for (i, paper) in run.reviewed_papers().enumerate() {
+        out.push_str(&format!("{}. {}\n", i + 1, paper.reference));
+    }
+    out
+}
+
+fn render_latex(run: &ReviewRun) -> String {
+    let mut out = String::from("\\documentclass{article}\n\\begin{document}\n\n");
+    out.push_str(&format!(
+        "\\section*{{Literature review}}\n\n\\emph{{{}}}\n",
+        escape_latex(&run.question)
+    ));
+    if let Some(overview) = &run.overview {
+        out.push_str(&format!("\n{}\n", escape_latex(overview)));
+    }
+    for theme in &run.themes {
+        out.push_str(&format!(
+            "\n\\subsection*{{{}}}\n\n{}\n",
+            escape_latex(&theme.name),
+            escape_latex(&theme.summary)
+        ));
+        let items: Vec<String> = theme
+            .findings
+            .iter()
+            .filter_map(|id| source(run, id))
+            .map(|(number, paper, finding)| {
+                let pages = pages(finding);
+                let location = pages.trim_start_matches(", ");
+                let cite = match location {
+                    "" => format!("\\cite{{{}}}", latex_key(number, paper)),
+                    location => format!("\\cite[{}]{{{}}}", location, latex_key(number, paper)),
+                };
+                format!("  \\item {} {}\n", escape_latex(&finding.claim), cite)
+            })
+            .collect();
+        if !items.is_empty() {
+            out.push_str(&format!("\n\\begin{{itemize}}\n{}\\end{{itemize}}\n", items.concat()));
+        }
+    }
+    let left_out: Vec<String> = left_out(run)
+        .into_iter()
+        .map(|(paper, error)| format!("  \\item {}: {}\n", escape_latex(&paper.reference), escape_latex(error)))
+        .collect();
+    if !left_out.is_empty() {
+        out.push_str(&format!(
+            "\n\\subsection*{{Papers left out}}\n\n\\begin{{itemize}}\n{}\\end{{itemize}}\n",
+            left_out.concat()
+        ));
+    }
+    out.push_str(&format!("\n\\begin{{thebibliography}}{{{}}}\n", run.reviewed_papers().count()));
+    for (i, paper) in run.reviewed_papers().enumerate() {
+        out.push_str(&format!(
+            "\\bibitem{{{}}} {}\n",
+            latex_key(i + 1, paper),
+            escape_latex(&paper.reference)
+        ));
+    }
+    out.push_str("\\end{thebibliography}\n\n\\end{document}\n");
+    out
+}
@@ -0,0 +1,161 @@
+use crate::jobs::{JobRecord, JobStore};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Latex,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Latex => "tex",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::Latex => "LaTeX",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStage {
+    Extracting,
+    Clustering,
+    Writing,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    // "<paper number>.<finding number>", used to assign findings to themes
+    pub id: String,
+    pub claim: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+    #[serde(default)]
+    pub pages: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperFindings {
+    pub document: String,
+    pub title: Option<String>,
+    // Reference-list line, from the library when the paper is linked to an entry
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cite_key: Option<String>,
+    pub findings: Vec<Finding>,
+    // Why the paper couldn't be read or extracted; such papers are left out of themes and references
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PaperFindings {
+    pub fn failed(document: &str, error: String) -> Self {
+        let name = Path::new(document)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| document.to_string());
+        Self {
+            document: document.to_string(),
+            title: None,
+            reference: name,
+            cite_key: None,
+            findings: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Theme {
+    pub name: String,
+    pub summary: String,
+    pub findings: Vec<String>,
+}
+
+// A review job and everything it has produced so far; saved after every step so it can resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRun {
+    pub id: String,
+    pub question: String,
+    pub documents: Vec<String>,
+    pub format: ReportFormat,
+    pub stage: ReviewStage,
+    #[serde(default)]
+    pub papers: Vec<PaperFindings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub themes: Vec<Theme>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewSummary {
+    pub id: String,
+    pub question: String,
+    pub stage: ReviewStage,
+    pub document_count: usize,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<&ReviewRun> for ReviewSummary {
+    fn from(run: &ReviewRun) -> Self {
+        Self {
+            id: run.id.clone(),
+            question: run.question.clone(),
+            stage: run.stage,
+            document_count: run.documents.len(),
+            error: run.error.clone(),
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+impl ReviewRun {
+    pub fn reviewed_papers(&self) -> impl Iterator<Item = &PaperFindings> {
+        self.papers.iter().filter(|p| p.error.is_none())
+    }
+}
+
+impl JobRecord for ReviewRun {
+    const NOUN: &'static str = "Review";
+    type Summary = ReviewSummary;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    fn set_updated_at(&mut self, timestamp: i64) {
+        self.updated_at = timestamp;
+    }
+}
+
+// One JSON file per run under the reviews directory
+pub type ReviewStore = JobStore<ReviewRun>;
@@ -0,0 +1,123 @@
+use super::*;
+
+const RUN: &str = "7f1c2a9e-3b4d-4e5f-8a6b-9c0d1e2f3a4b";
+
+fn finding(id: &str, claim: &str, pages: Vec<u32>) -> Finding {
+    Finding {
+        id: id.to_string(),
+        claim: claim.to_string(),
+        evidence: None,
+        pages,
+    }
+}
+
+fn run(format: ReportFormat) -> ReviewRun {
+    let papers = vec![
+        PaperFindings {
+            document: "/papers/svi.pdf".to_string(),
+            title: Some("Stochastic Variational Inference".to_string()),
+            reference: "Hoffman, Matthew D. (2013). Stochastic Variational Inference.".to_string(),
+            cite_key: Some("hoffman2013".to_string()),
+            findings: vec![
+                finding("1.1", "SVI scales to millions of documents", vec![7]),
+                finding("1.2", "Step sizes must decay", vec![]),
+            ],
+            error: None,
+        },
+        PaperFindings {
+            document: "/papers/amortized.pdf".to_string(),
+            title: None,
+            reference: "amortized.pdf (amortized.pdf)".to_string(),
+            cite_key: None,
+            findings: vec![finding("2.1", "Amortization leaves a gap of 5% in the ELBO", vec![3, 4])],
+            error: None,
+        },
+    ];
+    let themes = settle_themes(
+        &papers,
+        vec![
+            Theme {
+                name: "Scalability".to_string(),
+                summary: "Stochastic methods scale.".to_string(),
+                findings: vec!["1.1".to_string(), "9.9".to_string(), "2.1".to_string()],
+            },
+            Theme {
+                name: "Duplicates".to_string(),
+                summary: "Only repeats.".to_string(),
+                findings: vec!["1.1".to_string()],
+            },
+        ],
+    );
+    ReviewRun {
+        id: RUN.to_string(),
+        question: "How does variational inference scale?".to_string(),
+        documents: papers.iter().map(|p| p.document.clone()).collect(),
+        format,
+        stage: ReviewStage::Writing,
+        papers,
+        overview: Some("It scales well.".to_string()),
+        themes,
+        report: None,
+        session_id: None,
+        error: None,
+        created_at: 0,
+        updated_at: 0,
+    }
+}
+
+#[test]
+fn themes_keep_each_known_finding_once() {
+    let themes = run(ReportFormat::Markdown).themes;
+    let names: Vec<&str> = themes.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["Scalability", "Other findings"]);
+    assert_eq!(themes[0].findings, ["1.1", "2.1"]);
+    assert_eq!(themes[1].findings, ["1.2"]);
+}
+
+#[test]
+fn reports_cite_papers_with_pages() {
+    let markdown = render_report(&run(ReportFormat::Markdown));
+    assert!(markdown.contains("## Scalability\n\nStochastic methods scale.\n\n- SVI scales to millions of documents [1, p. 7]\n"));
+    assert!(markdown.contains("- Amortization leaves a gap of 5% in the ELBO [2, pp. 3, 4]\n"));
+    assert!(markdown.ends_with("## References\n\n1. Hoffman, Matthew D. (2013). Stochastic Variational Inference.\n2. amortized.pdf (amortized.pdf)\n"));
+
+    let latex = render_report(&run(ReportFormat::Latex));
+    assert!(latex.contains("\\item SVI scales to millions of documents \\cite[p. 7]{hoffman2013}"));
+    assert!(latex.contains("gap of 5\\% in the ELBO \\cite[pp. 3, 4]{ref2}"));
+    assert!(latex.contains("\\bibitem{ref2} amortized.pdf (amortized.pdf)"));
+}
+
+#[test]
+fn papers_that_failed_are_left_out_of_citations() {
+    let mut review = run(ReportFormat::Markdown);
+    review
+        .papers
+        .insert(1, PaperFindings::failed("/papers/scanned.pdf", "No text found".to_string()));
+    assert_eq!(review.reviewed_papers().count(), 2);
+
+    let markdown = render_report(&review);
+    assert!(markdown.contains("- Amortization leaves a gap of 5% in the ELBO [2, pp. 3, 4]\n"));
+    assert!(markdown.contains("## Papers left out\n\n- scanned.pdf: No text found\n"));
+    assert!(markdown.ends_with("1. Hoffman, Matthew D. (2013). Stochastic Variational Inference.\n2. amortized.pdf (amortized.pdf)\n"));
+
+    review.format = ReportFormat::Latex;
+    let latex = render_report(&review);
+    assert!(latex.contains("\\begin{thebibliography}{2}"));
+    assert!(latex.contains("\\item scanned.pdf: No text found"));
+    assert!(!latex.contains("\\bibitem{ref2} scanned.pdf"));
+}
+
+#[test]
+fn runs_are_saved_and_listed() {
+    let dir = std::env::temp_dir().join(format!("ccru-reviews-{}", crate::chat::new_id()));
+    let store = ReviewStore::new(dir.clone());
+    let mut saved = run(ReportFormat::Markdown);
+    store.save(&mut saved).unwrap();
+
+    let loaded = store.load(RUN).unwrap();
+    assert_eq!(loaded.papers.len(), 2);
+    assert_eq!(loaded.themes.len(), 2);
+    assert_eq!(store.list()[0].document_count, 2);
+    assert!(store.load("missing").is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
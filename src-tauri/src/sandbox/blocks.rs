@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodeBlock {
+    // The info string after the opening fence, lowercased; empty when there is none
+    pub language: String,
+    pub code: String,
+}
+
+impl CodeBlock {
+    pub fn is_python(&self) -> bool {
+        matches!(self.language.as_str(), "python" | "py" | "python3")
+    }
+}
+
+// Fenced code blocks in Markdown, in order; an unclosed fence runs to the end of the text
+pub fn code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, String, Vec<&str>)> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        match &mut open {
+            Some((fence, _, lines)) => {
+                if trimmed.starts_with(fence.as_str()) && trimmed.trim_end().chars().all(|c| c == '`' || c == '~') {
+                    let (_, language, lines) = open.take().unwrap();
+                    blocks.push(CodeBlock {
+                        language,
+                        code: lines.join("\n"),
+                    });
+                } else {
+                    lines.push(line);
+                }
+            }
+            None => {
+                let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+                let Some(marker) = marker else {
+                    continue;
+                };
+                let fence: String = trimmed.chars().take_while(|c| *c == marker).collect();
+                if fence.len() >= 3 {
+                    let info = trimmed[fence.len()..].trim();
+                    let language = info.split_whitespace().next().unwrap_or("").to_lowercase();
+                    open = Some((fence, language, Vec::new()));
+                }
+            }
+        }
+    }
+    if let Some((_, language, lines)) = open {
+        blocks.push(CodeBlock {
+            language,
+            code: lines.join("\n"),
+        });
+    }
+    blocks
+}
@@ -0,0 +1,67 @@
+use crate::chat::ChatHistory;
+use crate::sandbox::{code_blocks, CodeRunner, ExecutionResult};
+use crate::AppState;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+async fn run_python(runner: Arc<CodeRunner>, code: String) -> Result<ExecutionResult, String> {
+    tauri::async_runtime::spawn_blocking(move || runner.run_python(&code))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn run_code(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    code: String,
+) -> Result<ExecutionResult, String> {
+    let runner = {
+        let holder = app_state.lock().await;
+        holder.code_runner.clone().ok_or("The configuration is not loaded yet")?
+    };
+    run_python(runner, code).await
+}
+
+// Runs a Python block from an assistant message (the first one unless `block` says which)
+// and attaches the result to the message
+#[tauri::command]
+pub async fn run_code_block(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    session_id: String,
+    message_id: String,
+    block: Option<usize>,
+) -> Result<ExecutionResult, String> {
+    let (runner, chat_history) = {
+        let mut holder = app_state.lock().await;
+        let runner = holder.code_runner.clone().ok_or("The configuration is not loaded yet")?;
+        let chat_history = holder
+            .chat_history
+            .get_or_insert_with(|| Arc::new(ChatHistory::new()))
+            .clone();
+        (runner, chat_history)
+    };
+    let session = chat_history
+        .get_session(&session_id)
+        .await
+        .ok_or("Session not found")?;
+    let message = session
+        .messages
+        .iter()
+        .find(|m| m.id == message_id)
+        .ok_or("Message not found")?;
+    if message.role != "assistant" {
+        return Err("Only code from assistant messages can be run".to_string());
+    }
+    let blocks: Vec<_> = code_blocks(&message.content).into_iter().filter(|b| b.is_python()).collect();
+    let code = blocks
+        .into_iter()
+        .nth(block.unwrap_or(0))
+        .ok_or("The message has no such Python code block")?
+        .code;
+
+    let execution = run_python(runner, code).await?;
+    chat_history
+        .attach_execution(&session_id, &message_id, execution.clone())
+        .await?;
+    Ok(execution)
+}
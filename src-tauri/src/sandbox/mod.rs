@@ -0,0 +1,9 @@
+mod runner;
+pub use runner::*;
+mod blocks;
+pub use blocks::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,46 @@
+# Applies the sandbox limits, then runs the user's code as __main__
+import os
+import runpy
+import sys
+
+
+def limit_resources():
+    try:
+        import resource
+    except ImportError:  # Windows has no resource module
+        return
+    cpu = int(os.environ["CCRU_CPU_SECONDS"])
+    resource.setrlimit(resource.RLIMIT_CPU, (cpu, cpu + 1))
+    memory = int(os.environ["CCRU_MEMORY_BYTES"])
+    try:
+        resource.setrlimit(resource.RLIMIT_AS, (memory, memory))
+    except (ValueError, OSError):  # macOS doesn't enforce RLIMIT_AS
+        pass
+    # Writes past this fail with EFBIG, since Python ignores SIGXFSZ
+    file_bytes = int(os.environ["CCRU_FILE_BYTES"])
+    resource.setrlimit(resource.RLIMIT_FSIZE, (file_bytes, file_bytes))
+
+
+# Only a guard with a clear error; on Linux the interpreter also runs without any network interface
+def block_network():
+    import socket
+
+    def blocked(*args, **kwargs):
+        raise PermissionError("Network access is disabled in the sandbox")
+
+    for name in ("connect", "connect_ex", "bind", "sendto"):
+        setattr(socket.socket, name, blocked)
+    socket.getaddrinfo = blocked
+    socket.create_connection = blocked
+
+
+limit_resources()
+if os.environ.get("CCRU_ALLOW_NETWORK") != "1":
+    block_network()
+for name in ("CCRU_CPU_SECONDS", "CCRU_MEMORY_BYTES", "CCRU_FILE_BYTES", "CCRU_ALLOW_NETWORK"):
+    os.environ.pop(name, None)
+
+script = sys.argv[1]
+sys.argv = sys.argv[1:]
+sys.path[0] = os.getcwd()
+runpy.run_path(script, run_name="__main__")
@@ -0,0 +1,299 @@
+use crate::chat::new_id;
+use crate::config::SandboxConfig;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+const RUNNER: &str = include_str!("runner.py");
+
+// Files the code leaves behind beyond this many are dropped
+const MAX_OUTPUT_FILES: usize = 50;
+
+// Variables passed through to the interpreter so virtualenvs, conda and pyenv keep working; nothing else is
+const KEPT_VARIABLES: [&str; 5] = ["PATH", "LANG", "SYSTEMROOT", "VIRTUAL_ENV", "CONDA_PREFIX"];
+const KEPT_PREFIXES: [&str; 2] = ["PYENV", "LC_"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputFile {
+    // Relative to the working directory the code ran in
+    pub name: String,
+    // Where the file was kept after the run
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionResult {
+    pub id: String,
+    pub language: String,
+    pub code: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    // The limit that stopped the run: "wall-clock" or "cpu-time"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<String>,
+    // Output beyond maxOutputBytes was cut off
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<OutputFile>,
+    pub duration_ms: u64,
+}
+
+impl ExecutionResult {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    // What the model gets to see of the run
+    pub fn report(&self) -> String {
+        let mut report = match (&self.limit, self.exit_code) {
+            (Some(limit), _) => format!("The code was stopped by the {} limit.", limit),
+            _ if self.succeeded() => "The code ran successfully.".to_string(),
+            (None, Some(code)) => format!("The code exited with status {}.", code),
+            (None, None) => "The code was killed.".to_string(),
+        };
+        if !self.stdout.is_empty() {
+            report.push_str(&format!("\n\nstdout:\n```\n{}\n```", self.stdout.trim_end()));
+        }
+        if !self.stderr.is_empty() {
+            report.push_str(&format!("\n\nstderr:\n```\n{}\n```", self.stderr.trim_end()));
+        }
+        if !self.files.is_empty() {
+            let names: Vec<&str> = self.files.iter().map(|f| f.name.as_str()).collect();
+            report.push_str(&format!("\n\nFiles written: {}", names.join(", ")));
+        }
+        report
+    }
+}
+
+// Runs Python as a subprocess in a fresh temporary directory, under the configured limits.
+// Without network access it gets a network namespace of its own where Linux allows one;
+// elsewhere the network is only blocked inside the interpreter.
+// Only the network is isolated: the code can read and write anything the user can, and HOME
+// pointing at the temporary directory just keeps it out of the user's dotfiles by default
+pub struct CodeRunner {
+    pub config: SandboxConfig,
+    // Files written by each run are kept under <output_dir>/<run id>
+    pub output_dir: PathBuf,
+}
+
+fn read_limited(path: &Path, max_bytes: usize) -> (String, bool) {
+    let bytes = fs::read(path).unwrap_or_default();
+    let truncated = bytes.len() > max_bytes;
+    let end = bytes.len().min(max_bytes);
+    (String::from_utf8_lossy(&bytes[..end]).to_string(), truncated)
+}
+
+// Every regular file under `dir`, skipping hidden files and directories and never following symlinks
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_string_lossy().starts_with('.') || entry.file_name() == "__pycache__" {
+            continue;
+        }
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        if kind.is_dir() {
+            collect_files(&entry.path(), files);
+        } else if kind.is_file() {
+            files.push(entry.path());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn cpu_limit_hit(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    // SIGXCPU, sent at the soft limit
+    status.signal() == Some(24)
+}
+
+#[cfg(not(unix))]
+fn cpu_limit_hit(_status: &ExitStatus) -> bool {
+    false
+}
+
+// Whether `unshare` can start the interpreter in a user and network namespace with no way out
+#[cfg(target_os = "linux")]
+pub fn network_isolation() -> bool {
+    static AVAILABLE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let available = Command::new("unshare")
+            .args(["-rn", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !available {
+            log::warn!("unshare -rn isn't available; the sandbox can only block the network inside Python");
+        }
+        available
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn network_isolation() -> bool {
+    false
+}
+
+// Whether the child has exited, without reaping it: an unreaped child keeps its pid, and with it
+// the id of its process group, so kill_group can't hit a group that took the id over
+#[cfg(unix)]
+fn exited(child: &mut Child) -> Result<bool, String> {
+    // SAFETY: waitid only writes into `info`, which is a zeroed siginfo_t owned by this frame
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+    if unsafe { libc::waitid(libc::P_PID, child.id() as libc::id_t, &mut info, flags) } == -1 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    // With WNOHANG and nothing to report, waitid leaves `info` zeroed
+    Ok(info.si_signo != 0)
+}
+
+#[cfg(not(unix))]
+fn exited(child: &mut Child) -> Result<bool, String> {
+    child.try_wait().map(|status| status.is_some()).map_err(|e| e.to_string())
+}
+
+// The code runs in its own process group, so this also stops whatever it started
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    // SAFETY: kill takes no pointers; the group id is the pid of a child that hasn't been reaped yet
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+impl CodeRunner {
+    // Blocks until the code finishes or hits a limit
+    pub fn run_python(&self, code: &str) -> Result<ExecutionResult, String> {
+        let id = new_id();
+        let root = std::env::temp_dir().join(format!("ccru-sandbox-{}", id));
+        let result = self.run_in(&root, &id, code);
+        let _ = fs::remove_dir_all(&root);
+        result
+    }
+
+    fn run_in(&self, root: &Path, id: &str, code: &str) -> Result<ExecutionResult, String> {
+        let work = root.join("work");
+        fs::create_dir_all(&work).map_err(|e| format!("Failed to create the sandbox directory: {}", e))?;
+        let runner = root.join("runner.py");
+        let script = root.join("main.py");
+        fs::write(&runner, RUNNER).map_err(|e| e.to_string())?;
+        fs::write(&script, code).map_err(|e| e.to_string())?;
+        let stdout = File::create(root.join("stdout")).map_err(|e| e.to_string())?;
+        let stderr = File::create(root.join("stderr")).map_err(|e| e.to_string())?;
+
+        let mut command = match !self.config.allow_network && network_isolation() {
+            true => {
+                let mut command = Command::new("unshare");
+                command.args(["-rn", "--"]).arg(&self.config.python);
+                command
+            }
+            false => Command::new(&self.config.python),
+        };
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let max_file_bytes = self.config.max_file_mb * 1024 * 1024;
+        command
+            .arg(&runner)
+            .arg(&script)
+            .current_dir(&work)
+            .env_clear()
+            .envs(std::env::vars().filter(|(name, _)| {
+                KEPT_VARIABLES.contains(&name.as_str()) || KEPT_PREFIXES.iter().any(|p| name.starts_with(p))
+            }))
+            .env("HOME", root)
+            .env("MPLBACKEND", "Agg")
+            .env("MPLCONFIGDIR", root.join("matplotlib"))
+            .env("PYTHONIOENCODING", "utf-8")
+            .env("PYTHONDONTWRITEBYTECODE", "1")
+            .env("OPENBLAS_NUM_THREADS", "1")
+            .env("CCRU_CPU_SECONDS", self.config.cpu_seconds.to_string())
+            .env("CCRU_MEMORY_BYTES", (self.config.memory_mb * 1024 * 1024).to_string())
+            .env("CCRU_FILE_BYTES", max_file_bytes.to_string())
+            .env("CCRU_ALLOW_NETWORK", if self.config.allow_network { "1" } else { "0" })
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr);
+
+        let started = Instant::now();
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", self.config.python, e))?;
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let finished = loop {
+            if exited(&mut child)? {
+                break true;
+            }
+            if started.elapsed() >= timeout {
+                break false;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        // Processes the code left running in the background don't outlive the run either
+        kill_group(&mut child);
+        let status = child.wait().map_err(|e| e.to_string())?;
+        let status = finished.then_some(status);
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let max_bytes = self.config.max_output_bytes;
+        let (stdout, stdout_cut) = read_limited(&root.join("stdout"), max_bytes);
+        let (stderr, stderr_cut) = read_limited(&root.join("stderr"), max_bytes);
+        let limit = match &status {
+            None => Some("wall-clock".to_string()),
+            Some(status) if cpu_limit_hit(status) => Some("cpu-time".to_string()),
+            Some(_) => None,
+        };
+
+        let mut paths = Vec::new();
+        collect_files(&work, &mut paths);
+        let mut files = Vec::new();
+        let mut kept_bytes = 0;
+        for path in paths.into_iter().take(MAX_OUTPUT_FILES) {
+            // Files that would take the run past maxFileMb in total are dropped
+            let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(u64::MAX);
+            if len > max_file_bytes - kept_bytes {
+                continue;
+            }
+            kept_bytes += len;
+            let name = path.strip_prefix(&work).unwrap_or(&path).to_string_lossy().to_string();
+            let kept = self.output_dir.join(id).join(&name);
+            if let Some(parent) = kept.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to keep {}: {}", name, e))?;
+            }
+            let size = fs::copy(&path, &kept).map_err(|e| format!("Failed to keep {}: {}", name, e))?;
+            files.push(OutputFile {
+                name,
+                path: kept.display().to_string(),
+                size,
+            });
+        }
+
+        Ok(ExecutionResult {
+            id: id.to_string(),
+            language: "python".to_string(),
+            code: code.to_string(),
+            exit_code: status.and_then(|s| s.code()),
+            stdout,
+            stderr,
+            limit,
+            truncated: stdout_cut || stderr_cut,
+            files,
+            duration_ms,
+        })
+    }
+}
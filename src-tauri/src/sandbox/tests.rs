@@ -0,0 +1,125 @@
+use super::*;
+use crate::config::SandboxConfig;
+
+fn runner(config: SandboxConfig) -> CodeRunner {
+    CodeRunner {
+        config,
+        output_dir: std::env::temp_dir().join(format!("ccru-sandbox-test-{}", uuid::Uuid::new_v4())),
+    }
+}
+
+#[test]
+fn finds_fenced_code_blocks() {
+    let content = "Let's compute it:\n\n```python\nprint(1 + 1)\n```\n\nOr in shell:\n~~~bash\necho 2\n~~~\n```py\nx = 1";
+    let blocks = code_blocks(content);
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[0].code, "print(1 + 1)");
+    assert!(blocks[0].is_python());
+    assert_eq!(blocks[1].language, "bash");
+    assert!(!blocks[1].is_python());
+    // An unclosed fence runs to the end
+    assert_eq!(blocks[2].code, "x = 1");
+}
+
+#[test]
+fn captures_output_and_files() {
+    let runner = runner(SandboxConfig::default());
+    let code = "import sys\nprint('mean', sum([1, 2, 3]) / 3)\nprint('careful', file=sys.stderr)\n\
+                open('result.csv', 'w').write('a,b\\n1,2\\n')";
+    let result = runner.run_python(code).unwrap();
+
+    assert!(result.succeeded(), "{}", result.stderr);
+    assert_eq!(result.stdout, "mean 2.0\n");
+    assert_eq!(result.stderr, "careful\n");
+    assert_eq!(result.files.len(), 1);
+    assert_eq!(result.files[0].name, "result.csv");
+    assert_eq!(std::fs::read_to_string(&result.files[0].path).unwrap(), "a,b\n1,2\n");
+    assert!(result.report().contains("Files written: result.csv"));
+    let _ = std::fs::remove_dir_all(&runner.output_dir);
+}
+
+#[test]
+fn home_is_the_run_directory() {
+    let runner = runner(SandboxConfig::default());
+    let result = runner.run_python("import os\nprint(os.path.expanduser('~'))").unwrap();
+
+    assert!(result.succeeded(), "{}", result.stderr);
+    assert!(result.stdout.contains(&format!("ccru-sandbox-{}", result.id)), "{}", result.stdout);
+    let _ = std::fs::remove_dir_all(&runner.output_dir);
+}
+
+#[test]
+fn enforces_limits_and_blocks_the_network() {
+    let limited = runner(SandboxConfig {
+        timeout_seconds: 1,
+        max_output_bytes: 100,
+        ..SandboxConfig::default()
+    });
+
+    let slow = limited.run_python("import time\ntime.sleep(10)").unwrap();
+    assert_eq!(slow.limit.as_deref(), Some("wall-clock"));
+    assert!(slow.duration_ms < 5000);
+
+    let noisy = limited.run_python("print('x' * 1000)").unwrap();
+    assert!(noisy.truncated);
+    assert_eq!(noisy.stdout.len(), 100);
+
+    let network = runner(SandboxConfig::default())
+        .run_python("import socket\nsocket.create_connection(('example.com', 80))")
+        .unwrap();
+    assert!(!network.succeeded());
+    assert!(network.stderr.contains("Network access is disabled"), "{}", network.stderr);
+}
+
+#[test]
+fn network_is_cut_off_below_the_interpreter() {
+    if !network_isolation() {
+        return;
+    }
+    // _socket skips the guard the runner puts on the socket module
+    let code = "import _socket\n\
+                s = _socket.socket(_socket.AF_INET, _socket.SOCK_STREAM)\n\
+                s.settimeout(2)\n\
+                s.connect(('1.1.1.1', 80))";
+    let result = runner(SandboxConfig::default()).run_python(code).unwrap();
+    assert!(!result.succeeded());
+    assert!(result.stderr.contains("OSError"), "{}", result.stderr);
+}
+
+#[test]
+fn caps_file_sizes() {
+    let runner = runner(SandboxConfig {
+        max_file_mb: 1,
+        ..SandboxConfig::default()
+    });
+    let code = "open('small.txt', 'w').write('x' * 1000)\n\
+                try:\n    open('big.bin', 'wb').write(b'0' * (2 * 1024 * 1024))\n\
+                except OSError as e:\n    print('refused', e.errno)";
+    let result = runner.run_python(code).unwrap();
+    assert!(result.succeeded(), "{}", result.stderr);
+    assert!(result.stdout.starts_with("refused"), "{}", result.stdout);
+    let kept: u64 = result.files.iter().map(|f| f.size).sum();
+    assert!(kept <= 1024 * 1024);
+    assert!(result.files.iter().any(|f| f.name == "small.txt"));
+    let _ = std::fs::remove_dir_all(&runner.output_dir);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn timeouts_stop_child_processes() {
+    let limited = runner(SandboxConfig {
+        timeout_seconds: 1,
+        ..SandboxConfig::default()
+    });
+    let code = "import subprocess, sys, time\n\
+                child = subprocess.Popen(['sleep', '30'])\n\
+                print(child.pid, flush=True)\n\
+                time.sleep(10)";
+    let result = limited.run_python(code).unwrap();
+    assert_eq!(result.limit.as_deref(), Some("wall-clock"));
+    let pid = result.stdout.trim();
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    // Gone, or a zombie waiting for init to reap it
+    let state = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+    assert!(state.is_empty() || state.contains(") Z "), "{}", state);
+}
@@ -0,0 +1,69 @@
+use crate::chat::ChatHistory;
+use crate::search::{SearchFilters, SearchHit, SemanticMessageIndex};
+use crate::AppState;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+#[tauri::command]
+pub async fn search_messages(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    query: String,
+    filters: Option<SearchFilters>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let mut holder = app_state.lock().await;
+    if holder.chat_history.is_none() {
+        holder.chat_history = Some(Arc::new(ChatHistory::new()));
+    }
+    let chat_history = holder.chat_history.as_ref().unwrap();
+    Ok(chat_history
+        .search(
+            &query,
+            &filters.unwrap_or_default(),
+            limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+        .await)
+}
+
+async fn semantic_index(
+    app_state: &tauri::State<'_, Mutex<AppState>>,
+) -> Result<(Arc<SemanticMessageIndex>, Arc<ChatHistory>), String> {
+    let mut holder = app_state.lock().await;
+    if holder.chat_history.is_none() {
+        holder.chat_history = Some(Arc::new(ChatHistory::new()));
+    }
+    let index = holder
+        .message_vectors
+        .clone()
+        .ok_or_else(|| "Semantic search is not configured; set semantic = true under [search]".to_string())?;
+    Ok((index, holder.chat_history.clone().unwrap()))
+}
+
+// Finds messages across sessions by meaning as well as by keywords
+#[tauri::command]
+pub async fn semantic_search_messages(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    query: String,
+    filters: Option<SearchFilters>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let (index, chat_history) = semantic_index(&app_state).await?;
+    let mut hits = index
+        .search(&query, &filters.unwrap_or_default(), limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+        .await?;
+    chat_history.fill_session_titles(&mut hits).await;
+    Ok(hits)
+}
+
+// Embeds every message that isn't in the semantic index yet; returns how many were added
+#[tauri::command]
+pub async fn index_message_vectors(app_state: tauri::State<'_, Mutex<AppState>>) -> Result<usize, String> {
+    let (index, chat_history) = semantic_index(&app_state).await?;
+    let mut added = 0;
+    for session in chat_history.list_sessions().await {
+        added += index.add_messages(&session.id, &session.messages).await?;
+    }
+    Ok(added)
+}
@@ -0,0 +1,131 @@
+use super::{tokenize, Query};
+use std::collections::HashMap;
+
+// Standard Okapi BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+// Positional inverted index over numbered documents, ranked with BM25
+#[derive(Debug, Default)]
+pub struct InvertedIndex {
+    postings: HashMap<String, HashMap<u32, Vec<u32>>>,
+    lengths: HashMap<u32, u32>,
+    total_length: u64,
+}
+
+impl InvertedIndex {
+    pub fn insert(&mut self, doc: u32, text: &str) {
+        let tokens = tokenize(text);
+        self.total_length += tokens.len() as u64;
+        self.lengths.insert(doc, tokens.len() as u32);
+        for token in tokens {
+            self.postings
+                .entry(token.term)
+                .or_default()
+                .entry(doc)
+                .or_default()
+                .push(token.position);
+        }
+    }
+
+    // Takes the same text the document was inserted with
+    pub fn remove(&mut self, doc: u32, text: &str) {
+        let Some(length) = self.lengths.remove(&doc) else {
+            return;
+        };
+        self.total_length -= length as u64;
+        for token in tokenize(text) {
+            if let Some(docs) = self.postings.get_mut(&token.term) {
+                docs.remove(&doc);
+                if docs.is_empty() {
+                    self.postings.remove(&token.term);
+                }
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    fn score(&self, term: &str, doc: u32) -> f64 {
+        let Some(docs) = self.postings.get(term) else {
+            return 0.0;
+        };
+        let Some(positions) = docs.get(&doc) else {
+            return 0.0;
+        };
+        let n = self.len() as f64;
+        let df = docs.len() as f64;
+        let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+        let tf = positions.len() as f64;
+        let avg_length = self.total_length as f64 / n;
+        let length = self.lengths[&doc] as f64;
+        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / avg_length))
+    }
+
+    fn contains_phrase(&self, doc: u32, phrase: &[String]) -> bool {
+        let positions: Option<Vec<&Vec<u32>>> = phrase
+            .iter()
+            .map(|term| self.postings.get(term)?.get(&doc))
+            .collect();
+        let Some(positions) = positions else {
+            return false;
+        };
+        positions[0].iter().any(|&start| {
+            positions[1..]
+                .iter()
+                .enumerate()
+                .all(|(offset, p)| p.contains(&(start + offset as u32 + 1)))
+        })
+    }
+
+    // Documents containing every query term and phrase, best first
+    pub fn search(&self, query: &Query, accept: impl Fn(u32) -> bool) -> Vec<(u32, f64)> {
+        let required = query.required_terms();
+        let mut lists: Vec<&HashMap<u32, Vec<u32>>> = Vec::new();
+        for term in &required {
+            match self.postings.get(*term) {
+                Some(docs) => lists.push(docs),
+                None => return Vec::new(),
+            }
+        }
+        lists.sort_by_key(|docs| docs.len());
+        let Some((smallest, rest)) = lists.split_first() else {
+            return Vec::new();
+        };
+
+        let mut hits: Vec<(u32, f64)> = smallest
+            .keys()
+            .copied()
+            .filter(|doc| rest.iter().all(|docs| docs.contains_key(doc)))
+            .filter(|doc| accept(*doc))
+            .filter(|doc| query.phrases.iter().all(|p| self.contains_phrase(*doc, p)))
+            .map(|doc| (doc, required.iter().map(|t| self.score(t, doc)).sum()))
+            .collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits
+    }
+
+    // Documents containing any of the terms, best first; for natural-language queries
+    pub fn rank(&self, terms: &[String], accept: impl Fn(u32) -> bool) -> Vec<(u32, f64)> {
+        let mut unique: Vec<&str> = terms.iter().map(String::as_str).collect();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        for term in unique {
+            let Some(docs) = self.postings.get(term) else {
+                continue;
+            };
+            for &doc in docs.keys() {
+                if accept(doc) {
+                    *scores.entry(doc).or_default() += self.score(term, doc);
+                }
+            }
+        }
+        let mut hits: Vec<(u32, f64)> = scores.into_iter().collect();
+        hits.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        hits
+    }
+}
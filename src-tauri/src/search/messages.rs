@@ -0,0 +1,178 @@
+use super::{tokenize, InvertedIndex, Query};
+use crate::chat::{ChatMessage, ChatSession};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Tokens of context kept before the first match, and the approximate snippet length in bytes
+const SNIPPET_LEAD_TOKENS: usize = 8;
+const SNIPPET_LENGTH: usize = 240;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchFilters {
+    // Unix timestamps, inclusive
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub role: Option<String>,
+    pub provider: Option<String>,
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnippetSegment {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub session_title: Option<String>,
+    pub message_id: String,
+    pub role: String,
+    pub provider: Option<String>,
+    pub timestamp: i64,
+    pub score: f64,
+    pub snippet: Vec<SnippetSegment>,
+}
+
+struct IndexedMessage {
+    session_id: String,
+    message_id: String,
+    role: String,
+    provider: Option<String>,
+    timestamp: i64,
+    content: String,
+}
+
+impl IndexedMessage {
+    fn matches(&self, filters: &SearchFilters) -> bool {
+        filters.from.map_or(true, |from| self.timestamp >= from)
+            && filters.to.map_or(true, |to| self.timestamp <= to)
+            && filters.role.as_ref().map_or(true, |role| role.eq_ignore_ascii_case(&self.role))
+            && filters.provider.as_ref().map_or(true, |provider| {
+                self.provider.as_ref().is_some_and(|p| p.eq_ignore_ascii_case(provider))
+            })
+            && filters.session_id.as_ref().map_or(true, |id| *id == self.session_id)
+    }
+}
+
+// Full-text index over the active messages of every chat session
+#[derive(Default)]
+pub struct MessageIndex {
+    index: InvertedIndex,
+    docs: HashMap<u32, IndexedMessage>,
+    next_doc: u32,
+}
+
+impl MessageIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_message(&mut self, session_id: &str, message: &ChatMessage) {
+        let doc = self.next_doc;
+        self.next_doc += 1;
+        self.index.insert(doc, &message.content);
+        self.docs.insert(
+            doc,
+            IndexedMessage {
+                session_id: session_id.to_string(),
+                message_id: message.id.clone(),
+                role: message.role.clone(),
+                provider: message.provider.clone(),
+                timestamp: message.timestamp,
+                content: message.content.clone(),
+            },
+        );
+    }
+
+    pub fn add_session(&mut self, session: &ChatSession) {
+        for message in &session.messages {
+            self.add_message(&session.id, message);
+        }
+    }
+
+    pub fn remove_session(&mut self, session_id: &str) {
+        let docs: Vec<u32> = self
+            .docs
+            .iter()
+            .filter(|(_, m)| m.session_id == session_id)
+            .map(|(doc, _)| *doc)
+            .collect();
+        for doc in docs {
+            let message = self.docs.remove(&doc).unwrap();
+            self.index.remove(doc, &message.content);
+        }
+    }
+
+    // Hits come back without session titles; ChatHistory fills those in
+    pub fn search(&self, query: &str, filters: &SearchFilters, limit: usize) -> Vec<SearchHit> {
+        let query = Query::parse(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        self.index
+            .search(&query, |doc| self.docs[&doc].matches(filters))
+            .into_iter()
+            .take(limit)
+            .map(|(doc, score)| {
+                let message = &self.docs[&doc];
+                SearchHit {
+                    session_id: message.session_id.clone(),
+                    session_title: None,
+                    message_id: message.message_id.clone(),
+                    role: message.role.clone(),
+                    provider: message.provider.clone(),
+                    timestamp: message.timestamp,
+                    score,
+                    snippet: snippet(&message.content, &query),
+                }
+            })
+            .collect()
+    }
+}
+
+// A window of the message around its first match, split into plain and highlighted runs
+pub fn snippet(content: &str, query: &Query) -> Vec<SnippetSegment> {
+    let required = query.required_terms();
+    let tokens = tokenize(content);
+    let first_match = tokens
+        .iter()
+        .position(|t| required.contains(&t.term.as_str()))
+        .unwrap_or(0);
+
+    let lead = first_match.saturating_sub(SNIPPET_LEAD_TOKENS);
+    let start = if lead == 0 { 0 } else { tokens[lead].start };
+    let end = match tokens.iter().rposition(|t| t.end <= start + SNIPPET_LENGTH) {
+        Some(last) if last + 1 < tokens.len() => tokens[last].end,
+        _ => content.len(),
+    };
+
+    let mut segments = Vec::new();
+    let mut push = |text: &str, highlighted: bool| {
+        if !text.is_empty() {
+            segments.push(SnippetSegment {
+                text: text.to_string(),
+                highlighted,
+            });
+        }
+    };
+    if start > 0 {
+        push("…", false);
+    }
+    let mut cursor = start;
+    for token in tokens.iter().filter(|t| t.start >= start && t.end <= end) {
+        if required.contains(&token.term.as_str()) {
+            push(&content[cursor..token.start], false);
+            push(&content[token.start..token.end], true);
+            cursor = token.end;
+        }
+    }
+    push(&content[cursor..end], false);
+    if end < content.len() {
+        push("…", false);
+    }
+
+    segments
+}
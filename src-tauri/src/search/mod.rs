@@ -0,0 +1,15 @@
+mod tokenizer;
+pub use tokenizer::*;
+mod query;
+pub use query::*;
+mod inverted;
+pub use inverted::*;
+mod messages;
+pub use messages::*;
+mod semantic;
+pub use semantic::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
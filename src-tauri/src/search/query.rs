@@ -0,0 +1,38 @@
+use super::terms;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub terms: Vec<String>,
+    pub phrases: Vec<Vec<String>>,
+}
+
+impl Query {
+    // Words outside double quotes are individual terms; quoted text must appear as a phrase
+    pub fn parse(text: &str) -> Self {
+        let mut query = Query::default();
+        for (i, part) in text.split('"').enumerate() {
+            let words = terms(part);
+            if i % 2 == 1 && words.len() > 1 {
+                query.phrases.push(words);
+            } else {
+                query.terms.extend(words);
+            }
+        }
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.phrases.is_empty()
+    }
+
+    // Every distinct term a matching document has to contain
+    pub fn required_terms(&self) -> Vec<&str> {
+        let mut required: Vec<&str> = Vec::new();
+        for term in self.terms.iter().chain(self.phrases.iter().flatten()) {
+            if !required.contains(&term.as_str()) {
+                required.push(term);
+            }
+        }
+        required
+    }
+}
@@ -0,0 +1,153 @@
+use super::{snippet, Query, SearchFilters, SearchHit};
+use crate::budget::BudgetedEmbedder;
+use crate::chat::ChatMessage;
+use crate::vector::{normalize, HybridQuery, Metadata, MetadataFilter, VectorCollection, VectorRecord};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+// New messages are written to disk this long after they were indexed, together with any that follow
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+impl SearchFilters {
+    fn matches_metadata(&self, metadata: &Metadata) -> bool {
+        let timestamp: i64 = metadata.get("timestamp").and_then(|t| t.parse().ok()).unwrap_or(0);
+        let field = |key: &str| metadata.get(key).map(String::as_str);
+        self.from.map_or(true, |from| timestamp >= from)
+            && self.to.map_or(true, |to| timestamp <= to)
+            && self.role.as_ref().map_or(true, |role| {
+                field("role").is_some_and(|r| r.eq_ignore_ascii_case(role))
+            })
+            && self.provider.as_ref().map_or(true, |provider| {
+                field("provider").is_some_and(|p| p.eq_ignore_ascii_case(provider))
+            })
+            && self.session_id.as_ref().map_or(true, |id| field("session_id") == Some(id))
+    }
+}
+
+// Embeddings of chat messages across sessions, searched together with BM25
+pub struct SemanticMessageIndex {
+    embedder: Arc<BudgetedEmbedder>,
+    path: PathBuf,
+    vectors: Mutex<VectorCollection>,
+    save_pending: AtomicBool,
+}
+
+impl SemanticMessageIndex {
+    pub fn load(embedder: Arc<BudgetedEmbedder>, path: PathBuf) -> Self {
+        let model = embedder.embedding_model().unwrap_or_default().to_string();
+        let vectors = VectorCollection::load(&path, &model);
+        Self {
+            embedder,
+            path,
+            vectors: Mutex::new(vectors),
+            save_pending: AtomicBool::new(false),
+        }
+    }
+
+    // Embeds the messages that aren't indexed yet; returns how many were added
+    pub async fn add_messages(self: &Arc<Self>, session_id: &str, messages: &[ChatMessage]) -> Result<usize, String> {
+        let pending: Vec<&ChatMessage> = {
+            let vectors = self.vectors.lock().await;
+            messages
+                .iter()
+                .filter(|m| !m.content.trim().is_empty() && !vectors.contains(&m.id))
+                .collect()
+        };
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let texts: Vec<String> = pending.iter().map(|m| m.content.clone()).collect();
+        let embeddings = self.embedder.embed(&texts).await.map_err(|e| e.error)?;
+
+        let mut vectors = self.vectors.lock().await;
+        for (message, embedding) in pending.iter().zip(embeddings) {
+            let mut metadata = Metadata::new();
+            metadata.insert("session_id".to_string(), session_id.to_string());
+            metadata.insert("role".to_string(), message.role.clone());
+            metadata.insert("timestamp".to_string(), message.timestamp.to_string());
+            if let Some(provider) = &message.provider {
+                metadata.insert("provider".to_string(), provider.clone());
+            }
+            let record = VectorRecord {
+                id: message.id.clone(),
+                text: message.content.clone(),
+                metadata,
+            };
+            vectors.upsert(record, embedding)?;
+        }
+        if vectors.needs_compaction() {
+            vectors.reindex();
+        }
+        self.schedule_save();
+        Ok(pending.len())
+    }
+
+    // Saving rewrites the whole collection, so it happens once per burst of messages rather than after each
+    fn schedule_save(self: &Arc<Self>) {
+        if self.save_pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let index = self.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            let vectors = index.vectors.lock().await;
+            index.save_pending.store(false, Ordering::SeqCst);
+            if let Err(e) = vectors.save(&index.path) {
+                log::warn!("Failed to save message vectors: {}", e);
+            }
+        });
+    }
+
+    pub async fn remove_session(&self, session_id: &str) -> Result<(), String> {
+        let mut vectors = self.vectors.lock().await;
+        let filter = MetadataFilter([("session_id".to_string(), vec![session_id.to_string()])].into());
+        if vectors.remove_where(&filter) > 0 {
+            vectors.save(&self.path)?;
+        }
+        Ok(())
+    }
+
+    // Hits come back without session titles; ChatHistory fills those in
+    pub async fn search(&self, query: &str, filters: &SearchFilters, limit: usize) -> Result<Vec<SearchHit>, String> {
+        if query.trim().is_empty() || self.vectors.lock().await.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedding = self
+            .embedder
+            .embed(&[query.to_string()])
+            .await
+            .map_err(|e| e.error)?
+            .pop()
+            .ok_or_else(|| "No embedding returned for the query".to_string())?;
+        let embedding = normalize(embedding);
+
+        let hybrid = HybridQuery {
+            text: query,
+            vector: Some(&embedding),
+            top_k: limit,
+            min_score: 0.0,
+        };
+        let parsed = Query::parse(query);
+        let vectors = self.vectors.lock().await;
+        Ok(vectors
+            .search(&hybrid, |record| filters.matches_metadata(&record.metadata))
+            .into_iter()
+            .map(|hit| {
+                let metadata = &hit.record.metadata;
+                SearchHit {
+                    session_id: metadata.get("session_id").cloned().unwrap_or_default(),
+                    session_title: None,
+                    message_id: hit.record.id.clone(),
+                    role: metadata.get("role").cloned().unwrap_or_default(),
+                    provider: metadata.get("provider").cloned(),
+                    timestamp: metadata.get("timestamp").and_then(|t| t.parse().ok()).unwrap_or(0),
+                    score: hit.score,
+                    snippet: snippet(&hit.record.text, &parsed),
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,176 @@
+use super::*;
+use crate::chat::{ChatHistory, ChatMessage, ChatSession};
+
+fn message(id: &str, role: &str, content: &str, timestamp: i64) -> ChatMessage {
+    ChatMessage {
+        id: id.to_string(),
+        parent_id: None,
+        role: role.to_string(),
+        content: content.to_string(),
+        timestamp,
+        provider: (role == "assistant").then(|| "deepseek".to_string()),
+        ..Default::default()
+    }
+}
+
+fn session(id: &str, messages: Vec<ChatMessage>) -> ChatSession {
+    ChatSession {
+        id: id.to_string(),
+        title: Some(format!("Title {}", id)),
+        messages,
+        ..Default::default()
+    }
+}
+
+fn index() -> MessageIndex {
+    let mut index = MessageIndex::new();
+    index.add_session(&session(
+        "vi",
+        vec![
+            message("q1", "user", "Can you explain variational inference?", 100),
+            message(
+                "a1",
+                "assistant",
+                "Variational inference turns posterior inference into optimization. \
+                 Variational methods pick a family and minimize the KL divergence.",
+                110,
+            ),
+        ],
+    ));
+    index.add_session(&session(
+        "mcmc",
+        vec![
+            message("q2", "user", "How does MCMC inference compare?", 200),
+            message("a2", "assistant", "MCMC samples from the posterior instead.", 210),
+        ],
+    ));
+    index
+}
+
+fn ids(hits: &[SearchHit]) -> Vec<&str> {
+    let mut ids: Vec<&str> = hits.iter().map(|h| h.message_id.as_str()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn tokenizer_lowercases_and_tracks_offsets() {
+    let tokens = tokenize("Hello, Wörld-42!");
+    let terms: Vec<&str> = tokens.iter().map(|t| t.term.as_str()).collect();
+    assert_eq!(terms, ["hello", "wörld", "42"]);
+    assert_eq!((tokens[1].start, tokens[1].end), (7, 13));
+    assert_eq!(tokens[2].position, 2);
+}
+
+#[test]
+fn query_separates_terms_and_phrases() {
+    let query = Query::parse(r#"posterior "variational inference" KL"#);
+    assert_eq!(query.terms, ["posterior", "kl"]);
+    assert_eq!(query.phrases, [vec!["variational", "inference"]]);
+}
+
+#[test]
+fn all_terms_must_match_and_rank_by_bm25() {
+    let index = index();
+    assert_eq!(ids(&index.search("inference", &SearchFilters::default(), 10)), ["a1", "q1", "q2"]);
+    // Same term frequency, so the shorter message ranks first
+    let hits = index.search("mcmc", &SearchFilters::default(), 10);
+    assert_eq!(hits[0].message_id, "q2");
+    assert!(hits[0].score > hits[1].score);
+    assert_eq!(ids(&index.search("posterior inference", &SearchFilters::default(), 10)), ["a1"]);
+    assert!(index.search("bayes", &SearchFilters::default(), 10).is_empty());
+}
+
+#[test]
+fn phrases_require_adjacent_terms() {
+    let index = index();
+    let hits = index.search(r#""variational inference""#, &SearchFilters::default(), 10);
+    assert_eq!(ids(&hits), ["a1", "q1"]);
+    let hits = index.search(r#""inference variational""#, &SearchFilters::default(), 10);
+    assert!(hits.is_empty());
+}
+
+#[test]
+fn filters_narrow_results() {
+    let index = index();
+    let by_role = SearchFilters {
+        role: Some("user".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(ids(&index.search("inference", &by_role, 10)), ["q1", "q2"]);
+
+    let by_date = SearchFilters {
+        from: Some(150),
+        ..Default::default()
+    };
+    assert_eq!(ids(&index.search("inference", &by_date, 10)), ["q2"]);
+
+    let by_provider = SearchFilters {
+        provider: Some("DeepSeek".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(ids(&index.search("posterior", &by_provider, 10)), ["a1", "a2"]);
+}
+
+#[test]
+fn snippets_highlight_matches() {
+    let query = Query::parse("posterior");
+    let short = snippet("MCMC samples from the posterior instead.", &query);
+    let highlighted: Vec<&str> = short
+        .iter()
+        .filter(|s| s.highlighted)
+        .map(|s| s.text.as_str())
+        .collect();
+    assert_eq!(highlighted, ["posterior"]);
+    let text: String = short.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(text, "MCMC samples from the posterior instead.");
+
+    let long = format!("{} needle {}", "word ".repeat(100), "word ".repeat(100));
+    let windowed = snippet(&long, &Query::parse("needle"));
+    assert_eq!(windowed.first().unwrap().text, "…");
+    assert_eq!(windowed.last().unwrap().text, "…");
+    assert!(windowed.iter().any(|s| s.highlighted && s.text == "needle"));
+}
+
+#[tokio::test]
+async fn history_keeps_index_in_step() {
+    let history = ChatHistory::new();
+    let session_id = history.create_session(None).await;
+    history
+        .add_message(&session_id, "user", "Tell me about normalizing flows")
+        .await
+        .unwrap();
+
+    let hits = history.search("flows", &SearchFilters::default(), 10).await;
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].session_id, session_id);
+
+    history.delete_session(&session_id).await.unwrap();
+    assert!(history.search("flows", &SearchFilters::default(), 10).await.is_empty());
+}
+
+#[tokio::test]
+async fn message_vectors_are_saved_once_after_a_burst() {
+    let dir = std::env::temp_dir().join(format!("ccru-vectors-{}", uuid::Uuid::new_v4()));
+    let path = dir.join("messages.json");
+    let script: crate::llm_bridge::MockScript = toml::from_str("[fallback]\ncontent = \"ok\"").unwrap();
+    let embedder = std::sync::Arc::new(crate::budget::BudgetedEmbedder {
+        bridge: std::sync::Arc::new(crate::llm_bridge::MockBridge::new(script).unwrap()),
+        provider: "mock".to_string(),
+        budget: None,
+        app: None,
+    });
+    let model = embedder.embedding_model().unwrap_or_default().to_string();
+    let index = std::sync::Arc::new(SemanticMessageIndex::load(embedder, path.clone()));
+
+    let first = [message("m1", "user", "What is an ELBO?", 1)];
+    let second = [message("m2", "assistant", "A lower bound on the evidence.", 2)];
+    assert_eq!(index.add_messages("s1", &first).await.unwrap(), 1);
+    assert_eq!(index.add_messages("s1", &second).await.unwrap(), 1);
+    assert!(!path.exists());
+
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    let saved = crate::vector::VectorCollection::load(&path, &model);
+    assert!(saved.contains("m1") && saved.contains("m2"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
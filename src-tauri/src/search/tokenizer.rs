@@ -0,0 +1,36 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub term: String,
+    // Byte range of the token in the original text
+    pub start: usize,
+    pub end: usize,
+    pub position: u32,
+}
+
+// Lowercased runs of alphanumeric characters; everything else is a separator
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                tokens.push(Token {
+                    term: text[s..i].to_lowercase(),
+                    start: s,
+                    end: i,
+                    position: tokens.len() as u32,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+pub fn terms(text: &str) -> Vec<String> {
+    tokenize(text).into_iter().map(|t| t.term).collect()
+}
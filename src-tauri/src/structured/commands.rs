@@ -0,0 +1,35 @@
+use crate::budget::{complete_with_budget, UsageContext};
+use crate::llm_bridge::{LLMRequest, Prompt};
+use crate::structured::{complete_json, StructuredOutput, DEFAULT_MAX_REPAIRS};
+use crate::AppState;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+// Completes the prompts as JSON conforming to `schema`, repairing invalid replies up to `max_repairs` times
+#[tauri::command]
+pub async fn complete_structured(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    prompts: Vec<Prompt>,
+    schema: Value,
+    max_repairs: Option<usize>,
+) -> Result<StructuredOutput, String> {
+    let (llm_bridge, budget, ctx) = {
+        let holder = app_state.lock().await;
+        let llm_bridge = holder.llm_bridge.clone().ok_or("No LLM provider is configured")?;
+        let ctx = UsageContext {
+            provider: holder.config.as_ref().unwrap().provider().to_string(),
+            model: llm_bridge.model().to_string(),
+            ..Default::default()
+        };
+        (llm_bridge, holder.budget.clone(), ctx)
+    };
+    let request = LLMRequest {
+        messages: prompts,
+        ..Default::default()
+    };
+    complete_json(request, &schema, max_repairs.unwrap_or(DEFAULT_MAX_REPAIRS), |request| {
+        complete_with_budget(&app, llm_bridge.as_ref(), budget.as_deref(), ctx.clone(), request)
+    })
+    .await
+}
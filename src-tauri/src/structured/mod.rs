@@ -0,0 +1,9 @@
+mod validate;
+pub use validate::*;
+mod repair;
+pub use repair::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use super::{parse_reply, SchemaValidator};
+use crate::chat::split_reasoning;
+use crate::llm_bridge::{LLMRequest, LLMResponse, LLMServiceError, Prompt, ResponseFormat};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+// Repair rounds after the first attempt before giving up
+pub const DEFAULT_MAX_REPAIRS: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredOutput {
+    pub value: Value,
+    // Requests it took, the first one included
+    pub attempts: usize,
+}
+
+fn schema_instructions(schema: &Value) -> String {
+    format!(
+        "Reply with only a JSON value that conforms to this JSON schema, without any other text:\n{}",
+        serde_json::to_string_pretty(schema).unwrap_or_default()
+    )
+}
+
+fn repair_prompt(problems: &[String]) -> String {
+    format!(
+        "Your reply does not conform to the schema:\n{}\nReply again with only the corrected JSON.",
+        problems.iter().map(|p| format!("- {}", p)).collect::<Vec<_>>().join("\n")
+    )
+}
+
+// Asks for JSON matching `schema` and, while the reply doesn't, shows the model what was wrong and asks again.
+// `send` makes the actual request, so callers decide on budget accounting
+pub async fn complete_json<F, Fut>(
+    mut request: LLMRequest,
+    schema: &Value,
+    max_repairs: usize,
+    mut send: F,
+) -> Result<StructuredOutput, String>
+where
+    F: FnMut(LLMRequest) -> Fut,
+    Fut: Future<Output = Result<LLMResponse, LLMServiceError>>,
+{
+    let validator = SchemaValidator::new(schema)?;
+    let instructions = schema_instructions(schema);
+    match request.messages.first_mut() {
+        Some(first) if first.role == "system" => {
+            first.content = format!("{}\n\n{}", first.content, instructions);
+        }
+        _ => request.messages.insert(
+            0,
+            Prompt {
+                role: "system".to_string(),
+                content: instructions,
+            },
+        ),
+    }
+    request.format = Some(ResponseFormat::JsonSchema { schema: schema.clone() });
+
+    let mut problems = Vec::new();
+    for attempt in 1..=max_repairs + 1 {
+        let response = send(request.clone()).await.map_err(|e| e.error)?;
+        problems = match parse_reply(&response.content) {
+            Ok(value) => {
+                let errors = validator.errors(&value);
+                if errors.is_empty() {
+                    return Ok(StructuredOutput { value, attempts: attempt });
+                }
+                errors
+            }
+            Err(e) => vec![e],
+        };
+        let (_, answer) = split_reasoning(&response.content);
+        request.messages.push(Prompt {
+            role: "assistant".to_string(),
+            content: answer.trim().to_string(),
+        });
+        request.messages.push(Prompt {
+            role: "user".to_string(),
+            content: repair_prompt(&problems),
+        });
+    }
+    Err(format!(
+        "The reply still didn't conform to the schema after {} repair attempts: {}",
+        max_repairs,
+        problems.join("; ")
+    ))
+}
+
+// Same as `complete_json`, with the schema derived from `T`
+pub async fn complete_typed<T, F, Fut>(request: LLMRequest, max_repairs: usize, send: F) -> Result<T, String>
+where
+    T: JsonSchema + DeserializeOwned,
+    F: FnMut(LLMRequest) -> Fut,
+    Fut: Future<Output = Result<LLMResponse, LLMServiceError>>,
+{
+    let schema = serde_json::to_value(schemars::schema_for!(T)).map_err(|e| e.to_string())?;
+    let output = complete_json(request, &schema, max_repairs, send).await?;
+    serde_json::from_value(output.value).map_err(|e| format!("Failed to read the reply: {}", e))
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::llm_bridge::{LLMBridge, LLMRequest, MockBridge, MockScript, Prompt, ResponseFormat};
+use serde_json::json;
+
+fn schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "dataset": { "type": "string" },
+            "sample_size": { "type": "integer" }
+        },
+        "required": ["dataset", "sample_size"]
+    })
+}
+
+#[test]
+fn finds_json_in_chatty_replies() {
+    let reply = "<think>\nThe paper uses MNIST.\n</think>\n\nHere it is:\n```json\n{\"dataset\": \"MNIST\"}\n```";
+    assert_eq!(parse_reply(reply).unwrap(), json!({ "dataset": "MNIST" }));
+    assert_eq!(parse_reply("[1, 2]").unwrap(), json!([1, 2]));
+    assert!(parse_reply("no JSON here").unwrap_err().contains("not valid JSON"));
+}
+
+#[test]
+fn validation_errors_name_the_offending_field() {
+    let validator = SchemaValidator::new(&schema()).unwrap();
+    assert!(validator.errors(&json!({ "dataset": "MNIST", "sample_size": 60000 })).is_empty());
+
+    let errors = validator.errors(&json!({ "dataset": "MNIST", "sample_size": "60k" }));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("/sample_size: "), "{}", errors[0]);
+    assert!(SchemaValidator::new(&json!({ "type": "no-such-type" })).is_err());
+}
+
+#[tokio::test]
+async fn invalid_replies_are_repaired_with_the_errors() {
+    let script: MockScript = toml::from_str(
+        r#"
+        [[responses]]
+        turn = 0
+        content = '{"dataset": "MNIST", "sample_size": "60k"}'
+
+        [[responses]]
+        turn = 1
+        content = '{"dataset": "MNIST", "sample_size": 60000}'
+        "#,
+    )
+    .unwrap();
+    let bridge = MockBridge::new(script).unwrap();
+    let request = LLMRequest {
+        messages: vec![Prompt {
+            role: "user".to_string(),
+            content: "Which dataset, and how many samples?".to_string(),
+        }],
+        ..Default::default()
+    };
+
+    let output = complete_json(request, &schema(), DEFAULT_MAX_REPAIRS, |r| bridge.complete(r))
+        .await
+        .unwrap();
+    assert_eq!(output.value, json!({ "dataset": "MNIST", "sample_size": 60000 }));
+    assert_eq!(output.attempts, 2);
+
+    let requests = bridge.recorded_requests();
+    assert_eq!(requests[0].messages[0].role, "system");
+    assert!(matches!(requests[0].format, Some(ResponseFormat::JsonSchema { .. })));
+    let repair = &requests[1].messages.last().unwrap().content;
+    assert!(repair.contains("/sample_size"), "{}", repair);
+
+    // Without repairs the first invalid reply is an error
+    let failing = MockBridge::new(toml::from_str("fallback = { content = 'not json' }").unwrap()).unwrap();
+    let err = complete_json(LLMRequest::default(), &schema(), 0, |r| failing.complete(r))
+        .await
+        .unwrap_err();
+    assert!(err.contains("after 0 repair attempts"), "{}", err);
+}
@@ -0,0 +1,48 @@
+use crate::chat::split_reasoning;
+use jsonschema::Validator;
+use serde_json::Value;
+
+// A compiled schema that describes every violation in a way the model can act on
+pub struct SchemaValidator {
+    validator: Validator,
+}
+
+impl SchemaValidator {
+    pub fn new(schema: &Value) -> Result<Self, String> {
+        jsonschema::validator_for(schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| format!("Invalid JSON schema: {}", e))
+    }
+
+    // One line per violation, e.g. `/year: "2018" is not of type "integer"`
+    pub fn errors(&self, value: &Value) -> Vec<String> {
+        self.validator
+            .iter_errors(value)
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                format!("{}: {}", if path.is_empty() { "/" } else { &path }, e)
+            })
+            .collect()
+    }
+}
+
+// The JSON in a reply: the whole answer, or else the outermost object or array in it, so code
+// fences and stray sentences around the JSON don't count as failures
+pub fn parse_reply(content: &str) -> Result<Value, String> {
+    let (_, answer) = split_reasoning(content);
+    let answer = answer.trim();
+    if let Ok(value) = serde_json::from_str(answer) {
+        return Ok(value);
+    }
+    for (open, close) in [('{', '}'), ('[', ']')] {
+        let (Some(start), Some(end)) = (answer.find(open), answer.rfind(close)) else {
+            continue;
+        };
+        if start < end {
+            if let Ok(value) = serde_json::from_str(&answer[start..=end]) {
+                return Ok(value);
+            }
+        }
+    }
+    serde_json::from_str(answer).map_err(|e| format!("The reply is not valid JSON: {}", e))
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+// Oldest entries are dropped beyond this
+const MAX_ENTRIES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSummary {
+    summary: String,
+    used_at: i64,
+}
+
+// Summaries by hash of everything that went into them, so unchanged parts of a document are never summarized twice
+pub struct SummaryCache {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, CachedSummary>>,
+}
+
+pub fn cache_key(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        // Separate the parts so ("ab", "c") and ("a", "bc") differ
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+impl SummaryCache {
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().await;
+        let entry = entries.get_mut(key)?;
+        entry.used_at = chrono::Utc::now().timestamp();
+        Some(entry.summary.clone())
+    }
+
+    pub async fn insert(&self, key: String, summary: String) {
+        let used_at = chrono::Utc::now().timestamp();
+        self.entries.lock().await.insert(key, CachedSummary { summary, used_at });
+    }
+
+    pub async fn save(&self) -> Result<(), String> {
+        let mut entries = self.entries.lock().await;
+        if entries.len() > MAX_ENTRIES {
+            let mut used: Vec<i64> = entries.values().map(|e| e.used_at).collect();
+            used.sort_unstable();
+            let cutoff = used[entries.len() - MAX_ENTRIES];
+            entries.retain(|_, e| e.used_at >= cutoff);
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        }
+        let content = serde_json::to_string(&*entries).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| format!("Failed to save summary cache: {}", e))
+    }
+}
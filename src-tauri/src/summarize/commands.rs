@@ -0,0 +1,34 @@
+use crate::rag::{extract_text, PageText};
+use crate::summarize::{SummaryOptions, SummaryResult, SummaryStyle};
+use crate::AppState;
+use std::path::Path;
+use tokio::sync::Mutex;
+
+// Summarizes either `text` or the document at `path`, in the configured style unless one is given
+#[tauri::command]
+pub async fn summarize_text(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    text: Option<String>,
+    path: Option<String>,
+    style: Option<SummaryStyle>,
+    instructions: Option<String>,
+) -> Result<SummaryResult, String> {
+    let (summarizer, budget) = {
+        let holder = app_state.lock().await;
+        let summarizer = holder.summarizer.clone().ok_or("No LLM provider is configured")?;
+        (summarizer, holder.budget.clone())
+    };
+    let pages = match (text, path) {
+        (Some(text), _) => vec![PageText { page: None, text }],
+        (None, Some(path)) => tauri::async_runtime::spawn_blocking(move || extract_text(Path::new(&path)))
+            .await
+            .map_err(|e| e.to_string())??,
+        (None, None) => return Err("Either text or a path is required".to_string()),
+    };
+    let options = SummaryOptions {
+        style: style.unwrap_or(summarizer.config.style),
+        instructions,
+    };
+    summarizer.summarize(&app, budget.as_deref(), &pages, &options).await
+}
@@ -0,0 +1,198 @@
+use super::{cache_key, split_for_summary, SummaryCache};
+use crate::budget::{complete_with_budget, BudgetTracker, UsageContext};
+use crate::chat::split_reasoning;
+use crate::config::SummarizationConfig;
+use crate::llm_bridge::{LLMBridge, LLMRequest, Prompt};
+use crate::rag::PageText;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummaryStyle {
+    #[default]
+    Paragraphs,
+    Bullets,
+    Outline,
+    // A single paragraph of at most about 200 words
+    Abstract,
+}
+
+impl SummaryStyle {
+    fn instructions(&self) -> &'static str {
+        match self {
+            SummaryStyle::Paragraphs => "Write the summary as a few paragraphs of prose.",
+            SummaryStyle::Bullets => "Write the summary as a Markdown bullet list of the key points.",
+            SummaryStyle::Outline => {
+                "Write the summary as a Markdown outline that follows the document's structure, with a heading per major part."
+            }
+            SummaryStyle::Abstract => "Write the summary as a single abstract-style paragraph of at most 200 words.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryResult {
+    pub summary: String,
+    // Pieces the document was split into, and how many of all summaries came from the cache
+    pub chunks: usize,
+    pub cached: usize,
+    // Rounds of merging needed to get down to one summary
+    pub levels: usize,
+}
+
+// How a summary is wanted: the style plus any extra instructions from the user
+pub struct SummaryOptions {
+    pub style: SummaryStyle,
+    pub instructions: Option<String>,
+}
+
+// Map-reduce summarizer: pieces are summarized in parallel, then merged a few at a time until one is left
+pub struct Summarizer {
+    pub llm_bridge: Arc<dyn LLMBridge>,
+    pub provider: String,
+    pub config: SummarizationConfig,
+    pub cache: Arc<SummaryCache>,
+}
+
+const NOTES_INSTRUCTIONS: &str = "Summarize the following part of a longer document as dense notes for a later summary of the whole. \
+    Keep every claim, result, number, name and definition that matters; leave out repetition and filler.";
+
+const MERGE_INSTRUCTIONS: &str = "The following are summaries of consecutive parts of one document. \
+    Merge them into a single summary, keeping the order of the document and every important claim, result and number.";
+
+// Roughly four characters to a token
+const CHARS_PER_TOKEN: usize = 4;
+
+struct Step<'a> {
+    app: &'a tauri::AppHandle,
+    budget: Option<&'a BudgetTracker>,
+}
+
+impl Summarizer {
+    async fn summarize_one(
+        &self,
+        step: &Step<'_>,
+        instructions: &str,
+        text: &str,
+        cached: &std::sync::atomic::AtomicUsize,
+    ) -> Result<String, String> {
+        let key = cache_key(&[self.llm_bridge.model(), instructions, text]);
+        if let Some(summary) = self.cache.get(&key).await {
+            cached.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            return Ok(summary);
+        }
+
+        let request = LLMRequest {
+            messages: vec![
+                Prompt {
+                    role: "system".to_string(),
+                    content: instructions.to_string(),
+                },
+                Prompt {
+                    role: "user".to_string(),
+                    content: text.to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let ctx = UsageContext {
+            provider: self.provider.clone(),
+            model: self.llm_bridge.model().to_string(),
+            session_id: None,
+            agent_run_id: None,
+        };
+        let response = complete_with_budget(step.app, self.llm_bridge.as_ref(), step.budget, ctx, request)
+            .await
+            .map_err(|e| e.error)?;
+        let (_, summary) = split_reasoning(&response.content);
+        let summary = summary.trim().to_string();
+        self.cache.insert(key, summary.clone()).await;
+        Ok(summary)
+    }
+
+    async fn summarize_all(
+        &self,
+        step: &Step<'_>,
+        instructions: &str,
+        texts: &[String],
+        cached: &std::sync::atomic::AtomicUsize,
+    ) -> Result<Vec<String>, String> {
+        stream::iter(texts)
+            .map(|text| self.summarize_one(step, instructions, text, cached))
+            .buffered(self.config.concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
+    pub async fn summarize(
+        &self,
+        app: &tauri::AppHandle,
+        budget: Option<&BudgetTracker>,
+        pages: &[PageText],
+        options: &SummaryOptions,
+    ) -> Result<SummaryResult, String> {
+        let step = Step { app, budget };
+        let cached = std::sync::atomic::AtomicUsize::new(0);
+        let result = self.summarize_pieces(&step, pages, options, &cached).await;
+        // Pieces summarized before a failure are kept, so a retry doesn't pay for them again
+        self.save_cache().await;
+        result
+    }
+
+    async fn summarize_pieces(
+        &self,
+        step: &Step<'_>,
+        pages: &[PageText],
+        options: &SummaryOptions,
+        cached: &std::sync::atomic::AtomicUsize,
+    ) -> Result<SummaryResult, String> {
+        let mut final_instructions = format!(
+            "Summarize the document given by the user. {}",
+            options.style.instructions()
+        );
+        if let Some(extra) = options.instructions.as_deref().filter(|i| !i.trim().is_empty()) {
+            final_instructions.push_str(&format!(" {}", extra.trim()));
+        }
+
+        let pieces = split_for_summary(pages, self.config.chunk_tokens * CHARS_PER_TOKEN);
+        if pieces.is_empty() {
+            return Err("There is no text to summarize".to_string());
+        }
+        let chunks = pieces.len();
+        if chunks == 1 {
+            let summary = self.summarize_one(step, &final_instructions, &pieces[0], cached).await?;
+            return Ok(SummaryResult {
+                summary,
+                chunks,
+                cached: cached.load(std::sync::atomic::Ordering::Relaxed),
+                levels: 0,
+            });
+        }
+
+        let mut summaries = self.summarize_all(step, NOTES_INSTRUCTIONS, &pieces, cached).await?;
+        let fan_in = self.config.merge_fan_in.max(2);
+        let mut levels = 1;
+        while summaries.len() > fan_in {
+            let groups: Vec<String> = summaries.chunks(fan_in).map(|group| group.join("\n\n---\n\n")).collect();
+            summaries = self.summarize_all(step, MERGE_INSTRUCTIONS, &groups, cached).await?;
+            levels += 1;
+        }
+        let merged = summaries.join("\n\n---\n\n");
+        let instructions = format!("{} The text consists of summaries of consecutive parts of it.", final_instructions);
+        let summary = self.summarize_one(step, &instructions, &merged, cached).await?;
+        Ok(SummaryResult {
+            summary,
+            chunks,
+            cached: cached.load(std::sync::atomic::Ordering::Relaxed),
+            levels,
+        })
+    }
+
+    async fn save_cache(&self) {
+        if let Err(e) = self.cache.save().await {
+            log::warn!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod split;
+pub use split::*;
+mod cache;
+pub use cache::*;
+mod engine;
+pub use engine::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,29 @@
+use crate::paper::parse_structure;
+use crate::rag::{chunk_ranges, PageText};
+
+// Pieces of at most `max_chars`, one per section, with longer sections cut at paragraph or sentence breaks.
+// A piece depends only on its own section, so editing one section leaves the cached notes on the others usable
+pub fn split_for_summary(pages: &[PageText], max_chars: usize) -> Vec<String> {
+    let structure = parse_structure("", pages);
+    let mut pieces = Vec::new();
+    for section in structure.body() {
+        if section.text().trim().is_empty() {
+            continue;
+        }
+        let text = match section.is_front_matter() {
+            true => section.text(),
+            false => format!("## {}\n{}", section.title(), section.text()),
+        };
+        if text.len() <= max_chars {
+            pieces.push(text);
+            continue;
+        }
+        pieces.extend(
+            chunk_ranges(&text, max_chars, 0)
+                .into_iter()
+                .map(|(start, end)| text[start..end].trim().to_string())
+                .filter(|piece| !piece.is_empty()),
+        );
+    }
+    pieces
+}
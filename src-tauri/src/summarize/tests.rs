@@ -0,0 +1,55 @@
+use super::*;
+use crate::rag::PageText;
+
+fn page(text: &str) -> PageText {
+    PageText {
+        page: None,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn splits_each_section_on_its_own() {
+    let method = "An encoder maps each document to its variational parameters. ".repeat(8);
+    let text = format!(
+        "# Notes on inference\n\n## Background\nTopic models are slow to fit.\n\n## Method\n{}\n\n## Results\nIt is fast.\n",
+        method.trim()
+    );
+    let pieces = split_for_summary(&[page(&text)], 300);
+
+    assert!(pieces.iter().all(|p| p.len() <= 300));
+    assert_eq!(pieces[0], "## Background\nTopic models are slow to fit.");
+    assert!(pieces[1].starts_with("## Method"));
+    assert_eq!(pieces.last().unwrap(), "## Results\nIt is fast.");
+    // Nothing from the section that had to be cut is lost
+    let joined = pieces.join(" ");
+    assert_eq!(joined.matches("An encoder maps").count(), 8);
+
+    // Editing one section leaves the pieces of the others as they were
+    let edited = text.replace("Topic models are slow to fit.", "Topic models used to be slow to fit, until now.");
+    let edited_pieces = split_for_summary(&[page(&edited)], 300);
+    assert_eq!(edited_pieces.len(), pieces.len());
+    assert_ne!(edited_pieces[0], pieces[0]);
+    assert_eq!(edited_pieces[1..], pieces[1..]);
+}
+
+#[test]
+fn cache_keys_depend_on_every_part() {
+    let key = cache_key(&["model", "instructions", "text"]);
+    assert_eq!(key, cache_key(&["model", "instructions", "text"]));
+    assert_ne!(key, cache_key(&["other-model", "instructions", "text"]));
+    assert_ne!(cache_key(&["ab", "c"]), cache_key(&["a", "bc"]));
+}
+
+#[tokio::test]
+async fn cache_survives_a_reload() {
+    let path = std::env::temp_dir().join(format!("ccru-summary-cache-{}.json", uuid::Uuid::new_v4()));
+    let cache = SummaryCache::load(path.clone());
+    cache.insert("key".to_string(), "A summary.".to_string()).await;
+    cache.save().await.unwrap();
+
+    let reloaded = SummaryCache::load(path.clone());
+    assert_eq!(reloaded.get("key").await.as_deref(), Some("A summary."));
+    assert_eq!(reloaded.get("missing").await, None);
+    let _ = std::fs::remove_file(path);
+}
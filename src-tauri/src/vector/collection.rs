@@ -0,0 +1,217 @@
+use super::{Hnsw, HnswParams};
+use crate::search::{terms, InvertedIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+// Constant of reciprocal-rank fusion; larger values flatten the difference between ranks
+const RRF_K: f64 = 60.0;
+// Each ranking contributes this many candidates per requested hit before fusion
+const CANDIDATES_PER_HIT: usize = 4;
+
+pub type Metadata = BTreeMap<String, String>;
+
+// Matches records whose metadata has every listed key with one of its listed values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataFilter(pub BTreeMap<String, Vec<String>>);
+
+impl MetadataFilter {
+    pub fn matches(&self, metadata: &Metadata) -> bool {
+        self.0.iter().all(|(key, values)| {
+            metadata
+                .get(key)
+                .is_some_and(|value| values.is_empty() || values.contains(value))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorRecord {
+    pub id: String,
+    pub text: String,
+    pub metadata: Metadata,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HybridHit {
+    pub record: VectorRecord,
+    pub score: f64,
+    pub vector_score: Option<f32>,
+    pub lexical_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HybridQuery<'a> {
+    pub text: &'a str,
+    // Unit vector of the query text; without it only the lexical ranking is used
+    pub vector: Option<&'a [f32]>,
+    pub top_k: usize,
+    // Vector matches below this cosine similarity are dropped before fusion
+    pub min_score: f32,
+}
+
+pub fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+// Records with an HNSW index over their embeddings and a BM25 index over their text.
+// Only records and the graph are persisted; the lexical index is rebuilt on load.
+#[derive(Default, Serialize, Deserialize)]
+pub struct VectorCollection {
+    embedding_model: String,
+    dimensions: Option<usize>,
+    graph: Hnsw,
+    records: HashMap<u32, VectorRecord>,
+    #[serde(skip)]
+    slots: HashMap<String, u32>,
+    #[serde(skip)]
+    lexical: InvertedIndex,
+}
+
+impl VectorCollection {
+    pub fn new(embedding_model: &str) -> Self {
+        Self {
+            embedding_model: embedding_model.to_string(),
+            graph: Hnsw::new(HnswParams::default()),
+            ..Default::default()
+        }
+    }
+
+    // A fresh collection when the file is missing, unreadable or built with another model
+    pub fn load(path: &Path, embedding_model: &str) -> Self {
+        let Some(mut collection) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<VectorCollection>(&content).ok())
+            .filter(|c| c.embedding_model == embedding_model)
+        else {
+            return Self::new(embedding_model);
+        };
+        for (&slot, record) in &collection.records {
+            collection.slots.insert(record.id.clone(), slot);
+            collection.lexical.insert(slot, &record.text);
+        }
+        collection
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("Failed to save vector index: {}", e))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.slots.contains_key(id)
+    }
+
+    // Adds a record or replaces the one with the same id
+    pub fn upsert(&mut self, record: VectorRecord, embedding: Vec<f32>) -> Result<(), String> {
+        match self.dimensions {
+            Some(dimensions) if dimensions != embedding.len() => {
+                return Err(format!(
+                    "Embedding has {} dimensions, the collection expects {}",
+                    embedding.len(),
+                    dimensions
+                ));
+            }
+            _ => self.dimensions = Some(embedding.len()),
+        }
+        self.remove(&record.id);
+        let slot = self.graph.insert(normalize(embedding));
+        self.lexical.insert(slot, &record.text);
+        self.slots.insert(record.id.clone(), slot);
+        self.records.insert(slot, record);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(slot) = self.slots.remove(id) else {
+            return false;
+        };
+        let record = self.records.remove(&slot).unwrap();
+        self.lexical.remove(slot, &record.text);
+        self.graph.delete(slot);
+        true
+    }
+
+    pub fn remove_where(&mut self, filter: &MetadataFilter) -> usize {
+        let ids: Vec<String> = self
+            .records
+            .values()
+            .filter(|r| filter.matches(&r.metadata))
+            .map(|r| r.id.clone())
+            .collect();
+        for id in &ids {
+            self.remove(id);
+        }
+        ids.len()
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.graph.deleted_len() > self.graph.live_len().max(16)
+    }
+
+    // Rebuilds the graph from the live records, dropping tombstones
+    pub fn reindex(&mut self) {
+        let mut graph = Hnsw::new(self.graph.params());
+        let mut records = HashMap::new();
+        let mut lexical = InvertedIndex::default();
+        let mut old: Vec<(u32, VectorRecord)> = self.records.drain().collect();
+        old.sort_by_key(|(slot, _)| *slot);
+        for (old_slot, record) in old {
+            let slot = graph.insert(self.graph.vector(old_slot).to_vec());
+            lexical.insert(slot, &record.text);
+            self.slots.insert(record.id.clone(), slot);
+            records.insert(slot, record);
+        }
+        self.graph = graph;
+        self.records = records;
+        self.lexical = lexical;
+    }
+
+    // Fuses the vector and BM25 rankings with reciprocal-rank fusion
+    pub fn search(&self, query: &HybridQuery, accept: impl Fn(&VectorRecord) -> bool) -> Vec<HybridHit> {
+        let depth = query.top_k * CANDIDATES_PER_HIT;
+        let accept_slot = |slot: u32| self.records.get(&slot).is_some_and(&accept);
+        let mut fused: HashMap<u32, (f64, Option<f32>, Option<f64>)> = HashMap::new();
+
+        if let Some(vector) = query.vector {
+            let hits = self.graph.search(vector, depth, accept_slot);
+            for (rank, (slot, score)) in hits.into_iter().filter(|(_, s)| *s >= query.min_score).enumerate() {
+                let entry = fused.entry(slot).or_default();
+                entry.0 += 1.0 / (RRF_K + rank as f64 + 1.0);
+                entry.1 = Some(score);
+            }
+        }
+
+        let hits = self.lexical.rank(&terms(query.text), accept_slot);
+        for (rank, (slot, score)) in hits.into_iter().take(depth).enumerate() {
+            let entry = fused.entry(slot).or_default();
+            entry.0 += 1.0 / (RRF_K + rank as f64 + 1.0);
+            entry.2 = Some(score);
+        }
+
+        let mut hits: Vec<HybridHit> = fused
+            .into_iter()
+            .map(|(slot, (score, vector_score, lexical_score))| HybridHit {
+                record: self.records[&slot].clone(),
+                score,
+                vector_score,
+                lexical_score,
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.record.id.cmp(&b.record.id)));
+        hits.truncate(query.top_k);
+        hits
+    }
+}
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HnswParams {
+    // Links per node above layer 0; layer 0 keeps twice as many
+    pub m: usize,
+    pub ef_construction: usize,
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    vector: Vec<f32>,
+    // Neighbour slots for each layer the node lives on, layer 0 first
+    layers: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+// Similarity paired with a slot, ordered by similarity
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(other.1.cmp(&self.1))
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// Hierarchical navigable small world graph over unit vectors, scored by dot product.
+// Deleted nodes stay in the graph as tombstones until the index is rebuilt.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hnsw {
+    params: HnswParams,
+    nodes: Vec<Node>,
+    entry: Option<u32>,
+    deleted: usize,
+}
+
+impl Hnsw {
+    pub fn new(params: HnswParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    pub fn params(&self) -> HnswParams {
+        self.params
+    }
+
+    pub fn vector(&self, slot: u32) -> &[f32] {
+        &self.nodes[slot as usize].vector
+    }
+
+    pub fn live_len(&self) -> usize {
+        self.nodes.len() - self.deleted
+    }
+
+    pub fn deleted_len(&self) -> usize {
+        self.deleted
+    }
+
+    // Deterministic level draw, so rebuilding the same vectors gives the same graph
+    fn random_level(&self) -> usize {
+        let mut x = (self.nodes.len() as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        x ^= x >> 31;
+        let uniform = ((x >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.params.m.max(2) as f64).ln();
+        (-uniform.ln() * ml) as usize
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    fn similarity(&self, query: &[f32], slot: u32) -> f32 {
+        dot(query, &self.nodes[slot as usize].vector)
+    }
+
+    // Best-first search of one layer; returns up to `ef` nodes, most similar first
+    fn search_layer(&self, query: &[f32], entry: &[u32], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entry.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = BinaryHeap::new();
+        let mut found: BinaryHeap<Reverse<Scored>> = BinaryHeap::new();
+        for &slot in entry {
+            let scored = Scored(self.similarity(query, slot), slot);
+            candidates.push(scored);
+            found.push(Reverse(scored));
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let worst = found.peek().map(|r| r.0 .0).unwrap_or(f32::NEG_INFINITY);
+            if candidate.0 < worst && found.len() >= ef {
+                break;
+            }
+            let Some(links) = self.nodes[candidate.1 as usize].layers.get(layer) else {
+                continue;
+            };
+            for &neighbour in links {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let scored = Scored(self.similarity(query, neighbour), neighbour);
+                let worst = found.peek().map(|r| r.0 .0).unwrap_or(f32::NEG_INFINITY);
+                if found.len() < ef || scored.0 > worst {
+                    candidates.push(scored);
+                    found.push(Reverse(scored));
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<Scored> = found.into_iter().map(|r| r.0).collect();
+        found.sort_by(|a, b| b.cmp(a));
+        found
+    }
+
+    // Follows the single best link down to `layer`, starting from the entry point
+    fn descend(&self, query: &[f32], to_layer: usize) -> Option<u32> {
+        let mut current = self.entry?;
+        let top = self.nodes[current as usize].layers.len() - 1;
+        for layer in (to_layer + 1..=top).rev() {
+            current = self.search_layer(query, &[current], 1, layer)[0].1;
+        }
+        Some(current)
+    }
+
+    fn prune(&mut self, slot: u32, layer: usize) {
+        let max = self.max_links(layer);
+        let links = &self.nodes[slot as usize].layers[layer];
+        if links.len() <= max {
+            return;
+        }
+        let vector = self.nodes[slot as usize].vector.clone();
+        let mut scored: Vec<Scored> = links.iter().map(|&n| Scored(self.similarity(&vector, n), n)).collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(max);
+        self.nodes[slot as usize].layers[layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+
+    // Adds a unit vector and returns its slot
+    pub fn insert(&mut self, vector: Vec<f32>) -> u32 {
+        let slot = self.nodes.len() as u32;
+        let level = self.random_level();
+        self.nodes.push(Node {
+            vector,
+            layers: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(slot);
+            return slot;
+        };
+        let top = self.nodes[entry as usize].layers.len() - 1;
+        let query = self.nodes[slot as usize].vector.clone();
+        let mut nearest = vec![self.descend(&query, level.min(top)).unwrap()];
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &nearest, self.params.ef_construction, layer);
+            let neighbours: Vec<u32> = found.iter().take(self.max_links(layer)).map(|s| s.1).collect();
+            for &neighbour in &neighbours {
+                self.nodes[neighbour as usize].layers[layer].push(slot);
+                self.prune(neighbour, layer);
+            }
+            self.nodes[slot as usize].layers[layer] = neighbours;
+            nearest = found.into_iter().map(|s| s.1).collect();
+        }
+
+        if level > top {
+            self.entry = Some(slot);
+        }
+        slot
+    }
+
+    pub fn delete(&mut self, slot: u32) -> bool {
+        match self.nodes.get_mut(slot as usize) {
+            Some(node) if !node.deleted => {
+                node.deleted = true;
+                self.deleted += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Up to `k` live nodes accepted by the filter, most similar first
+    pub fn search(&self, query: &[f32], k: usize, accept: impl Fn(u32) -> bool) -> Vec<(u32, f32)> {
+        let Some(entry) = self.descend(query, 0) else {
+            return Vec::new();
+        };
+        let ef = self.params.ef_search.max(k);
+        let mut hits: Vec<(u32, f32)> = self
+            .search_layer(query, &[entry], ef, 0)
+            .into_iter()
+            .filter(|s| !self.nodes[s.1 as usize].deleted && accept(s.1))
+            .map(|s| (s.1, s.0))
+            .take(k)
+            .collect();
+
+        // A selective filter can starve the graph walk; scan the accepted nodes directly instead
+        if hits.len() < k && hits.len() < self.live_len() {
+            let mut scanned: Vec<Scored> = (0..self.nodes.len() as u32)
+                .filter(|&slot| !self.nodes[slot as usize].deleted && accept(slot))
+                .map(|slot| Scored(self.similarity(query, slot), slot))
+                .collect();
+            scanned.sort_by(|a, b| b.cmp(a));
+            hits = scanned.into_iter().take(k).map(|s| (s.1, s.0)).collect();
+        }
+        hits
+    }
+}
@@ -0,0 +1,7 @@
+mod hnsw;
+pub use hnsw::*;
+mod collection;
+pub use collection::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,126 @@
+use super::*;
+
+fn record(id: &str, text: &str, document: &str) -> VectorRecord {
+    VectorRecord {
+        id: id.to_string(),
+        text: text.to_string(),
+        metadata: [("document".to_string(), document.to_string())].into(),
+    }
+}
+
+// Points spread around the unit circle, each a few degrees from the last
+fn circle(i: usize) -> Vec<f32> {
+    let angle = i as f32 * 0.05;
+    vec![angle.cos(), angle.sin()]
+}
+
+#[test]
+fn hnsw_finds_the_nearest_neighbours() {
+    let mut graph = Hnsw::new(HnswParams {
+        m: 4,
+        ef_construction: 32,
+        ef_search: 16,
+    });
+    for i in 0..120 {
+        graph.insert(normalize(circle(i)));
+    }
+
+    let hits = graph.search(&normalize(circle(40)), 3, |_| true);
+    let slots: Vec<u32> = hits.iter().map(|(slot, _)| *slot).collect();
+    assert_eq!(slots[0], 40);
+    assert!(slots.contains(&39) && slots.contains(&41), "unexpected neighbours: {:?}", slots);
+}
+
+#[test]
+fn hnsw_skips_deleted_and_filtered_nodes() {
+    let mut graph = Hnsw::new(HnswParams::default());
+    for i in 0..50 {
+        graph.insert(normalize(circle(i)));
+    }
+    graph.delete(10);
+
+    let hits = graph.search(&normalize(circle(10)), 2, |_| true);
+    assert!(hits.iter().all(|(slot, _)| *slot != 10));
+
+    // Only even slots, so the walk has to fall back on scanning the accepted nodes
+    let hits = graph.search(&normalize(circle(10)), 3, |slot| slot % 2 == 0 && slot > 30);
+    let slots: Vec<u32> = hits.iter().map(|(slot, _)| *slot).collect();
+    assert_eq!(slots, [32, 34, 36]);
+}
+
+#[test]
+fn fusion_rewards_agreement_between_rankings() {
+    let mut collection = VectorCollection::new("test");
+    collection
+        .upsert(record("a", "hamiltonian monte carlo sampling", "mcmc.md"), vec![1.0, 0.0])
+        .unwrap();
+    collection
+        .upsert(record("b", "variational inference with the elbo", "vi.md"), vec![0.9, 0.1])
+        .unwrap();
+    collection
+        .upsert(record("c", "gradient descent on the elbo", "opt.md"), vec![0.0, 1.0])
+        .unwrap();
+
+    let query = HybridQuery {
+        text: "elbo",
+        vector: Some(&normalize(vec![1.0, 0.0])),
+        top_k: 3,
+        min_score: 0.0,
+    };
+    let hits = collection.search(&query, |_| true);
+    let ids: Vec<&str> = hits.iter().map(|h| h.record.id.as_str()).collect();
+    // b is second by vector and matches the term, so it beats a (vector only) and c
+    assert_eq!(ids[0], "b");
+    assert!(hits[0].vector_score.is_some() && hits[0].lexical_score.is_some());
+
+    let filter = MetadataFilter([("document".to_string(), vec!["opt.md".to_string()])].into());
+    let hits = collection.search(&query, |r| filter.matches(&r.metadata));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].record.id, "c");
+}
+
+#[test]
+fn upserts_replace_and_deletes_survive_reindex() {
+    let mut collection = VectorCollection::new("test");
+    collection.upsert(record("a", "first", "x.md"), vec![1.0, 0.0]).unwrap();
+    collection.upsert(record("a", "second", "x.md"), vec![0.0, 1.0]).unwrap();
+    collection.upsert(record("b", "other", "y.md"), vec![1.0, 1.0]).unwrap();
+    assert!(collection.contains("a") && collection.contains("b"));
+    assert!(collection.upsert(record("c", "wrong", "z.md"), vec![1.0]).is_err());
+
+    let filter = MetadataFilter([("document".to_string(), vec!["y.md".to_string()])].into());
+    assert_eq!(collection.remove_where(&filter), 1);
+    collection.reindex();
+
+    let query = HybridQuery {
+        text: "second",
+        vector: Some(&normalize(vec![0.0, 1.0])),
+        top_k: 5,
+        min_score: 0.0,
+    };
+    let hits = collection.search(&query, |_| true);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].record.text, "second");
+}
+
+#[test]
+fn collections_round_trip_through_disk() {
+    let dir = std::env::temp_dir().join(format!("ccru-vectors-{}", crate::chat::new_id()));
+    let path = dir.join("vectors.json");
+    let mut collection = VectorCollection::new("nomic-embed-text");
+    collection.upsert(record("a", "posterior collapse", "vae.md"), vec![0.3, 0.7]).unwrap();
+    collection.save(&path).unwrap();
+
+    let loaded = VectorCollection::load(&path, "nomic-embed-text");
+    assert!(loaded.contains("a"));
+    let query = HybridQuery {
+        text: "posterior",
+        vector: None,
+        top_k: 1,
+        min_score: 0.0,
+    };
+    assert_eq!(loaded.search(&query, |_| true)[0].record.id, "a");
+    // Vectors from another model aren't comparable, so the collection starts over
+    assert!(VectorCollection::load(&path, "other-model").is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,179 @@
+use crate::chat::{render_workspace, save_export, ExportFormat};
+use crate::workspace::{Workspace, WorkspaceSettings, WorkspaceStore};
+use crate::AppState;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::{oneshot, Mutex};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub session_count: usize,
+    pub document_count: usize,
+    pub updated_at: i64,
+}
+
+pub fn workspace_store(holder: &AppState) -> Result<Arc<WorkspaceStore>, String> {
+    holder
+        .workspaces
+        .clone()
+        .ok_or_else(|| "Workspaces are not loaded yet".to_string())
+}
+
+#[tauri::command]
+pub async fn create_workspace(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    name: String,
+    settings: Option<WorkspaceSettings>,
+) -> Result<Workspace, String> {
+    let holder = app_state.lock().await;
+    workspace_store(&holder)?
+        .create(&name, settings.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn update_workspace(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+    settings: WorkspaceSettings,
+) -> Result<Workspace, String> {
+    let holder = app_state.lock().await;
+    workspace_store(&holder)?.update(&workspace_id, settings).await
+}
+
+#[tauri::command]
+pub async fn get_workspace(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+) -> Result<Workspace, String> {
+    let holder = app_state.lock().await;
+    workspace_store(&holder)?
+        .get(&workspace_id)
+        .await
+        .ok_or_else(|| "Workspace not found".to_string())
+}
+
+#[tauri::command]
+pub async fn list_workspaces(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+) -> Result<Vec<WorkspaceSummary>, String> {
+    let holder = app_state.lock().await;
+    let workspaces = workspace_store(&holder)?.list().await;
+    let sessions = match holder.chat_history.as_ref() {
+        Some(chat_history) => chat_history.list_sessions().await,
+        None => Vec::new(),
+    };
+    Ok(workspaces
+        .into_iter()
+        .map(|workspace| WorkspaceSummary {
+            session_count: sessions
+                .iter()
+                .filter(|s| s.workspace_id.as_ref() == Some(&workspace.id))
+                .count(),
+            document_count: workspace.documents.len(),
+            id: workspace.id,
+            name: workspace.name,
+            description: workspace.description,
+            updated_at: workspace.updated_at,
+        })
+        .collect())
+}
+
+// Sessions of a deleted workspace are removed too unless `keep_sessions` is set
+#[tauri::command]
+pub async fn delete_workspace(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+    keep_sessions: Option<bool>,
+) -> Result<(), String> {
+    let holder = app_state.lock().await;
+    workspace_store(&holder)?.delete(&workspace_id).await?;
+    if let Some(chat_history) = holder.chat_history.as_ref() {
+        chat_history
+            .release_workspace(&workspace_id, !keep_sessions.unwrap_or(false))
+            .await;
+    }
+    Ok(())
+}
+
+// Reads a text document into the workspace; opens a file picker when no path is given
+#[tauri::command]
+pub async fn attach_workspace_document(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+    path: Option<String>,
+) -> Result<Option<Workspace>, String> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let (tx, rx) = oneshot::channel();
+            app.dialog().file().pick_file(move |path| {
+                let _ = tx.send(path);
+            });
+            let Some(path) = rx.await.map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            path.into_path().map_err(|e| e.to_string())?.display().to_string()
+        }
+    };
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {} as text: {}", path, e))?;
+    let name = Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    let holder = app_state.lock().await;
+    let workspace = workspace_store(&holder)?
+        .add_document(&workspace_id, &name, Some(path), content)
+        .await?;
+    Ok(Some(workspace))
+}
+
+#[tauri::command]
+pub async fn remove_workspace_document(
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+    document_id: String,
+) -> Result<Workspace, String> {
+    let holder = app_state.lock().await;
+    workspace_store(&holder)?
+        .remove_document(&workspace_id, &document_id)
+        .await
+}
+
+#[tauri::command]
+pub async fn export_workspace(
+    app: tauri::AppHandle,
+    app_state: tauri::State<'_, Mutex<AppState>>,
+    workspace_id: String,
+    format: ExportFormat,
+) -> Result<Option<String>, String> {
+    let (workspace, sessions) = {
+        let holder = app_state.lock().await;
+        let workspace = workspace_store(&holder)?
+            .get(&workspace_id)
+            .await
+            .ok_or_else(|| "Workspace not found".to_string())?;
+        let sessions = match holder.chat_history.as_ref() {
+            Some(chat_history) => chat_history.list_sessions().await,
+            None => Vec::new(),
+        };
+        let sessions: Vec<_> = sessions
+            .into_iter()
+            .filter(|s| s.workspace_id.as_ref() == Some(&workspace_id))
+            .collect();
+        (workspace, sessions)
+    };
+
+    let rendered = render_workspace(&workspace, &sessions, format);
+    save_export(&app, &format!("ccru-workspace-{}", workspace.id), format, rendered).await
+}
@@ -0,0 +1,7 @@
+mod store;
+pub use store::*;
+mod commands;
+pub use commands::*;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,241 @@
+use crate::chat::{new_id, ChatSession};
+use crate::llm_bridge::GenerationParams;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+// Reference documents longer than this are cut off when folded into a session's system prompt
+const MAX_DOCUMENT_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceDocument {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub content: String,
+    pub added_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "GenerationParams::is_default")]
+    pub params: GenerationParams,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub documents: Vec<ReferenceDocument>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+// Editable workspace fields; None leaves a field unchanged and an empty string clears it
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspaceSettings {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub system_prompt: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub params: Option<GenerationParams>,
+}
+
+impl WorkspaceSettings {
+    fn apply(self, workspace: &mut Workspace) {
+        fn non_empty(value: String) -> Option<String> {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        }
+
+        if let Some(name) = self.name.and_then(non_empty) {
+            workspace.name = name;
+        }
+        if let Some(description) = self.description {
+            workspace.description = non_empty(description);
+        }
+        if let Some(system_prompt) = self.system_prompt {
+            workspace.system_prompt = non_empty(system_prompt);
+        }
+        if let Some(provider) = self.provider {
+            workspace.provider = non_empty(provider);
+        }
+        if let Some(model) = self.model {
+            workspace.model = non_empty(model);
+        }
+        if let Some(params) = self.params {
+            workspace.params = params;
+        }
+    }
+}
+
+impl Workspace {
+    // Standing instructions followed by the attached documents
+    pub fn context_prompt(&self) -> Option<String> {
+        let mut parts: Vec<String> = self.system_prompt.iter().cloned().collect();
+        if !self.documents.is_empty() {
+            let mut documents = String::from("Reference documents for this project:");
+            for document in &self.documents {
+                let content = match document.content.char_indices().nth(MAX_DOCUMENT_CHARS) {
+                    Some((end, _)) => format!("{}\n[truncated]", &document.content[..end]),
+                    None => document.content.clone(),
+                };
+                documents.push_str(&format!("\n\n## {}\n\n{}", document.name, content.trim()));
+            }
+            parts.push(documents);
+        }
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+
+    // Sessions keep a snapshot of the workspace as it was when they were created
+    pub fn apply_to(&self, session: &mut ChatSession) {
+        session.workspace_id = Some(self.id.clone());
+        session.system_prompt = self.context_prompt();
+        session.provider = self.provider.clone();
+        session.model = self.model.clone();
+        session.params = self.params;
+    }
+}
+
+pub struct WorkspaceStore {
+    path: PathBuf,
+    workspaces: Mutex<Vec<Workspace>>,
+}
+
+impl WorkspaceStore {
+    pub fn load(path: PathBuf) -> Self {
+        let workspaces = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(workspaces) => Some(workspaces),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable workspace file {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            workspaces: Mutex::new(workspaces),
+        }
+    }
+
+    fn save(&self, workspaces: &[Workspace]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create data directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(workspaces).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| format!("Failed to save workspaces: {}", e))
+    }
+
+    pub async fn list(&self) -> Vec<Workspace> {
+        self.workspaces.lock().await.clone()
+    }
+
+    pub async fn get(&self, workspace_id: &str) -> Option<Workspace> {
+        let workspaces = self.workspaces.lock().await;
+        workspaces.iter().find(|w| w.id == workspace_id).cloned()
+    }
+
+    pub async fn create(&self, name: &str, settings: WorkspaceSettings) -> Result<Workspace, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Workspace name cannot be empty".to_string());
+        }
+        let now = chrono::Utc::now().timestamp();
+        let mut workspace = Workspace {
+            id: new_id(),
+            name: name.to_string(),
+            description: None,
+            system_prompt: None,
+            provider: None,
+            model: None,
+            params: GenerationParams::default(),
+            documents: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        settings.apply(&mut workspace);
+
+        let mut workspaces = self.workspaces.lock().await;
+        workspaces.push(workspace.clone());
+        self.save(&workspaces)?;
+        Ok(workspace)
+    }
+
+    async fn modify(
+        &self,
+        workspace_id: &str,
+        change: impl FnOnce(&mut Workspace) -> Result<(), String>,
+    ) -> Result<Workspace, String> {
+        let mut workspaces = self.workspaces.lock().await;
+        let workspace = workspaces
+            .iter_mut()
+            .find(|w| w.id == workspace_id)
+            .ok_or_else(|| "Workspace not found".to_string())?;
+        change(workspace)?;
+        workspace.updated_at = chrono::Utc::now().timestamp();
+        let workspace = workspace.clone();
+        self.save(&workspaces)?;
+        Ok(workspace)
+    }
+
+    pub async fn update(&self, workspace_id: &str, settings: WorkspaceSettings) -> Result<Workspace, String> {
+        self.modify(workspace_id, |workspace| {
+            settings.apply(workspace);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn add_document(
+        &self,
+        workspace_id: &str,
+        name: &str,
+        path: Option<String>,
+        content: String,
+    ) -> Result<Workspace, String> {
+        self.modify(workspace_id, |workspace| {
+            workspace.documents.push(ReferenceDocument {
+                id: new_id(),
+                name: name.to_string(),
+                path,
+                content,
+                added_at: chrono::Utc::now().timestamp(),
+            });
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn remove_document(&self, workspace_id: &str, document_id: &str) -> Result<Workspace, String> {
+        self.modify(workspace_id, |workspace| {
+            let position = workspace
+                .documents
+                .iter()
+                .position(|d| d.id == document_id)
+                .ok_or_else(|| "Document not found".to_string())?;
+            workspace.documents.remove(position);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete(&self, workspace_id: &str) -> Result<(), String> {
+        let mut workspaces = self.workspaces.lock().await;
+        let position = workspaces
+            .iter()
+            .position(|w| w.id == workspace_id)
+            .ok_or_else(|| "Workspace not found".to_string())?;
+        workspaces.remove(position);
+        self.save(&workspaces)
+    }
+}
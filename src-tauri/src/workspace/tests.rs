@@ -0,0 +1,84 @@
+use super::*;
+use crate::chat::{ChatHistory, ChatSession};
+use crate::llm_bridge::GenerationParams;
+
+fn store() -> WorkspaceStore {
+    WorkspaceStore::load(std::env::temp_dir().join(format!("ccru-workspaces-{}.json", crate::chat::new_id())))
+}
+
+fn settings(value: serde_json::Value) -> WorkspaceSettings {
+    serde_json::from_value(value).unwrap()
+}
+
+#[tokio::test]
+async fn sessions_inherit_the_workspace_as_created() {
+    let store = store();
+    let workspace = store
+        .create(
+            " Thesis ",
+            settings(serde_json::json!({
+                "system_prompt": "Answer as a reviewer.",
+                "provider": "openai",
+                "model": "gpt-4o",
+                "params": { "temperature": 0.3, "max_tokens": 900 },
+            })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(workspace.name, "Thesis");
+    let workspace = store
+        .add_document(&workspace.id, "outline.md", None, "1. Intro\n2. Method".to_string())
+        .await
+        .unwrap();
+
+    let mut session = ChatSession::default();
+    workspace.apply_to(&mut session);
+    assert_eq!(session.workspace_id.as_deref(), Some(workspace.id.as_str()));
+    assert_eq!(
+        session.system_prompt.as_deref(),
+        Some("Answer as a reviewer.\n\nReference documents for this project:\n\n## outline.md\n\n1. Intro\n2. Method")
+    );
+    assert_eq!((session.provider.as_deref(), session.model.as_deref()), (Some("openai"), Some("gpt-4o")));
+    assert_eq!(
+        session.params,
+        GenerationParams {
+            temperature: Some(0.3),
+            max_tokens: Some(900),
+        }
+    );
+
+    // Later changes reach new sessions only; an empty string clears a setting
+    let updated = store
+        .update(&workspace.id, settings(serde_json::json!({ "system_prompt": "", "model": "" })))
+        .await
+        .unwrap();
+    let mut later = ChatSession::default();
+    updated.apply_to(&mut later);
+    assert!(later.system_prompt.as_deref().is_some_and(|p| p.starts_with("Reference documents")));
+    assert_eq!(later.model, None);
+    assert_eq!(session.model.as_deref(), Some("gpt-4o"));
+    assert!(store.create(" ", WorkspaceSettings::default()).await.is_err());
+}
+
+#[tokio::test]
+async fn released_workspaces_keep_or_delete_their_sessions() {
+    let store = store();
+    let kept = store.create("Kept", WorkspaceSettings::default()).await.unwrap();
+    let dropped = store.create("Dropped", WorkspaceSettings::default()).await.unwrap();
+    let history = ChatHistory::new();
+    let kept_session = history.create_session(Some(&kept)).await;
+    let dropped_session = history.create_session(Some(&dropped)).await;
+    let standalone = history.create_session(None).await;
+
+    history.release_workspace(&kept.id, false).await;
+    history.release_workspace(&dropped.id, true).await;
+    let sessions = history.list_sessions().await;
+    let ids: Vec<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, [kept_session.as_str(), standalone.as_str()]);
+    assert!(sessions.iter().all(|s| s.workspace_id.is_none()));
+    assert!(history.get_session(&dropped_session).await.is_none());
+
+    store.delete(&kept.id).await.unwrap();
+    assert_eq!(store.list().await.len(), 1);
+    assert!(store.get(&kept.id).await.is_none());
+}